@@ -4,67 +4,74 @@
 
 pub mod function;
 pub mod operation;
+pub mod cancellation;
+pub mod runner;
+pub(crate) mod timer;
+pub(crate) mod pool;
 
 use std::{
-    any::Any,
-    ops::{Deref, DerefMut},
-    sync::Arc,
     pin::Pin,
     future::Future,
     hash::Hash,
     collections::{ BTreeSet, VecDeque },
     fmt::Display,
-    thread
+    time::Duration
 };
 
 use thiserror::Error;
 
 use crate::{
     graph::{
-        Error as GraphError,
         Graph,
-        GraphAccess,
-        GraphTraits,
-        GraphType,
-        traverser::{
-            Traverser,
-            TraverserAccess,
-            AsyncTraverserTraits,
-            Traversable
-        }
+        Directed,
+        Cyclic
+    },
+    graph_repr::BTreeRepr,
+    traits::{
+        GetNode,
+        GetNodeMut,
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterAdjacent
+    },
+    function_graph::{
+        variable::{ Variable, Variables },
+        function::FunctionResult
     },
-    function_graph::variable::{ Variable, Variables },
-    async_function_graph::operation::{ AsyncOperation, Error as AsyncOperationError }
+    async_function_graph::{
+        operation::{ AsyncOperation, Error as AsyncOperationError },
+        cancellation::CancellationToken
+    }
 };
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Graph Error: {0}")]
-    GraphError( #[from] GraphError),
     #[error("Operation Error: {0}")]
-    OperationError( #[from] AsyncOperationError )
+    OperationError( #[from] AsyncOperationError ),
+    #[error("Operation {id} failed: {source}")]
+    OperationFailed { id: String, source: AsyncOperationError },
+    #[error("Operation not found")]
+    OperationNotFound
 }
 
-#[derive( Debug )]
-pub struct AsyncFn ();
-impl GraphType for AsyncFn {}
-pub type AsyncFnGraph<I, J> = Graph<AsyncFn, I, AsyncOperation<J>, bool>;
-pub type FnTraverser<'a, I, J> = Traverser<'a, I, AsyncOperation<J>, bool, Graph<AsyncFn, I, AsyncOperation<J>, bool>>;
+/// A graph of asynchronous operations. Edges are enabled when `true`; disabled edges are kept but never followed.
+pub type AsyncFnGraph<I, J> = Graph<Directed, Cyclic, BTreeRepr<I, AsyncOperation<J>, bool>>;
 
-impl<'a, I, J> AsyncFnGraph<I, J>
+impl<I, J> AsyncFnGraph<I, J>
 where
-    I: 'a + Clone + Ord + Display,
+    I: Clone + Ord + Display,
     J: 'static + Clone + Ord + Hash + Display
 {
     pub fn generate_dot_to_file( &self, file_name: String ) {
         let mut dot = String::new();
         dot.push_str( "digraph G {\n" );
-        for ( node_id, node_data ) in self.nodes().iter() {
-            node_data.data().variables().iter().for_each( |( _, _ )|
+        for ( node_id, operation ) in self.iter_node_ids() {
+            operation.variables().iter().for_each( |( _, _ )|
                 dot.push_str( &format!( " {} [label=\"{}\"];\n", node_id, node_id ) )
             );
 
-            for ( adj_node_id, edge ) in node_data.adjacencies().iter() {
+            for ( adj_node_id, edge ) in self.iter_adjacent( node_id.clone() ) {
                 if *edge {
                     dot.push_str( &format!( " {} -> {} [label=\"{}\" color=\"blue\"];\n", node_id, adj_node_id, edge ) );
                 } else {
@@ -78,7 +85,7 @@ where
 
     pub fn add_operation<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
     where
-        F: for<'b> Fn(&'b Variables<J>) -> Pin<Box<dyn Future<Output = ()> + Send + 'b>>
+        F: for<'b> Fn(&'b Variables<J>) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'b>>
         + Send
         + Sync
         + 'static,
@@ -86,31 +93,63 @@ where
         self.add_node( id, AsyncOperation::new(
             variables,
             function
-        ))?;
+        ));
+        Ok( () )
+    }
+
+    /// Adds an operation whose function is handed the [`CancellationToken`] of its execution.
+    pub fn add_cancellable_operation<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
+    where
+        F: for<'b> Fn(&'b Variables<J>, &'b CancellationToken) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'b>>
+        + Send
+        + Sync
+        + 'static,
+    {
+        self.add_node( id, AsyncOperation::cancellable(
+            variables,
+            function
+        ));
+        Ok( () )
+    }
+
+    /// Limits how long a [`Runner`](runner::Runner) lets the operation execute, overriding the runner's timeout.
+    pub fn set_timeout( &mut self, id: I, timeout: Option<Duration> ) -> Result<(), Error> {
+        self.node_mut( id ).ok_or( Error::OperationNotFound )?.set_timeout( timeout );
         Ok( () )
     }
 }
 
-impl<'a, I, J> GraphTraits<'a, I, AsyncOperation<J>, bool> for AsyncFnGraph<I, J>
+impl<I, J> AsyncFnGraph<I, J>
 where
-    I: 'a + Clone + Ord,
+    I: Clone + Ord,
     J: 'static + Clone + Ord + Hash
-{}
-
-impl<'a, I, J> AsyncTraverserTraits<'a, AsyncFn, I, AsyncOperation<J>, bool, AsyncFnGraph<I, J>> for FnTraverser<'a, I, J>
-where
-    I: 'a + Clone + Ord,
-    J: 'static + Clone + Ord + Hash,
-    Self: TraverserAccess<'a, AsyncFn, I, AsyncOperation<J>, bool, AsyncFnGraph<I, J>>
 {
-    async fn bfs_step( &'a self, queue: &mut VecDeque<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
-        while let Some(current_id) = queue.pop_front() {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an edge between two operations, failing if either of them is missing.
+    pub fn add_edge( &mut self, id1: I, id2: I, enabled: bool ) -> Result<(), Error> {
+        if self.node( id1.clone() ).is_none() || self.node( id2.clone() ).is_none() {
+            return Err( Error::OperationNotFound );
+        }
+        AddEdge::add_edge( self, id1, id2, enabled );
+        Ok( () )
+    }
+
+    pub(crate) fn enabled_successors( &self, id: &I ) -> Vec<I> {
+        self.iter_adjacent( id.clone() )
+            .filter( |( _, edge )| **edge )
+            .map( |( next_id, _ )| next_id )
+            .collect()
+    }
+
+    fn bfs_step( &self, queue: &mut VecDeque<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
+        while let Some( current_id ) = queue.pop_front() {
             if visited.insert( current_id.clone() ) {
-                if let Some( current_node ) = self.graph().data().get( &current_id ) {
-                    for ( next_id, edge ) in current_node.adjacencies().iter() {
-                        if *edge && !visited.contains( next_id ) {
-                            queue.push_back( next_id.clone() );
-                        }
+                for next_id in self.enabled_successors( &current_id ) {
+                    if !visited.contains( &next_id ) {
+                        queue.push_back( next_id );
                     }
                 }
                 return Some( current_id );
@@ -119,14 +158,12 @@ where
         None
     }
 
-    async fn dfs_step( &'a self, stack: &mut Vec<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
+    fn dfs_step( &self, stack: &mut Vec<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
         while let Some( current_id ) = stack.pop() {
             if visited.insert( current_id.clone() ) {
-                if let Some( current_node ) = self.graph().data().get( &current_id ) {
-                    for ( next_id, edge ) in current_node.adjacencies().iter() {
-                        if *edge && !visited.contains( next_id ) {
-                            stack.push( next_id.clone() );
-                        }
+                for next_id in self.enabled_successors( &current_id ) {
+                    if !visited.contains( &next_id ) {
+                        stack.push( next_id );
                     }
                 }
                 return Some( current_id );
@@ -135,35 +172,101 @@ where
         None
     }
 
-    async fn bfs( &'a self, start: I ) {
+    /// Executes the operations reachable from `start` over enabled edges one at a time in breadth-first order, stopping
+    /// at the first operation that fails.
+    ///
+    pub async fn bfs( &self, start: I ) -> Result<(), Error>
+    where
+        I: Display
+    {
         let mut queue = VecDeque::new();
         let mut visited = BTreeSet::new();
-        queue.push_back( start.clone() );
+        queue.push_back( start );
         while !queue.is_empty() {
-            if let Some( current_id ) = self.bfs_step( &mut queue, &mut visited ).await {
-                if let Some( operation ) = self.graph().data().get_node( current_id ) {
-                    operation.execute().await.unwrap();
+            if let Some( current_id ) = self.bfs_step( &mut queue, &mut visited ) {
+                if let Some( operation ) = self.node( current_id.clone() ) {
+                    operation.execute().await.map_err( |source| Error::OperationFailed { id: current_id.to_string(), source } )?;
                 }
             }
         }
+        Ok( () )
     }
 
-    async fn dfs( &'a self, start: I ) {
+    /// Executes the operations reachable from `start` over enabled edges one at a time in depth-first order, stopping at
+    /// the first operation that fails.
+    ///
+    pub async fn dfs( &self, start: I ) -> Result<(), Error>
+    where
+        I: Display
+    {
         let mut stack = Vec::new();
         let mut visited = BTreeSet::new();
-        stack.push( start.clone() );
+        stack.push( start );
         while !stack.is_empty() {
-            if let Some( current_id ) = self.dfs_step(&mut stack, &mut visited).await {
-                if let Some( operation ) = self.graph().data().get_node( current_id ) {
-                    operation.execute().await.unwrap();
+            if let Some( current_id ) = self.dfs_step( &mut stack, &mut visited ) {
+                if let Some( operation ) = self.node( current_id.clone() ) {
+                    operation.execute().await.map_err( |source| Error::OperationFailed { id: current_id.to_string(), source } )?;
                 }
             }
         }
+        Ok( () )
     }
 }
 
-impl<'a, I, J> Traversable<'a, AsyncFn, I, AsyncOperation<J>, bool> for AsyncFnGraph<I, J>
-where
-    I: 'a + Clone + Ord,
-    J: 'static + Clone + Ord + Hash,
-{}
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        traits::GetNode,
+        function_graph::variable::{ TypedVariable, Error as VariableError },
+        async_function_graph::{
+            AsyncFnGraph,
+            Error,
+            operation::Error as AsyncOperationError,
+            runner::{ Inline, Runner, Status, tests::block_on }
+        }
+    };
+
+    fn failing_graph( ran: &TypedVariable<bool> ) -> AsyncFnGraph<char, char> {
+        let mut graph = AsyncFnGraph::<char, char>::new();
+        graph.add_operation( 'a', [], |variables| Box::pin( async move {
+            variables.get_typed::<i32>( &'x' )?;
+            Ok( () )
+        })).unwrap();
+        graph.add_operation( 'b', [ ( 'r', ran.clone().into() ) ], |variables| Box::pin( async move {
+            *variables.get_typed_mut::<bool>( &'r' )? = true;
+            Ok( () )
+        })).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_failing_async_operation() {
+        let ran = TypedVariable::shared( false );
+        let graph = failing_graph( &ran );
+
+        let error = block_on( graph.bfs( 'a' ) ).unwrap_err();
+        let Error::OperationFailed { id, source: AsyncOperationError::Failed( source ) } = error else {
+            panic!( "Expected a failed operation, found {:?}", error );
+        };
+        assert_eq!( id, "a" );
+        assert!( matches!( source.downcast_ref::<VariableError>(), Some( VariableError::NotFound( _ ) ) ) );
+        assert!( block_on( graph.dfs( 'a' ) ).is_err() );
+        assert!( block_on( graph.node( 'b' ).unwrap().execute() ).is_ok() );
+        assert!( *ran.read() );
+    }
+
+    #[test]
+    fn test_runner_reports_failing_async_operation() {
+        let ran = TypedVariable::shared( false );
+        let graph = Arc::new( failing_graph( &ran ) );
+
+        let report = block_on( Runner::new( Inline ).run( graph, 'a' ) );
+        assert!( !report.is_success() );
+        assert!( report.status( &'a' ).is_some_and( Status::is_failed ) );
+        assert_eq!( report.skipped().collect::<Vec<_>>(), vec![ &'b' ] );
+        assert!( !*ran.read() );
+    }
+}
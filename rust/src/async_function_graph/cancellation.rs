@@ -0,0 +1,101 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    future::{ Future, poll_fn },
+    sync::{ Arc, Mutex, Weak, atomic::{ AtomicBool, Ordering } },
+    task::{ Poll, Waker }
+};
+
+#[derive( Debug, Default )]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+    children: Mutex<Vec<Weak<Inner>>>
+}
+
+impl Inner {
+    fn cancel( &self ) {
+        if self.cancelled.swap( true, Ordering::AcqRel ) {
+            return;
+        }
+        self.wakers.lock().expect( "Failed to acquire waker lock" ).drain( .. ).for_each( Waker::wake );
+        let children = std::mem::take( &mut *self.children.lock().expect( "Failed to acquire children lock" ) );
+        children.iter().filter_map( Weak::upgrade ).for_each( |child| child.cancel() );
+    }
+}
+
+/// A cooperative cancellation signal shared between a run and the operations it executes.
+///
+/// Cancelling a token cancels every token derived from it with [`CancellationToken::child`], but not its parent.
+/// Operations observe cancellation by checking [`CancellationToken::is_cancelled`] or awaiting
+/// [`CancellationToken::cancelled`].
+///
+#[derive( Debug, Clone, Default )]
+pub struct CancellationToken( Arc<Inner> );
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel( &self ) {
+        self.0.cancel();
+    }
+
+    pub fn is_cancelled( &self ) -> bool {
+        self.0.cancelled.load( Ordering::Acquire )
+    }
+
+    /// A token that is cancelled together with this one, and can also be cancelled on its own.
+    pub fn child( &self ) -> Self {
+        let child = Self::new();
+        let mut children = self.0.children.lock().expect( "Failed to acquire children lock" );
+        children.retain( |weak| weak.strong_count() > 0 );
+        children.push( Arc::downgrade( &child.0 ) );
+        drop( children );
+        if self.is_cancelled() {
+            child.cancel();
+        }
+        child
+    }
+
+    /// Resolves once the token is cancelled.
+    pub fn cancelled( &self ) -> impl Future<Output = ()> + Send + '_ {
+        poll_fn( move |context| {
+            if self.is_cancelled() {
+                return Poll::Ready( () );
+            }
+            let mut wakers = self.0.wakers.lock().expect( "Failed to acquire waker lock" );
+            if !wakers.iter().any( |waker| waker.will_wake( context.waker() ) ) {
+                wakers.push( context.waker().clone() );
+            }
+            drop( wakers );
+            // Cancellation may have happened while the waker was being registered.
+            match self.is_cancelled() {
+                true => Poll::Ready( () ),
+                false => Poll::Pending
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_reaches_children() {
+        let parent = CancellationToken::new();
+        let child = parent.child();
+        let sibling = parent.child();
+
+        child.cancel();
+        assert!( child.is_cancelled() );
+        assert!( !parent.is_cancelled() );
+        assert!( !sibling.is_cancelled() );
+
+        parent.cancel();
+        assert!( sibling.is_cancelled() );
+        assert!( parent.child().is_cancelled() );
+    }
+}
@@ -4,15 +4,40 @@ use std::{
     pin::Pin,
     future::Future,
 };
-use crate::function_graph::variable::Variables;
+use crate::{
+    function_graph::{
+        variable::Variables,
+        function::FunctionResult
+    },
+    async_function_graph::cancellation::CancellationToken
+};
 
 pub trait AsyncFn<I>: Send + Sync {
-    fn call<'a>(&'a self, vars: &'a Variables<I>) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+    fn call<'a>(&'a self, vars: &'a Variables<I>, token: &'a CancellationToken) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>>;
 }
 
 impl<I, T> AsyncFn<I> for T
 where
-    T: for<'a> Fn(&'a Variables<I>) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
+    T: for<'a> Fn(&'a Variables<I>, &'a CancellationToken) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn call<'a>(
+        &'a self,
+        vars: &'a Variables<I>,
+        token: &'a CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>> {
+        (self)(vars, token)
+    }
+}
+
+/// Adapts a function that does not observe cancellation.
+struct Uncancellable<T>(T);
+
+impl<I, T> AsyncFn<I> for Uncancellable<T>
+where
+    T: for<'a> Fn(&'a Variables<I>) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>>
         + Send
         + Sync
         + 'static,
@@ -20,8 +45,9 @@ where
     fn call<'a>(
         &'a self,
         vars: &'a Variables<I>,
-    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
-        (self)(vars)
+        _token: &'a CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>> {
+        (self.0)(vars)
     }
 }
 
@@ -31,7 +57,18 @@ pub struct AsyncFunction<I>(Arc<dyn AsyncFn<I> + 'static>);
 impl<I> AsyncFunction<I> {
     pub fn new<T>( function: T ) -> Self
     where
-        T: for<'a> Fn( &'a Variables<I> ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
+        T: for<'a> Fn( &'a Variables<I> ) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self( Arc::new( Uncancellable( function ) ) )
+    }
+
+    /// A function that is handed the [`CancellationToken`] of its execution, so it can stop early once cancelled.
+    pub fn cancellable<T>( function: T ) -> Self
+    where
+        T: for<'a> Fn( &'a Variables<I>, &'a CancellationToken ) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>>
             + Send
             + Sync
             + 'static,
@@ -42,8 +79,9 @@ impl<I> AsyncFunction<I> {
     pub fn call<'a>(
         &'a self,
         vars: &'a Variables<I>,
-    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
-        self.0.call( vars )
+        token: &'a CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'a>> {
+        self.0.call( vars, token )
     }
 }
 
@@ -66,18 +104,17 @@ impl<I> std::fmt::Debug for AsyncFunction<I> {
     }
 }
 
-async fn async_test_function( vars: &Variables<()> ) {
-    println!( "Hello, world!" );
-}
-
 #[cfg(test)]
 mod tests{
     use super::*;
 
+    async fn async_test_function( _vars: &Variables<()> ) -> FunctionResult {
+        println!( "Hello, world!" );
+        Ok( () )
+    }
+
     #[test]
     fn new_test() {
-        use crate::async_function_graph::function::AsyncFunction;
-        use crate::function_graph::variable::Variables;
-        let function = AsyncFunction::new(|vars| Box::pin(async_test_function(vars)));
+        let _function = AsyncFunction::new(|vars| Box::pin(async_test_function(vars)));
     }
 }
@@ -1,43 +1,92 @@
 use std::{
     any::Any,
     hash::Hash,
-    ops::{Deref, DerefMut},
-    sync::Arc,
+    ops::Deref,
     pin::Pin,
     future::Future,
+    time::Duration
 };
 use thiserror::Error;
 use crate::{
-    function_graph::variable::{Variable, Variables},
-    async_function_graph::function::AsyncFunction,
+    function_graph::{
+        variable::{Variable, Variables},
+        function::{ FunctionError, FunctionResult },
+        operation::Error as OperationError
+    },
+    async_function_graph::{
+        function::AsyncFunction,
+        cancellation::CancellationToken
+    }
 };
 
 #[derive(Error, Debug)]
-pub enum Error {}
+pub enum Error {
+    #[error("Operation failed: {0}")]
+    Failed( FunctionError )
+}
+
+impl From<OperationError> for Error {
+    fn from( error: OperationError ) -> Self {
+        match error {
+            OperationError::Failed( error ) => Self::Failed( error )
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct AsyncOperation<I> {
     variables: Variables<I>,
     function: AsyncFunction<I>,
+    timeout: Option<Duration>
 }
 
-impl<'a, I> AsyncOperation<I>
+impl<I> AsyncOperation<I>
 where
-    I: 'a + Ord + Hash,
+    I: Ord + Hash,
 {
     pub fn new<const N: usize, F>(variables: [(I, Variable); N], function: F) -> Self
     where
-        F: for<'b> Fn(&'b Variables<I>) -> Pin<Box<dyn Future<Output = ()> + Send + 'b>>
+        F: for<'b> Fn(&'b Variables<I>) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'b>>
         + Send
         + Sync
         + 'static,
     {
+        Self::from_function( variables, AsyncFunction::new( function ) )
+    }
+
+    /// An operation whose function is handed the [`CancellationToken`] of its execution.
+    pub fn cancellable<const N: usize, F>(variables: [(I, Variable); N], function: F) -> Self
+    where
+        F: for<'b> Fn(&'b Variables<I>, &'b CancellationToken) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'b>>
+        + Send
+        + Sync
+        + 'static,
+    {
+        Self::from_function( variables, AsyncFunction::cancellable( function ) )
+    }
+
+    fn from_function<const N: usize>( variables: [ ( I, Variable ); N ], function: AsyncFunction<I> ) -> Self {
         Self {
             variables: Variables::new( variables ),
-            function: AsyncFunction::new( function ),
+            function,
+            timeout: None
         }
     }
 
+    pub fn with_timeout( mut self, timeout: Duration ) -> Self {
+        self.timeout = Some( timeout );
+        self
+    }
+
+    /// How long a runner lets the operation execute before cancelling it.
+    pub fn timeout( &self ) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn set_timeout( &mut self, timeout: Option<Duration> ) {
+        self.timeout = timeout;
+    }
+
     pub fn variables( &self ) -> &Variables<I> {
         &self.variables
     }
@@ -55,13 +104,15 @@ where
     }
 
     pub async fn execute( &self ) -> Result<(), Error> {
-        self.function.call( &self.variables ).await;
-        Ok( () )
+        self.execute_with_token( &CancellationToken::new() ).await
+    }
+
+    pub async fn execute_with_token( &self, token: &CancellationToken ) -> Result<(), Error> {
+        self.function.call( &self.variables, token ).await.map_err( Error::Failed )
     }
 
     pub async fn execute_mut( &mut self ) -> Result<(), Error> {
-        self.function.call( &mut self.variables ).await;
-        Ok( () )
+        self.function.call( &self.variables, &CancellationToken::new() ).await.map_err( Error::Failed )
     }
 }
 
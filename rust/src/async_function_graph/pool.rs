@@ -0,0 +1,240 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::VecDeque,
+    num::NonZeroUsize,
+    sync::{ Arc, Condvar, Mutex, Once, OnceLock, atomic::{ AtomicU8, AtomicUsize, Ordering } },
+    thread
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+const QUEUED: u8 = 0;
+const RUNNING: u8 = 1;
+const DONE: u8 = 2;
+/// Dropped before it started, the job is skipped.
+const ABANDONED: u8 = 3;
+/// Dropped while running, a replacement thread has been started and the job's thread exits once it returns.
+const DETACHED: u8 = 4;
+
+/// A set of threads that run blocking jobs in the order they were submitted.
+///
+/// Used by the default [`Spawner::spawn_blocking`](crate::async_function_graph::runner::Spawner::spawn_blocking), so
+/// a run with many blocking operations does not start a thread for each of them. Jobs beyond the number of threads
+/// wait in a queue, so blocking operations must not wait on each other.
+///
+/// A job whose [`Ticket`] is dropped while it runs, e.g. because its operation timed out, no longer counts against
+/// the pool: a replacement thread is started, and the job's own thread exits once the job returns. A job whose ticket
+/// is dropped before it starts is skipped.
+///
+pub(crate) struct BlockingPool {
+    jobs: Mutex<VecDeque<( Arc<AtomicU8>, Job )>>,
+    available: Condvar,
+    started: AtomicUsize,
+    workers: AtomicUsize
+}
+
+/// The claim of a submitted job on its thread, see [`BlockingPool`].
+pub(crate) struct Ticket {
+    pool: &'static BlockingPool,
+    state: Arc<AtomicU8>,
+    armed: bool
+}
+
+impl Ticket {
+    /// Keeps the job on its thread when the ticket is dropped, once its output has been received.
+    pub(crate) fn disarm( &mut self ) {
+        self.armed = false;
+    }
+}
+
+impl Drop for Ticket {
+    fn drop( &mut self ) {
+        if !self.armed || self.state.compare_exchange( QUEUED, ABANDONED, Ordering::AcqRel, Ordering::Acquire ).is_ok() {
+            return;
+        }
+        if self.state.compare_exchange( RUNNING, DETACHED, Ordering::AcqRel, Ordering::Acquire ).is_ok() {
+            self.pool.spawn_worker();
+        }
+    }
+}
+
+impl BlockingPool {
+    fn new() -> Self {
+        Self {
+            jobs: Mutex::new( VecDeque::new() ),
+            available: Condvar::new(),
+            started: AtomicUsize::new( 0 ),
+            workers: AtomicUsize::new( 0 )
+        }
+    }
+
+    /// The pool shared by every spawner, with one thread per available core, started on first use.
+    pub(crate) fn shared() -> &'static Self {
+        static POOL: OnceLock<BlockingPool> = OnceLock::new();
+        static STARTED: Once = Once::new();
+        let pool = POOL.get_or_init( Self::new );
+        STARTED.call_once( || pool.start( Self::size() ) );
+        pool
+    }
+
+    /// The number of threads in the shared pool.
+    pub(crate) fn size() -> usize {
+        thread::available_parallelism().map_or( 4, NonZeroUsize::get )
+    }
+
+    fn start( &'static self, threads: usize ) {
+        for _ in 0..threads {
+            self.spawn_worker();
+        }
+    }
+
+    fn spawn_worker( &'static self ) {
+        let index = self.started.fetch_add( 1, Ordering::Relaxed );
+        self.workers.fetch_add( 1, Ordering::AcqRel );
+        thread::Builder::new()
+            .name( format!( "graphs-blocking-{}", index ) )
+            .spawn( || self.work() )
+            .expect( "Failed to start a blocking thread" );
+    }
+
+    /// The number of threads currently serving the pool, including those finishing a detached job.
+    #[cfg(test)]
+    fn workers( &self ) -> usize {
+        self.workers.load( Ordering::Acquire )
+    }
+
+    pub(crate) fn execute<F>( &'static self, job: F ) -> Ticket
+    where
+        F: 'static + FnOnce() + Send
+    {
+        let state = Arc::new( AtomicU8::new( QUEUED ) );
+        self.jobs.lock().expect( "Failed to acquire job lock" ).push_back( ( state.clone(), Box::new( job ) ) );
+        self.available.notify_one();
+        Ticket { pool: self, state, armed: true }
+    }
+
+    fn work( &self ) {
+        loop {
+            let ( state, job ) = {
+                let mut jobs = self.jobs.lock().expect( "Failed to acquire job lock" );
+                loop {
+                    match jobs.pop_front() {
+                        Some( job ) => break job,
+                        None => jobs = self.available.wait( jobs ).expect( "Failed to acquire job lock" )
+                    }
+                }
+            };
+            if state.compare_exchange( QUEUED, RUNNING, Ordering::AcqRel, Ordering::Acquire ).is_err() {
+                continue;
+            }
+            job();
+            if state.swap( DONE, Ordering::AcqRel ) == DETACHED {
+                self.workers.fetch_sub( 1, Ordering::AcqRel );
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeSet,
+        sync::{ Arc, Mutex, mpsc },
+        thread,
+        time::{ Duration, Instant }
+    };
+
+    use crate::async_function_graph::{
+        pool::{ BlockingPool, Ticket },
+        runner::{ Inline, Spawner, tests::block_on }
+    };
+
+    #[test]
+    fn test_spawn_blocking_reuses_pool_threads() {
+        let threads = Arc::new( Mutex::new( BTreeSet::new() ) );
+        let outputs: Vec<_> = ( 0..BlockingPool::size() * 4 ).map( |index| {
+            let threads = threads.clone();
+            Inline.spawn_blocking( move || {
+                threads.lock().unwrap().insert( format!( "{:?}", thread::current().id() ) );
+                index * 2
+            })
+        }).collect();
+
+        for ( index, output ) in outputs.into_iter().enumerate() {
+            assert_eq!( block_on( output ), index * 2 );
+        }
+        assert!( threads.lock().unwrap().len() <= BlockingPool::size() );
+    }
+
+    fn private_pool( threads: usize ) -> &'static BlockingPool {
+        let pool = Box::leak( Box::new( BlockingPool::new() ) );
+        pool.start( threads );
+        pool
+    }
+
+    /// Submits a job that blocks until the returned sender is used, and waits until it runs.
+    fn blocking_job( pool: &'static BlockingPool ) -> ( Ticket, mpsc::Sender<()> ) {
+        let ( release, released ) = mpsc::channel();
+        let ( started, running ) = mpsc::channel();
+        let ticket = pool.execute( move || {
+            started.send( () ).unwrap();
+            let _ = released.recv();
+        });
+        running.recv_timeout( Duration::from_secs( 5 ) ).expect( "The blocking job did not start" );
+        ( ticket, release )
+    }
+
+    #[test]
+    fn test_detached_job_starts_replacement_thread() {
+        let pool = private_pool( 1 );
+        let ( ticket, release ) = blocking_job( pool );
+
+        let ( done, finished ) = mpsc::channel();
+        let mut next = pool.execute( move || done.send( () ).unwrap() );
+        assert!( finished.recv_timeout( Duration::from_millis( 50 ) ).is_err() );
+
+        // Dropping the ticket of the running job, as a timed out operation does, frees its place in the pool.
+        drop( ticket );
+        finished.recv_timeout( Duration::from_secs( 5 ) ).expect( "The queued job did not run" );
+        next.disarm();
+        assert_eq!( pool.workers(), 2 );
+
+        release.send( () ).unwrap();
+        let deadline = Instant::now() + Duration::from_secs( 5 );
+        while pool.workers() > 1 && Instant::now() < deadline {
+            thread::sleep( Duration::from_millis( 1 ) );
+        }
+        assert_eq!( pool.workers(), 1 );
+    }
+
+    #[test]
+    fn test_abandoned_job_is_skipped() {
+        let pool = private_pool( 1 );
+        let ( mut ticket, release ) = blocking_job( pool );
+        ticket.disarm();
+
+        let ran = Arc::new( Mutex::new( Vec::new() ) );
+        let abandoned = {
+            let ran = ran.clone();
+            pool.execute( move || ran.lock().unwrap().push( "abandoned" ) )
+        };
+        let ( done, finished ) = mpsc::channel();
+        let mut next = {
+            let ran = ran.clone();
+            pool.execute( move || {
+                ran.lock().unwrap().push( "next" );
+                done.send( () ).unwrap();
+            })
+        };
+        drop( abandoned );
+        drop( ticket );
+        release.send( () ).unwrap();
+
+        finished.recv_timeout( Duration::from_secs( 5 ) ).expect( "The queued job did not run" );
+        next.disarm();
+        assert_eq!( *ran.lock().unwrap(), vec![ "next" ] );
+        assert_eq!( pool.workers(), 1 );
+    }
+}
@@ -0,0 +1,587 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::{ BTreeMap, VecDeque },
+    future::{ Future, poll_fn },
+    hash::Hash,
+    panic::{ self, AssertUnwindSafe },
+    pin::{ Pin, pin },
+    sync::{ Arc, Mutex },
+    task::{ Poll, Waker },
+    time::Duration
+};
+
+use crate::{
+    traits::GetNode,
+    function_graph::{
+        variable::Variables,
+        schedule::Schedule,
+        trace::{ Probe, Trace }
+    },
+    async_function_graph::{
+        AsyncFnGraph,
+        operation::Error as AsyncOperationError,
+        cancellation::CancellationToken,
+        timer::Timer,
+        pool::BlockingPool
+    }
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Called by [`Runnable::launch`] where the operation starts executing, on the thread that executes it.
+pub type Started = Box<dyn FnOnce() + Send>;
+
+/// Starts futures on an executor.
+///
+/// This keeps the [`Runner`] independent of any particular async runtime. A Tokio spawner, for example, wraps
+/// `tokio::spawn` and maps the `JoinHandle` back to the future's output.
+///
+pub trait Spawner {
+    /// Starts `future` and returns a future that resolves to its output.
+    fn spawn<T>( &self, future: BoxFuture<'static, T> ) -> BoxFuture<'static, T>
+    where
+        T: 'static + Send;
+
+    /// Runs `function` where it may block, and returns a future that resolves to its output.
+    ///
+    /// The default implementation runs it on a pool with one thread per available core, shared by all spawners. When
+    /// the returned future is dropped while `function` runs, e.g. on a timeout, the pool starts a replacement thread.
+    /// Spawners for runtimes with a blocking pool should override it.
+    ///
+    fn spawn_blocking<F, T>( &self, function: F ) -> BoxFuture<'static, T>
+    where
+        F: 'static + FnOnce() -> T + Send,
+        T: 'static + Send
+    {
+        let slot = Arc::new( Mutex::new( ( None, None::<Waker> ) ) );
+        let thread_slot = slot.clone();
+        let mut ticket = BlockingPool::shared().execute( move || {
+            let output = panic::catch_unwind( AssertUnwindSafe( function ) );
+            let mut slot = thread_slot.lock().expect( "Failed to acquire output lock" );
+            slot.0 = Some( output );
+            if let Some( waker ) = slot.1.take() {
+                waker.wake();
+            }
+        });
+        Box::pin( poll_fn( move |context| {
+            let mut slot = slot.lock().expect( "Failed to acquire output lock" );
+            let output = slot.0.take();
+            if output.is_some() {
+                ticket.disarm();
+            }
+            match output {
+                Some( Ok( output ) ) => Poll::Ready( output ),
+                Some( Err( payload ) ) => panic::resume_unwind( payload ),
+                None => {
+                    slot.1 = Some( context.waker().clone() );
+                    Poll::Pending
+                }
+            }
+        }))
+    }
+
+    /// Returns a future that resolves once `duration` has passed, used for operation timeouts.
+    ///
+    /// The default implementation waits on a single timer thread shared by all spawners. Spawners for runtimes with
+    /// their own timer should override it.
+    ///
+    fn sleep( &self, duration: Duration ) -> BoxFuture<'static, ()> {
+        Timer::shared().sleep( duration )
+    }
+}
+
+/// A spawner that does not spawn, the futures are polled concurrently on the task awaiting the runner.
+#[derive( Debug, Clone, Copy, Default )]
+pub struct Inline;
+
+impl Spawner for Inline {
+    fn spawn<T>( &self, future: BoxFuture<'static, T> ) -> BoxFuture<'static, T>
+    where
+        T: 'static + Send
+    {
+        future
+    }
+}
+
+/// A graph whose operations a [`Runner`] can execute.
+pub trait Runnable<I>: 'static + Send + Sync {
+    /// The key type of the operations' variables.
+    type Key: 'static + Clone + Ord + Hash + Send + Sync;
+
+    /// The variables bound to the operation `id`.
+    fn variables( &self, id: &I ) -> Option<&Variables<Self::Key>>;
+
+    /// The successors of `id` along enabled edges.
+    fn successors( &self, id: &I ) -> Vec<I>;
+
+    /// The timeout the operation `id` sets for itself.
+    fn timeout( &self, id: &I ) -> Option<Duration>;
+
+    /// Returns a future that executes the operation `id`, starting any blocking work on `spawner`.
+    ///
+    /// `started` is called right before the operation executes, from within the spawned future or blocking job, so
+    /// a trace records the thread and time the operation actually ran at.
+    ///
+    fn launch<S>( graph: Arc<Self>, spawner: &S, id: I, token: CancellationToken, started: Started ) -> BoxFuture<'static, Result<(), AsyncOperationError>>
+    where
+        S: Spawner;
+}
+
+impl<I, J> Runnable<I> for AsyncFnGraph<I, J>
+where
+    I: 'static + Clone + Ord + Send + Sync,
+    J: 'static + Clone + Ord + Hash + Send + Sync
+{
+    type Key = J;
+
+    fn variables( &self, id: &I ) -> Option<&Variables<J>> {
+        self.node( id.clone() ).map( |operation| operation.variables() )
+    }
+
+    fn successors( &self, id: &I ) -> Vec<I> {
+        self.enabled_successors( id )
+    }
+
+    fn timeout( &self, id: &I ) -> Option<Duration> {
+        self.node( id.clone() ).and_then( |operation| operation.timeout() )
+    }
+
+    fn launch<S>( graph: Arc<Self>, _spawner: &S, id: I, token: CancellationToken, started: Started ) -> BoxFuture<'static, Result<(), AsyncOperationError>>
+    where
+        S: Spawner
+    {
+        Box::pin( async move {
+            started();
+            match graph.node( id ) {
+                Some( operation ) => operation.execute_with_token( &token ).await,
+                None => Ok( () )
+            }
+        })
+    }
+}
+
+/// The outcome of a single operation in a [`Runner`] run.
+#[derive( Debug )]
+pub enum Status {
+    Completed,
+    Failed( AsyncOperationError ),
+    /// Stopped, or never started, because the run was cancelled.
+    Cancelled,
+    /// Stopped because it ran longer than its timeout.
+    TimedOut,
+    /// Not executed because a predecessor did not complete.
+    Skipped
+}
+
+impl Status {
+    pub fn is_completed( &self ) -> bool {
+        matches!( self, Self::Completed )
+    }
+
+    pub fn is_failed( &self ) -> bool {
+        matches!( self, Self::Failed( _ ) )
+    }
+
+    pub fn is_cancelled( &self ) -> bool {
+        matches!( self, Self::Cancelled )
+    }
+
+    pub fn is_timed_out( &self ) -> bool {
+        matches!( self, Self::TimedOut )
+    }
+
+    pub fn is_skipped( &self ) -> bool {
+        matches!( self, Self::Skipped )
+    }
+}
+
+/// The per operation outcome of a [`Runner`] run, in completion order.
+#[derive( Debug )]
+pub struct RunReport<I> {
+    order: Vec<I>,
+    statuses: BTreeMap<I, Status>
+}
+
+impl<I> RunReport<I>
+where
+    I: Clone + Ord
+{
+    fn new() -> Self {
+        Self {
+            order: Vec::new(),
+            statuses: BTreeMap::new()
+        }
+    }
+
+    fn record( &mut self, id: I, status: Status ) {
+        if self.statuses.insert( id.clone(), status ).is_none() {
+            self.order.push( id );
+        }
+    }
+
+    pub fn status( &self, id: &I ) -> Option<&Status> {
+        self.statuses.get( id )
+    }
+
+    pub fn iter( &self ) -> impl Iterator<Item = ( &I, &Status )> {
+        self.order.iter().filter_map( |id| self.statuses.get( id ).map( |status| ( id, status ) ) )
+    }
+
+    pub fn completed( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_completed() ).map( |( id, _ )| id )
+    }
+
+    pub fn cancelled( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_cancelled() ).map( |( id, _ )| id )
+    }
+
+    pub fn timed_out( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_timed_out() ).map( |( id, _ )| id )
+    }
+
+    pub fn skipped( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_skipped() ).map( |( id, _ )| id )
+    }
+
+    pub fn is_success( &self ) -> bool {
+        self.statuses.values().all( Status::is_completed )
+    }
+}
+
+/// Runs the operations of an [`AsyncFnGraph`], or any other [`Runnable`] graph, concurrently.
+///
+/// An operation is launched on the spawner as soon as all of its predecessors have completed, with at most `limit`
+/// operations in flight at a time. Operations downstream of a failure or timeout are skipped.
+///
+/// Each operation is handed a child of the runner's [`CancellationToken`]. Cancelling the runner's token stops the
+/// operations in flight and cancels every operation that has not started yet. An operation that runs longer than its
+/// timeout, or the runner's timeout if it has none, has its token cancelled and its future dropped.
+///
+#[derive( Debug, Clone )]
+pub struct Runner<S> {
+    spawner: S,
+    limit: usize,
+    timeout: Option<Duration>,
+    token: CancellationToken
+}
+
+impl<S> Runner<S>
+where
+    S: Spawner
+{
+    pub fn new( spawner: S ) -> Self {
+        Self {
+            spawner,
+            limit: usize::MAX,
+            timeout: None,
+            token: CancellationToken::new()
+        }
+    }
+
+    pub fn limit( mut self, limit: usize ) -> Self {
+        self.limit = limit.max( 1 );
+        self
+    }
+
+    /// The timeout of operations that do not set their own.
+    pub fn timeout( mut self, timeout: Duration ) -> Self {
+        self.timeout = Some( timeout );
+        self
+    }
+
+    pub fn token( mut self, token: CancellationToken ) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// The token that cancels runs of this runner.
+    pub fn cancellation_token( &self ) -> &CancellationToken {
+        &self.token
+    }
+
+    pub async fn run<I, G>( &self, graph: Arc<G>, start: I ) -> RunReport<I>
+    where
+        I: 'static + Clone + Ord + Send + Sync,
+        G: Runnable<I>
+    {
+        self.run_with( graph, start, None ).await
+    }
+
+    /// Runs the graph, recording a [`Span`](crate::function_graph::trace::Span) in `trace` for every operation
+    /// launched.
+    pub async fn run_traced<I, G>( &self, graph: Arc<G>, start: I, trace: Arc<Trace<I, G::Key>> ) -> RunReport<I>
+    where
+        I: 'static + Clone + Ord + Send + Sync,
+        G: Runnable<I>
+    {
+        self.run_with( graph, start, Some( trace ) ).await
+    }
+
+    async fn run_with<I, G>( &self, graph: Arc<G>, start: I, trace: Option<Arc<Trace<I, G::Key>>> ) -> RunReport<I>
+    where
+        I: 'static + Clone + Ord + Send + Sync,
+        G: Runnable<I>
+    {
+        let schedule = Schedule::new( start, |id| graph.successors( id ) );
+        let mut successors: BTreeMap<I, Vec<I>> = BTreeMap::new();
+        let mut waiting: BTreeMap<I, usize> = BTreeMap::new();
+        let mut ready = VecDeque::new();
+        for id in schedule.order.iter() {
+            let predecessors = schedule.predecessors( id );
+            predecessors.iter().for_each( |predecessor| successors.entry( predecessor.clone() ).or_default().push( id.clone() ) );
+            match predecessors.len() {
+                0 => ready.push_back( id.clone() ),
+                count => { waiting.insert( id.clone(), count ); }
+            }
+        }
+
+        let mut report = RunReport::new();
+        let mut running: Vec<BoxFuture<'static, ( I, Status )>> = Vec::new();
+        while !ready.is_empty() || !running.is_empty() {
+            let mut finished = Vec::new();
+            while running.len() < self.limit {
+                let Some( id ) = ready.pop_front() else {
+                    break;
+                };
+                if self.token.is_cancelled() {
+                    finished.push( ( id, Status::Cancelled ) );
+                    continue;
+                }
+                let token = self.token.child();
+                let delay = graph.timeout( &id ).or( self.timeout ).map( |timeout| self.spawner.sleep( timeout ) );
+                let probe = Arc::new( Mutex::new( None ) );
+                let started: Started = match trace {
+                    Some( _ ) => {
+                        let ( graph, id, probe ) = ( graph.clone(), id.clone(), probe.clone() );
+                        Box::new( move || {
+                            *probe.lock().expect( "Failed to acquire probe lock" ) = graph.variables( &id ).map( Probe::new );
+                        })
+                    },
+                    None => Box::new( || () )
+                };
+                let execution = G::launch( graph.clone(), &self.spawner, id.clone(), token.clone(), started );
+                let trace = trace.clone();
+                running.push( self.spawner.spawn( Box::pin( async move {
+                    let status = execute( execution, &token, delay ).await;
+                    let probe = probe.lock().expect( "Failed to acquire probe lock" ).take();
+                    if let ( Some( trace ), Some( probe ) ) = ( trace, probe ) {
+                        probe.finish( &trace, id.clone(), !status.is_completed() );
+                    }
+                    ( id, status )
+                })));
+            }
+
+            if finished.is_empty() {
+                finished.push( next_completed( &mut running ).await );
+            }
+            let mut stack = finished;
+            while let Some( ( id, status ) ) = stack.pop() {
+                let completed = status.is_completed();
+                for next_id in successors.get( &id ).into_iter().flatten() {
+                    if completed {
+                        if let Some( count ) = waiting.get_mut( next_id ) {
+                            *count -= 1;
+                            if *count == 0 {
+                                waiting.remove( next_id );
+                                ready.push_back( next_id.clone() );
+                            }
+                        }
+                    } else if waiting.remove( next_id ).is_some() {
+                        let blocked = match self.token.is_cancelled() {
+                            true => Status::Cancelled,
+                            false => Status::Skipped
+                        };
+                        stack.push( ( next_id.clone(), blocked ) );
+                    }
+                }
+                report.record( id, status );
+            }
+        }
+        report
+    }
+}
+
+/// Drives `execution` until it finishes, `token` is cancelled, or `delay` elapses.
+async fn execute<F>( execution: F, token: &CancellationToken, delay: Option<BoxFuture<'static, ()>> ) -> Status
+where
+    F: Future<Output = Result<(), AsyncOperationError>>
+{
+    let mut execution = pin!( execution );
+    let mut cancelled = pin!( token.cancelled() );
+    let mut delay = delay;
+    poll_fn( |context| {
+        if let Poll::Ready( result ) = execution.as_mut().poll( context ) {
+            return Poll::Ready( result.map_or_else( Status::Failed, |_| Status::Completed ) );
+        }
+        if cancelled.as_mut().poll( context ).is_ready() {
+            return Poll::Ready( Status::Cancelled );
+        }
+        if delay.as_mut().is_some_and( |delay| delay.as_mut().poll( context ).is_ready() ) {
+            token.cancel();
+            return Poll::Ready( Status::TimedOut );
+        }
+        Poll::Pending
+    }).await
+}
+
+/// Resolves to the output of whichever running future finishes first, and removes it.
+fn next_completed<'a, T>( running: &'a mut Vec<BoxFuture<'static, T>> ) -> impl Future<Output = T> + 'a {
+    poll_fn( move |context| {
+        for index in 0..running.len() {
+            if let Poll::Ready( output ) = running[ index ].as_mut().poll( context ) {
+                drop( running.swap_remove( index ) );
+                return Poll::Ready( output );
+            }
+        }
+        Poll::Pending
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        sync::{ Arc, atomic::{ AtomicUsize, Ordering } },
+        task::{ Context, Poll, Wake, Waker },
+        thread::{ self, Thread },
+        time::Duration,
+        future::{ self, Future },
+        pin::pin
+    };
+
+    use crate::{
+        async_function_graph::{
+            AsyncFnGraph,
+            runner::{ Inline, Runner, Status }
+        }
+    };
+
+    struct ThreadWaker( Thread );
+
+    impl Wake for ThreadWaker {
+        fn wake( self: Arc<Self> ) {
+            self.0.unpark();
+        }
+    }
+
+    pub(crate) fn block_on<F>( future: F ) -> F::Output
+    where
+        F: Future
+    {
+        let mut future = pin!( future );
+        let waker = Waker::from( Arc::new( ThreadWaker( thread::current() ) ) );
+        let mut context = Context::from_waker( &waker );
+        loop {
+            match future.as_mut().poll( &mut context ) {
+                Poll::Ready( output ) => return output,
+                Poll::Pending => thread::park()
+            }
+        }
+    }
+
+    pub(crate) async fn yield_now() {
+        let mut yielded = false;
+        std::future::poll_fn( |context| {
+            if yielded {
+                Poll::Ready( () )
+            } else {
+                yielded = true;
+                context.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }).await
+    }
+
+    fn counting_graph( running: &Arc<AtomicUsize>, peak: &Arc<AtomicUsize> ) -> AsyncFnGraph<char, char> {
+        let mut graph = AsyncFnGraph::<char, char>::new();
+        for id in [ 'a', 'b', 'c', 'd', 'e', 'f' ] {
+            let running = running.clone();
+            let peak = peak.clone();
+            graph.add_operation( id, [], move |_| {
+                let running = running.clone();
+                let peak = peak.clone();
+                Box::pin( async move {
+                    peak.fetch_max( running.fetch_add( 1, Ordering::SeqCst ) + 1, Ordering::SeqCst );
+                    for _ in 0..8 {
+                        yield_now().await;
+                    }
+                    running.fetch_sub( 1, Ordering::SeqCst );
+                    Ok( () )
+                })
+            }).unwrap();
+        }
+        for id in [ 'b', 'c', 'd', 'e' ] {
+            graph.add_edge( 'a', id, true ).unwrap();
+            graph.add_edge( id, 'f', true ).unwrap();
+        }
+        graph
+    }
+
+    #[test]
+    fn test_runner_overlaps_operations() {
+        let running = Arc::new( AtomicUsize::new( 0 ) );
+        let peak = Arc::new( AtomicUsize::new( 0 ) );
+        let graph = Arc::new( counting_graph( &running, &peak ) );
+
+        let report = block_on( Runner::new( Inline ).run( graph, 'a' ) );
+        assert!( report.is_success() );
+        assert_eq!( report.completed().next(), Some( &'a' ) );
+        assert_eq!( report.completed().last(), Some( &'f' ) );
+        assert_eq!( peak.load( Ordering::SeqCst ), 4 );
+    }
+
+    #[test]
+    fn test_runner_limit() {
+        let running = Arc::new( AtomicUsize::new( 0 ) );
+        let peak = Arc::new( AtomicUsize::new( 0 ) );
+        let graph = Arc::new( counting_graph( &running, &peak ) );
+
+        let report = block_on( Runner::new( Inline ).limit( 2 ).run( graph, 'a' ) );
+        assert_eq!( report.completed().count(), 6 );
+        assert_eq!( peak.load( Ordering::SeqCst ), 2 );
+    }
+
+    #[test]
+    fn test_runner_timeout() {
+        let mut graph = AsyncFnGraph::<char, char>::new();
+        graph.add_operation( 'a', [], |_| Box::pin( future::pending() ) ).unwrap();
+        graph.add_operation( 'b', [], |_| Box::pin( async { Ok( () ) } ) ).unwrap();
+        graph.add_operation( 'c', [], |_| Box::pin( async { Ok( () ) } ) ).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph.add_edge( 'c', 'a', true ).unwrap();
+        graph.set_timeout( 'a', Some( Duration::from_millis( 10 ) ) ).unwrap();
+
+        let report = block_on( Runner::new( Inline ).run( Arc::new( graph ), 'c' ) );
+        assert!( !report.is_success() );
+        assert!( report.status( &'c' ).is_some_and( Status::is_completed ) );
+        assert_eq!( report.timed_out().collect::<Vec<_>>(), vec![ &'a' ] );
+        assert_eq!( report.skipped().collect::<Vec<_>>(), vec![ &'b' ] );
+    }
+
+    #[test]
+    fn test_runner_cancellation() {
+        let runner = Runner::new( Inline );
+        let token = runner.cancellation_token().clone();
+        let mut graph = AsyncFnGraph::<char, char>::new();
+        graph.add_operation( 'a', [], move |_| {
+            let token = token.clone();
+            Box::pin( async move {
+                token.cancel();
+                Ok( () )
+            })
+        }).unwrap();
+        graph.add_cancellable_operation( 'b', [], |_, token| Box::pin( async move {
+            token.cancelled().await;
+            Ok( () )
+        }) ).unwrap();
+        graph.add_operation( 'c', [], |_| Box::pin( async { Ok( () ) } ) ).unwrap();
+        graph.add_operation( 'd', [], |_| Box::pin( async { Ok( () ) } ) ).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph.add_edge( 'a', 'c', true ).unwrap();
+        graph.add_edge( 'c', 'd', true ).unwrap();
+
+        let report = block_on( runner.limit( 1 ).run( Arc::new( graph ), 'a' ) );
+        assert!( report.status( &'a' ).is_some_and( Status::is_completed ) );
+        assert_eq!( report.cancelled().count(), 3 );
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    cmp::{ Ordering, Reverse },
+    collections::BinaryHeap,
+    future::poll_fn,
+    sync::{ Arc, Condvar, Mutex, Once, OnceLock, Weak },
+    task::{ Poll, Waker },
+    thread,
+    time::{ Duration, Instant }
+};
+
+use crate::async_function_graph::runner::BoxFuture;
+
+/// A pending sleep, woken once its deadline has passed. Sleeps that were dropped leave an entry whose waker is gone.
+struct Entry {
+    deadline: Instant,
+    waker: Weak<Mutex<Waker>>
+}
+
+impl PartialEq for Entry {
+    fn eq( &self, other: &Self ) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp( &self, other: &Self ) -> Option<Ordering> {
+        Some( self.cmp( other ) )
+    }
+}
+
+impl Ord for Entry {
+    fn cmp( &self, other: &Self ) -> Ordering {
+        self.deadline.cmp( &other.deadline )
+    }
+}
+
+/// Wakes sleeping futures from a single thread, keeping their deadlines in a heap.
+///
+/// Used by the default [`Spawner::sleep`](crate::async_function_graph::runner::Spawner::sleep), so a run with many
+/// timeouts needs one thread instead of one per timeout. Entries of dropped sleeps are purged whenever the heap has
+/// doubled in size, so long timeouts that were dropped early do not pile up until their deadline.
+///
+pub(crate) struct Timer {
+    deadlines: Mutex<BinaryHeap<Reverse<Entry>>>,
+    changed: Condvar
+}
+
+impl Timer {
+    fn new() -> Self {
+        Self {
+            deadlines: Mutex::new( BinaryHeap::new() ),
+            changed: Condvar::new()
+        }
+    }
+
+    /// The timer shared by every spawner, its thread is started on first use.
+    pub(crate) fn shared() -> &'static Self {
+        static TIMER: OnceLock<Timer> = OnceLock::new();
+        static STARTED: Once = Once::new();
+        let timer = TIMER.get_or_init( Self::new );
+        STARTED.call_once( || {
+            thread::Builder::new()
+                .name( String::from( "graphs-timer" ) )
+                .spawn( || timer.run() )
+                .expect( "Failed to start the timer thread" );
+        });
+        timer
+    }
+
+    /// Returns a future that resolves once `duration` has passed.
+    pub(crate) fn sleep( &'static self, duration: Duration ) -> BoxFuture<'static, ()> {
+        let deadline = Instant::now() + duration;
+        let mut registered: Option<Arc<Mutex<Waker>>> = None;
+        Box::pin( poll_fn( move |context| {
+            if Instant::now() >= deadline {
+                return Poll::Ready( () );
+            }
+            match &registered {
+                Some( waker ) => *waker.lock().expect( "Failed to acquire waker lock" ) = context.waker().clone(),
+                None => {
+                    let waker = Arc::new( Mutex::new( context.waker().clone() ) );
+                    self.schedule( deadline, Arc::downgrade( &waker ) );
+                    registered = Some( waker );
+                }
+            }
+            Poll::Pending
+        }))
+    }
+
+    fn schedule( &self, deadline: Instant, waker: Weak<Mutex<Waker>> ) {
+        let mut deadlines = self.deadlines.lock().expect( "Failed to acquire timer lock" );
+        if deadlines.len().is_power_of_two() {
+            deadlines.retain( |Reverse( entry )| entry.waker.strong_count() > 0 );
+        }
+        deadlines.push( Reverse( Entry { deadline, waker } ) );
+        drop( deadlines );
+        self.changed.notify_one();
+    }
+
+    fn run( &self ) {
+        let mut deadlines = self.deadlines.lock().expect( "Failed to acquire timer lock" );
+        loop {
+            let now = Instant::now();
+            while deadlines.peek().is_some_and( |Reverse( entry )| entry.deadline <= now ) {
+                let Reverse( entry ) = deadlines.pop().expect( "Deadline is present" );
+                if let Some( waker ) = entry.waker.upgrade() {
+                    waker.lock().expect( "Failed to acquire waker lock" ).wake_by_ref();
+                }
+            }
+            deadlines = match deadlines.peek() {
+                Some( Reverse( entry ) ) => {
+                    let timeout = entry.deadline.saturating_duration_since( now );
+                    self.changed.wait_timeout( deadlines, timeout ).expect( "Failed to acquire timer lock" ).0
+                },
+                None => self.changed.wait( deadlines ).expect( "Failed to acquire timer lock" )
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cmp::Reverse,
+        task::{ Context, Poll, Waker },
+        time::{ Duration, Instant }
+    };
+
+    use crate::async_function_graph::{
+        timer::Timer,
+        runner::tests::block_on
+    };
+
+    #[test]
+    fn test_timer_wakes_earlier_deadline_first() {
+        let mut late = Timer::shared().sleep( Duration::from_secs( 3600 ) );
+        assert!( late.as_mut().poll( &mut Context::from_waker( Waker::noop() ) ).is_pending() );
+
+        let start = Instant::now();
+        block_on( Timer::shared().sleep( Duration::from_millis( 10 ) ) );
+        assert!( start.elapsed() >= Duration::from_millis( 10 ) );
+        assert!( start.elapsed() < Duration::from_secs( 3600 ) );
+
+        drop( late );
+        let mut done = Timer::shared().sleep( Duration::ZERO );
+        assert_eq!( done.as_mut().poll( &mut Context::from_waker( Waker::noop() ) ), Poll::Ready( () ) );
+    }
+
+    #[test]
+    fn test_timer_purges_dropped_sleeps() {
+        let timer: &'static Timer = Box::leak( Box::new( Timer::new() ) );
+        let mut context = Context::from_waker( Waker::noop() );
+        let mut kept = timer.sleep( Duration::from_secs( 3600 ) );
+        assert!( kept.as_mut().poll( &mut context ).is_pending() );
+        for _ in 0..1000 {
+            let mut dropped = timer.sleep( Duration::from_secs( 3600 ) );
+            assert!( dropped.as_mut().poll( &mut context ).is_pending() );
+        }
+
+        // Only the sleeps dropped since the last purge remain, next to the one still pending.
+        let entries = timer.deadlines.lock().unwrap().len();
+        assert!( entries <= 512, "{} entries left", entries );
+        assert_eq!( timer.deadlines.lock().unwrap().iter().filter( |Reverse( entry )| entry.waker.strong_count() > 0 ).count(), 1 );
+    }
+}
@@ -5,107 +5,353 @@
 pub mod variable;
 pub mod function;
 pub mod operation;
+pub mod edge;
+pub mod executor;
+pub mod subgraph;
+pub mod trace;
+pub mod registry;
+pub(crate) mod schedule;
 
 use std::{
     hash::Hash,
-    collections::{ BTreeSet, VecDeque },
+    collections::{ BTreeMap, BTreeSet, HashMap, VecDeque },
     fmt::Display,
-    thread
+    io::Write
 };
 
 use thiserror::Error;
 
 use crate::{
     graph::{
-        Error as GraphError,
         Graph,
-        GraphAccess,
-        GraphTraits,
-        GraphType,
-        traverser::{
-            Traverser,
-            TraverserAccess,
-            TraverserTraits,
-            Traversable
-        }
+        Directed,
+        Cyclic,
+        json::{ self, Json },
+        mermaid,
+        quote
+    },
+    graph_repr::BTreeRepr,
+    traits::{
+        GetNode,
+        GetEdge,
+        GetEdgeMut,
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterAdjacent
     },
     function_graph::{
-        variable::{ Variable, Variables },
-        operation::{ Operation, Error as OperationError }
+        variable::{ Variable, Variables, Error as VariableError },
+        function::{ BranchResult, FunctionResult },
+        operation::{ Operation, Loop, Error as OperationError },
+        edge::Edge,
+        executor::Executor,
+        subgraph::Subgraph
     }
 };
 
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Graph Error: {0}")]
-    GraphError( #[from] GraphError),
     #[error("Operation Error: {0}")]
-    OperationError( #[from] OperationError )
+    OperationError( #[from] OperationError ),
+    #[error("Variable Error: {0}")]
+    VariableError( #[from] VariableError ),
+    #[error("Operation {id} failed: {source}")]
+    OperationFailed { id: String, source: OperationError },
+    #[error("Operation not found")]
+    OperationNotFound,
+    #[error("Edge not found")]
+    EdgeNotFound
 }
 
-#[derive( Debug )]
-pub struct Functional ();
-impl GraphType for Functional {}
-pub type FnGraph<I, J> = Graph<Functional, I, Operation<J>, bool>;
-pub type FnTraverser<'a, I, J> = Traverser<'a, I, Operation<J>, bool, Graph<Functional, I, Operation<J>, bool>>;
+/// A graph of operations connected by [`Edge`]s.
+pub type FnGraph<I, J> = Graph<Directed, Cyclic, BTreeRepr<I, Operation<J>, Edge>>;
 
-impl<'a, I, J> FnGraph<I, J>
+impl<I, J> FnGraph<I, J>
 where
-    I: 'a + Clone + Ord + Display,
+    I: Clone + Ord + Display,
     J: 'static + Clone + Ord + Hash + Display
 {
-    pub fn generate_dot_to_file( &self, file_name: String ) {
+    /// The graph in the DOT language.
+    ///
+    /// Edges taken in the last run are drawn green, other enabled edges blue and disabled edges red.
+    ///
+    pub fn to_dot( &self ) -> String {
         let mut dot = String::new();
         dot.push_str( "digraph G {\n" );
-        for ( node_id, node_data ) in self.nodes().iter() {
-            node_data.data().variables().iter().for_each( |( _, _ )|
-                dot.push_str( &format!( " {} [label=\"{}\"];\n", node_id, node_id ) )
-            );
-
-            for ( adj_node_id, edge ) in node_data.adjacencies().iter() {
-                if *edge {
-                    dot.push_str( &format!( " {} -> {} [label=\"{}\" color=\"blue\"];\n", node_id, adj_node_id, edge ) );
+        for ( node_id, _ ) in self.iter_node_ids() {
+            let id = quote::dot( &node_id.to_string() );
+            dot.push_str( &format!( " {} [label={}];\n", id, id ) );
+
+            for ( adj_node_id, edge ) in self.iter_adjacent( node_id.clone() ) {
+                let adj_id = quote::dot( &adj_node_id.to_string() );
+                let label = quote::dot( &edge.label().map( str::to_string ).unwrap_or_else( || edge.enabled.to_string() ) );
+                if edge.enabled && self.was_taken( &node_id, &adj_node_id ) {
+                    dot.push_str( &format!( " {} -> {} [label={} color=\"green\" penwidth=2];\n", id, adj_id, label ) );
+                } else if edge.enabled {
+                    dot.push_str( &format!( " {} -> {} [label={} color=\"blue\"];\n", id, adj_id, label ) );
                 } else {
-                    dot.push_str( &format!( " {} -> {} [label=\"{}\" color=\"red\"];\n", node_id, adj_node_id, edge ) );
+                    dot.push_str( &format!( " {} -> {} [label={} color=\"red\"];\n", id, adj_id, label ) );
                 }
             }
         }
         dot.push_str( "}\n" );
-        std::fs::write( file_name, dot ).unwrap();
+        dot
+    }
+
+    pub fn generate_dot_to_file( &self, file_name: String ) -> std::io::Result<()> {
+        std::fs::write( file_name, self.to_dot() )
+    }
+
+    /// The graph as a Mermaid flowchart.
+    ///
+    /// Operations are labelled with their id and the names of their variables. Enabled edges are solid and disabled
+    /// edges dotted; edges taken in the last run are drawn green, like [`FnGraph::generate_dot_to_file`].
+    ///
+    pub fn to_mermaid( &self ) -> String {
+        let mut output = Vec::new();
+        let mut names = BTreeMap::new();
+        let mut loops = Vec::new();
+        writeln!( output, "flowchart TD" ).expect( "Failed to write to memory" );
+        for ( index, ( node_id, operation ) ) in self.iter_node_ids().enumerate() {
+            let name = format!( "n{}", index );
+            let mut keys: Vec<_> = operation.variables().keys().map( |key| key.to_string() ).collect();
+            keys.sort();
+            let label = if keys.is_empty() { node_id.to_string() } else { format!( "{}\n{}", node_id, keys.join( ", " ) ) };
+            writeln!( output, "    {}[{}]", name, quote::mermaid( &label ) ).expect( "Failed to write to memory" );
+            if operation.repeat().is_some() {
+                loops.push( name.clone() );
+            }
+            names.insert( node_id, name );
+        }
+
+        let mut styles = Vec::new();
+        for ( node_id, _ ) in self.iter_node_ids() {
+            for ( adj_node_id, edge ) in self.iter_adjacent( node_id.clone() ) {
+                let ( connector, style ) = if edge.enabled && self.was_taken( &node_id, &adj_node_id ) {
+                    ( "-->", "stroke:green,stroke-width:2px" )
+                } else if edge.enabled {
+                    ( "-->", "stroke:blue" )
+                } else {
+                    ( "-.->", "stroke:red" )
+                };
+                mermaid::write_edge( &mut output, &names[ &node_id ], connector, edge.label(), &names[ &adj_node_id ] ).expect( "Failed to write to memory" );
+                styles.push( Some( style.to_string() ) );
+            }
+        }
+
+        if !loops.is_empty() {
+            writeln!( output, "    classDef loop stroke-width:3px" ).expect( "Failed to write to memory" );
+            mermaid::write_classes( &mut output, &BTreeMap::from( [ ( "loop".to_string(), loops ) ] ) ).expect( "Failed to write to memory" );
+        }
+        mermaid::write_link_styles( &mut output, &styles ).expect( "Failed to write to memory" );
+        String::from_utf8( output ).expect( "Mermaid output is not valid UTF-8" )
+    }
+
+    /// The graph as a node-link JSON document, see [`json::Format`].
+    ///
+    /// Each operation carries its `variables` as `{ "name", "binding", "version" }`, where variables shared between
+    /// operations have the same `binding`, along with its branch `label`, whether it is a `loop` header and whether it
+    /// was `active` in the last run. Each edge carries whether it is `enabled` and whether it was `taken`.
+    ///
+    pub fn to_json( &self, format: json::Format ) -> String {
+        let mut bindings = HashMap::new();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for ( node_id, operation ) in self.iter_node_ids() {
+            let mut variables: Vec<_> = operation.variables().iter().collect();
+            variables.sort_by_key( |( key, _ )| *key );
+            let variables = variables.into_iter()
+                .map( |( key, variable )| {
+                    let count = bindings.len();
+                    let binding = *bindings.entry( variable.address() ).or_insert( count );
+                    Json::Object( vec![
+                        ( "name".to_string(), Json::String( key.to_string() ) ),
+                        ( "binding".to_string(), binding.into() ),
+                        ( "version".to_string(), variable.version().into() )
+                    ])
+                })
+                .collect();
+            nodes.push( vec![
+                ( "id".to_string(), Json::String( node_id.to_string() ) ),
+                ( "loop".to_string(), operation.repeat().is_some().into() ),
+                ( "active".to_string(), operation.was_active().into() ),
+                ( "variables".to_string(), Json::Array( variables ) )
+            ]);
+
+            for ( adj_node_id, edge ) in self.iter_adjacent( node_id.clone() ) {
+                edges.push( vec![
+                    ( "source".to_string(), Json::String( node_id.to_string() ) ),
+                    ( "target".to_string(), Json::String( adj_node_id.to_string() ) ),
+                    ( "enabled".to_string(), edge.enabled.into() ),
+                    ( "label".to_string(), edge.label().into() ),
+                    ( "taken".to_string(), ( edge.enabled && self.was_taken( &node_id, &adj_node_id ) ).into() )
+                ]);
+            }
+        }
+        json::document( format, true, Vec::new(), nodes, edges ).to_string()
+    }
+
+    pub fn generate_mermaid_to_file( &self, file_name: String ) -> std::io::Result<()> {
+        std::fs::write( file_name, self.to_mermaid() )
+    }
+
+    pub fn generate_json_to_file( &self, file_name: String, format: json::Format ) -> std::io::Result<()> {
+        std::fs::write( file_name, self.to_json( format ) )
     }
 
     pub fn add_operation<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
     where
-        F: 'static + Fn( &Variables<J> ) + Send + Sync
+        F: 'static + Fn( &Variables<J> ) -> FunctionResult + Send + Sync
     {
         self.add_node( id, Operation::new(
             variables,
             function
-        ))?;
+        ));
+        Ok( () )
+    }
+
+    /// Adds an operation that selects at runtime which of its outgoing edges fire.
+    ///
+    /// Successors are chosen by the [`Branch`](function::Branch) the function returns, matched against the labels of
+    /// the edges into them, see [`FnGraph::set_branch_label`].
+    ///
+    pub fn add_branch<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
+    where
+        F: 'static + Fn( &Variables<J> ) -> BranchResult + Send + Sync
+    {
+        self.add_node( id, Operation::branch(
+            variables,
+            function
+        ));
+        Ok( () )
+    }
+
+    /// Adds the header of a loop, see [`Loop`].
+    ///
+    /// Every operation on a cycle of enabled edges back to `id` forms the loop body. The header and body run until
+    /// `until` holds for the header's variables or `max_iterations` iterations have run, and the operations after the
+    /// loop run once it ends.
+    ///
+    pub fn add_loop<const N: usize, F, P>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F, until: P, max_iterations: usize ) -> Result<(), Error>
+    where
+        F: 'static + Fn( &Variables<J> ) -> FunctionResult + Send + Sync,
+        P: 'static + Fn( &Variables<J> ) -> bool + Send + Sync
+    {
+        self.add_node( id, Operation::new(
+            variables,
+            function
+        ).with_loop( Loop::new( until, max_iterations ) ) );
+        Ok( () )
+    }
+
+    /// Adds a whole graph as a single operation bound to the subgraph's ports, see [`Subgraph`].
+    pub fn add_subgraph<K, L>( &mut self, id: I, subgraph: Subgraph<K, L, J> ) -> Result<(), Error>
+    where
+        K: 'static + Clone + Ord + Send + Sync,
+        L: 'static + Clone + Ord + Hash + Send + Sync + std::fmt::Debug
+    {
+        self.add_node( id, subgraph.into_operation() );
+        Ok( () )
+    }
+
+    /// Labels the edge from `id1` to `id2`, so it only fires when the branch selected by `id1` admits `label`.
+    pub fn set_branch_label<L>( &mut self, id1: I, id2: I, label: L ) -> Result<(), Error>
+    where
+        L: Into<String>
+    {
+        self.edge_mut( id1, id2 ).ok_or( Error::EdgeNotFound )?.label = Some( label.into() );
         Ok( () )
     }
 }
 
-impl<'a, I, J> GraphTraits<'a, I, Operation<J>, bool> for FnGraph<I, J>
+impl<I, J> FnGraph<I, J>
 where
-    I: 'a + Clone + Ord,
+    I: Clone + Ord,
     J: 'static + Clone + Ord + Hash
-{}
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an edge between two operations, failing if either of them is missing.
+    pub fn add_edge( &mut self, id1: I, id2: I, enabled: bool ) -> Result<(), Error> {
+        if self.node( id1.clone() ).is_none() || self.node( id2.clone() ).is_none() {
+            return Err( Error::OperationNotFound );
+        }
+        AddEdge::add_edge( self, id1, id2, Edge::new( enabled ) );
+        Ok( () )
+    }
+
+    pub fn executor( &self ) -> Executor<'_, I, J> {
+        Executor::new( self )
+    }
+
+    /// The variable bound to `key` in the operation `id`.
+    pub fn variable( &self, id: I, key: &J ) -> Option<&Variable> {
+        self.node( id ).and_then( |operation| operation.variables().get( key ) )
+    }
+
+    /// An independent instance of the graph, with every variable deep-cloned.
+    ///
+    /// Variables shared between operations are shared between the same operations of the copy, but not with the
+    /// original, so the copy can be run with different inputs. Fails if a shared variable has no clone function, see
+    /// [`Variable::shared_cloneable`].
+    ///
+    pub fn deep_clone( &self ) -> Result<Self, Error>
+    where
+        J: std::fmt::Debug
+    {
+        Ok( self.deep_clone_with( &mut HashMap::new() )? )
+    }
 
-impl<'a, I, J> TraverserTraits<'a, Functional, I, Operation<J>, bool, FnGraph<I, J>> for FnTraverser<'a, I, J>
+    /// Deep-clones the graph, reusing the copies in `clones`, so a subgraph's ports stay bound to the copies of the
+    /// enclosing graph's variables.
+    pub(crate) fn deep_clone_with( &self, clones: &mut HashMap<usize, Variable> ) -> Result<Self, VariableError>
+    where
+        J: std::fmt::Debug
+    {
+        let mut graph = Self::new();
+        for ( id, operation ) in self.iter_node_ids() {
+            graph.add_node( id, operation.deep_clone_with( clones )? );
+        }
+        for ( id, _ ) in self.iter_node_ids() {
+            for ( next_id, edge ) in self.iter_adjacent( id.clone() ) {
+                AddEdge::add_edge( &mut graph, id.clone(), next_id, edge.clone() );
+            }
+        }
+        Ok( graph )
+    }
+
+    /// Whether the edge from `id1` to `id2` fired in the last executor run.
+    pub fn was_taken( &self, id1: &I, id2: &I ) -> bool {
+        match ( self.node( id1.clone() ), self.node( id2.clone() ), self.edge( id1.clone(), id2.clone() ) ) {
+            ( Some( operation1 ), Some( operation2 ), Some( edge ) ) => operation1.was_active()
+                && operation2.was_active()
+                && operation1.last_branch().is_some_and( |branch| branch.admits( edge.label() ) ),
+            _ => false
+        }
+    }
+
+    pub(crate) fn enabled_successors( &self, id: &I ) -> Vec<I> {
+        self.iter_adjacent( id.clone() )
+            .filter( |( _, edge )| edge.enabled )
+            .map( |( next_id, _ )| next_id )
+            .collect()
+    }
+}
+
+impl<I, J> FnGraph<I, J>
 where
-    I: 'a + Clone + Ord,
-    J: 'static + Clone + Ord + Hash,
-    Self: TraverserAccess<'a, Functional, I, Operation<J>, bool, FnGraph<I, J>>
+    I: Clone + Ord,
+    J: 'static + Clone + Ord + Hash
 {
-    fn bfs_step( &'a self, queue: &mut VecDeque<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
-        while let Some(current_id) = queue.pop_front() {
+    fn bfs_step( &self, queue: &mut VecDeque<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
+        while let Some( current_id ) = queue.pop_front() {
             if visited.insert( current_id.clone() ) {
-                if let Some( current_node ) = self.graph().data().get( &current_id ) {
-                    for ( next_id, edge ) in current_node.adjacencies().iter() {
-                        if *edge && !visited.contains( next_id ) {
-                            queue.push_back( next_id.clone() );
-                        }
+                for next_id in self.enabled_successors( &current_id ) {
+                    if !visited.contains( &next_id ) {
+                        queue.push_back( next_id );
                     }
                 }
                 return Some( current_id );
@@ -114,14 +360,12 @@ where
         None
     }
 
-    fn dfs_step( &'a self, stack: &mut Vec<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
+    fn dfs_step( &self, stack: &mut Vec<I>, visited: &mut BTreeSet<I> ) -> Option<I> {
         while let Some( current_id ) = stack.pop() {
             if visited.insert( current_id.clone() ) {
-                if let Some( current_node ) = self.graph().data().get( &current_id ) {
-                    for ( next_id, edge ) in current_node.adjacencies().iter() {
-                        if *edge && !visited.contains( next_id ) {
-                            stack.push( next_id.clone() );
-                        }
+                for next_id in self.enabled_successors( &current_id ) {
+                    if !visited.contains( &next_id ) {
+                        stack.push( next_id );
                     }
                 }
                 return Some( current_id );
@@ -130,53 +374,67 @@ where
         None
     }
 
-    fn bfs( &'a self, start: I ) {
+    fn execute_node( &self, id: I ) -> Result<(), Error>
+    where
+        I: Display
+    {
+        match self.node( id.clone() ) {
+            Some( operation ) => operation.execute()
+                .map( |_| () )
+                .map_err( |source| Error::OperationFailed { id: id.to_string(), source } ),
+            None => Ok( () )
+        }
+    }
+
+    /// Executes the operations reachable from `start` over enabled edges in breadth-first order, stopping at the
+    /// first operation that fails.
+    ///
+    pub fn bfs( &self, start: I ) -> Result<(), Error>
+    where
+        I: Display
+    {
         let mut queue = VecDeque::new();
         let mut visited = BTreeSet::new();
-        queue.push_back( start.clone() );
+        queue.push_back( start );
         while !queue.is_empty() {
             if let Some( current_id ) = self.bfs_step( &mut queue, &mut visited ) {
-                if let Some( operation ) = self.graph().data().get_node( current_id ) {
-                    operation.execute().unwrap();
-                }
+                self.execute_node( current_id )?;
             }
         }
+        Ok( () )
     }
 
-    fn dfs( &'a self, start: I ) {
+    /// Executes the operations reachable from `start` over enabled edges in depth-first order, stopping at the
+    /// first operation that fails.
+    ///
+    pub fn dfs( &self, start: I ) -> Result<(), Error>
+    where
+        I: Display
+    {
         let mut stack = Vec::new();
         let mut visited = BTreeSet::new();
-        stack.push( start.clone() );
+        stack.push( start );
         while !stack.is_empty() {
-            if let Some( current_id ) = self.dfs_step(&mut stack, &mut visited) {
-                if let Some( operation ) = self.graph().data().get_node( current_id ) {
-                    operation.execute().unwrap();
-                }
+            if let Some( current_id ) = self.dfs_step( &mut stack, &mut visited ) {
+                self.execute_node( current_id )?;
             }
         }
+        Ok( () )
     }
 }
 
-impl<'a, I, J> Traversable<'a, Functional, I, Operation<J>, bool> for FnGraph<I, J>
-where
-    I: 'a + Clone + Ord,
-    J: 'static + Clone + Ord + Hash,
-{}
-
 #[cfg(test)]
 mod tests {
     use crate::{
-        graph::{
-            Graph,
-            GraphTraits,
-            traverser::{
-                TraverserTraits,
-                Traversable
-            }
-        },
+        graph::json,
         function_graph::{
+            Error,
             FnGraph,
-            variable::Variable
+            variable::{ Variable, TypedVariable },
+            function::Branch,
+            executor::{ ExecutionPolicy, LoopOutcome },
+            subgraph::Subgraph,
+            trace::Trace
         }
     };
 
@@ -249,6 +507,7 @@ mod tests {
                     *b = *a + 2;
                     println!( "{} = {} + 2", *b, *a );
                 }
+                Ok( () )
             }
         ).unwrap();
         graph.add_operation( 'b',
@@ -264,6 +523,7 @@ mod tests {
                     *c = *b * 4;
                     println!( "{} = {} * 4", *c, *b );
                 }
+                Ok( () )
             }
         ).unwrap();
         graph.add_operation( 'c',
@@ -279,6 +539,7 @@ mod tests {
                     *d = *c - 1;
                     println!( "{} = {} - 1", *d, *c );
                 }
+                Ok( () )
             }
         ).unwrap();
         graph.add_operation( 'd',
@@ -287,6 +548,7 @@ mod tests {
                 if let Some( e ) = variables.read( &'e' ).downcast_ref::<String>() {
                     println!( "{}", e );
                 }
+                Ok( () )
             }
         ).unwrap();
         graph.add_operation( 'e',
@@ -295,9 +557,13 @@ mod tests {
                 if let Some( f ) = variables.read( &'f' ).downcast_ref::<String>() {
                     println!( "{}", f );
                 }
+                Ok( () )
             }
         ).unwrap();
-        graph.add_operation( 'f', [], |_| println!( "Done!" ) ).unwrap();
+        graph.add_operation( 'f', [], |_| {
+            println!( "Done!" );
+            Ok( () )
+        }).unwrap();
 
         graph.add_edge( 'a', 'b', true ).unwrap();
         graph.add_edge( 'b', 'c', true ).unwrap();
@@ -307,12 +573,30 @@ mod tests {
 
         //graph.generate_dot_to_file( "graphs/function_graph_before.dot".to_string() );
         let start = std::time::Instant::now();
-        graph.traverser().bfs( 'a' );
+        graph.bfs( 'a' ).unwrap();
         let duration = start.elapsed();
         println!("Time taken to traverse the graph: {:?}", duration);
         //graph.generate_dot_to_file( "graphs/function_graph_after.dot".to_string() );
     }
 
+    #[test]
+    fn test_traversal_reports_failed_operation() {
+        let reached = TypedVariable::shared( false );
+        let mut graph = FnGraph::<char, char>::new();
+        graph.add_operation( 'a', [], |_| Ok( () ) ).unwrap();
+        graph.add_operation( 'b', [], |_| Err( "b failed".into() ) ).unwrap();
+        graph.add_operation( 'c', [ ( 'r', reached.clone().into() ) ], |variables| {
+            *variables.get_typed_mut::<bool>( &'r' )? = true;
+            Ok( () )
+        }).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph.add_edge( 'b', 'c', true ).unwrap();
+
+        assert!( matches!( graph.bfs( 'a' ), Err( Error::OperationFailed { id, .. } ) if id == "b" ) );
+        assert!( matches!( graph.dfs( 'a' ), Err( Error::OperationFailed { id, .. } ) if id == "b" ) );
+        assert!( !*reached.read() );
+    }
+
     #[test]
     fn test_string_equation_graph() {
         let mut graph = FnGraph::<char, char>::new();
@@ -331,6 +615,7 @@ mod tests {
                 if let Some( a ) = variables.read( &'a' ).downcast_ref::<String>() {
                     print!( "{} ", a );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -340,6 +625,7 @@ mod tests {
                 if let Some( b ) = variables.read( &'b' ).downcast_ref::<String>() {
                     print!( "{} ", b );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -349,6 +635,7 @@ mod tests {
                 if let Some( c ) = variables.read( &'c' ).downcast_ref::<String>() {
                     print!( "{} ", c );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -358,6 +645,7 @@ mod tests {
                 if let Some( d ) = variables.read( &'d' ).downcast_ref::<String>() {
                     print!( "{} ", d );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -367,6 +655,7 @@ mod tests {
                 if let Some( e ) = variables.read( &'e' ).downcast_ref::<String>() {
                     println!( "{}", e );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -377,7 +666,7 @@ mod tests {
 
         //graph.generate_dot_to_file( "graphs/string_equation_graph_before.dot".to_string() );
         let start = std::time::Instant::now();
-        graph.traverser().bfs( 'a' );
+        graph.bfs( 'a' ).unwrap();
         let duration = start.elapsed();
         println!( "Time taken to traverse the graph: {:?}", duration );
         //graph.generate_dot_to_file( "graphs/string_equation_graph_after.dot".to_string() );
@@ -410,6 +699,7 @@ mod tests {
                     *b = *a + 2;
                     println!( "{} = {} + 2", *b, *a );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -427,6 +717,7 @@ mod tests {
                     *c = *b * 4;
                     println!( "{} = {} * 4", *c, *b );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -449,6 +740,7 @@ mod tests {
                         println!( "{} is not divisible by 3", *c );
                     }
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -466,6 +758,7 @@ mod tests {
                     *e = *c + 5;
                     println!( "{} = {} + 5 (divisible by 3 branch)", *e, *c );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -483,6 +776,7 @@ mod tests {
                     *f = *c - 2;
                     println!( "{} = {} - 2 (not divisible by 3 branch)", *f, *c );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -500,6 +794,7 @@ mod tests {
                     *g = *e * 2;
                     println!( "{} = {} * 2 (further divisible by 3 branch)", *g, *e );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -517,6 +812,7 @@ mod tests {
                     *h = *f + 7;
                     println!( "{} = {} + 7 (further not divisible by 3 branch)", *h, *f );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -534,6 +830,7 @@ mod tests {
                     *i = *g - 1;
                     println!( "{} = {} - 1 (converged branch)", *i, *g );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -549,7 +846,7 @@ mod tests {
 
         //graph.generate_dot_to_file( "graphs/function_graph_with_multiple_branches_before.dot".to_string() );
         let start = std::time::Instant::now();
-        graph.traverser().bfs( 'a' );
+        graph.bfs( 'a' ).unwrap();
         let duration = start.elapsed();
         println!( "Time taken to traverse the graph: {:?}", duration );
         //graph.generate_dot_to_file( "graphs/function_graph_with_multiple_branches_after.dot".to_string() );
@@ -571,6 +868,7 @@ mod tests {
                 if let Some( a ) = variables.read( &'a' ).downcast_ref::<char>() {
                     println!( "{}", a );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -580,6 +878,7 @@ mod tests {
                 if let Some( b ) = variables.read( &'b' ).downcast_ref::<char>() {
                     println!( "{}", b );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -589,6 +888,7 @@ mod tests {
                 if let Some( c ) = variables.read( &'c' ).downcast_ref::<char>() {
                     println!( "{}", c );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -598,6 +898,7 @@ mod tests {
                 if let Some( d ) = variables.read( &'d' ).downcast_ref::<char>() {
                     println!( "{}", d );
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -606,21 +907,436 @@ mod tests {
         sub_graph.add_edge( 'c', 'd', true ).unwrap();
 
         graph.add_operation( 'a',
-            [ ( 'e', Variable::owned( sub_graph ) ) ],
+            [ ( 'e', Variable::shared( sub_graph ) ) ],
             |variables| {
                 if let Some( e ) = variables.read( &'e' ).downcast_ref::<FnGraph<char, char>>() {
-                    e.traverser().bfs( 'a' );
+                    e.bfs( 'a' ).unwrap();
                 }
+                Ok( () )
             }
         ).unwrap();
 
         let start = std::time::Instant::now();
-        graph.traverser().bfs( 'a' );
+        graph.bfs( 'a' ).unwrap();
 
         let duration = start.elapsed();
         println!( "Time taken to traverse the graph: {:?}", duration );
     }
 
+    fn fallible_graph( c: &TypedVariable<i32> ) -> FnGraph<char, char> {
+        let mut graph = FnGraph::<char, char>::new();
+        let a = TypedVariable::shared( 1 );
+
+        graph.add_operation( 'a', [], |_| Ok( () ) ).unwrap();
+        graph.add_operation( 'b',
+            [ ( 'a', a.clone().into() ) ],
+            |variables| {
+                // Type mismatch: `a` holds an i32.
+                let a = variables.get_typed::<String>( &'a' )?;
+                println!( "{}", *a );
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'c',
+            [ ( 'c', c.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'c' )? += 1;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'd', [], |_| Ok( () ) ).unwrap();
+
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph.add_edge( 'b', 'c', true ).unwrap();
+        graph.add_edge( 'a', 'd', true ).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_execution_fail_fast() {
+        let c = TypedVariable::shared( 0 );
+        let graph = fallible_graph( &c );
+        let report = graph.executor().policy( ExecutionPolicy::FailFast ).run( 'a' );
+
+        assert!( !report.is_success() );
+        assert_eq!( report.failed().map( |( id, _ )| *id ).collect::<Vec<_>>(), vec![ 'b' ] );
+        assert!( report.status( &'c' ).is_none() );
+        assert_eq!( *c.read(), 0 );
+    }
+
+    #[test]
+    fn test_execution_skip_dependents() {
+        let c = TypedVariable::shared( 0 );
+        let graph = fallible_graph( &c );
+        let report = graph.executor().policy( ExecutionPolicy::SkipDependents ).run( 'a' );
+
+        assert_eq!( report.skipped().copied().collect::<Vec<_>>(), vec![ 'c' ] );
+        assert!( report.status( &'d' ).is_some_and( |status| status.is_completed() ) );
+        assert_eq!( *c.read(), 0 );
+    }
+
+    #[test]
+    fn test_execution_continue() {
+        let c = TypedVariable::shared( 0 );
+        let graph = fallible_graph( &c );
+        let report = graph.executor().policy( ExecutionPolicy::Continue ).run( 'a' );
+
+        assert_eq!( report.len(), 4 );
+        assert_eq!( report.completed().count(), 3 );
+        assert_eq!( *c.read(), 1 );
+    }
+
+    #[test]
+    fn test_incremental_execution() {
+        let mut graph = FnGraph::<char, char>::new();
+        let a = TypedVariable::shared( 1 );
+        let b = TypedVariable::shared( 0 );
+        let c = TypedVariable::shared( 0 );
+        let x = TypedVariable::shared( 1 );
+        let y = TypedVariable::shared( 0 );
+
+        graph.add_operation( 'a',
+            [ ( 'a', a.clone().into() ), ( 'b', b.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'b' )? = *variables.get_typed::<i32>( &'a' )? * 2;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'b',
+            [ ( 'b', b.clone().into() ), ( 'c', c.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'c' )? = *variables.get_typed::<i32>( &'b' )? + 1;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'x',
+            [ ( 'x', x.clone().into() ), ( 'y', y.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'y' )? = *variables.get_typed::<i32>( &'x' )? - 1;
+                Ok( () )
+            }
+        ).unwrap();
+
+        graph.add_edge( 'a', 'b', true ).unwrap();
+        graph.add_edge( 'a', 'x', true ).unwrap();
+
+        let report = graph.executor().incremental( true ).run( 'a' );
+        assert_eq!( report.completed().count(), 3 );
+        assert_eq!( *c.read(), 3 );
+
+        let report = graph.executor().incremental( true ).run( 'a' );
+        assert_eq!( report.cached().count(), 3 );
+
+        *a.write() = 5;
+        let report = graph.executor().incremental( true ).run( 'a' );
+        assert!( report.is_success() );
+        assert_eq!( report.completed().copied().collect::<Vec<_>>(), vec![ 'a', 'b' ] );
+        assert_eq!( report.cached().copied().collect::<Vec<_>>(), vec![ 'x' ] );
+        assert_eq!( *c.read(), 11 );
+    }
+
+    #[test]
+    fn test_conditional_branching() {
+        let mut graph = FnGraph::<char, char>::new();
+        let c = TypedVariable::shared( 6 );
+        let e = TypedVariable::shared( 0 );
+        let f = TypedVariable::shared( 0 );
+
+        graph.add_branch( 'c',
+            [ ( 'c', c.clone().into() ) ],
+            |variables| {
+                match *variables.get_typed::<i32>( &'c' )? % 3 {
+                    0 => Ok( Branch::to( "divisible" ) ),
+                    _ => Ok( Branch::to( "not_divisible" ) )
+                }
+            }
+        ).unwrap();
+        graph.add_operation( 'd',
+            [ ( 'c', c.clone().into() ), ( 'e', e.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'e' )? = *variables.get_typed::<i32>( &'c' )? + 5;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'e',
+            [ ( 'c', c.clone().into() ), ( 'f', f.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'f' )? = *variables.get_typed::<i32>( &'c' )? - 2;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'g', [], |_| Ok( () ) ).unwrap();
+        graph.add_edge( 'c', 'd', true ).unwrap();
+        graph.add_edge( 'c', 'e', true ).unwrap();
+        graph.set_branch_label( 'c', 'd', "divisible" ).unwrap();
+        graph.set_branch_label( 'c', 'e', "not_divisible" ).unwrap();
+        graph.add_edge( 'd', 'g', true ).unwrap();
+        graph.add_edge( 'e', 'g', true ).unwrap();
+
+        let report = graph.executor().run( 'c' );
+        assert!( report.is_success() );
+        assert_eq!( report.completed().copied().collect::<Vec<_>>(), vec![ 'c', 'd', 'g' ] );
+        assert_eq!( report.not_taken().copied().collect::<Vec<_>>(), vec![ 'e' ] );
+        assert_eq!( ( *e.read(), *f.read() ), ( 11, 0 ) );
+        assert!( graph.was_taken( &'c', &'d' ) );
+        assert!( !graph.was_taken( &'c', &'e' ) );
+
+        *c.write() = 7;
+        let report = graph.executor().run( 'c' );
+        assert_eq!( report.not_taken().copied().collect::<Vec<_>>(), vec![ 'd' ] );
+        assert_eq!( *f.read(), 5 );
+        assert!( graph.was_taken( &'e', &'g' ) );
+        assert!( !graph.was_taken( &'d', &'g' ) );
+    }
+
+    #[test]
+    fn test_fixed_point_loop() {
+        let mut graph = FnGraph::<char, char>::new();
+        let x = TypedVariable::shared( 1.0_f64 );
+        let error = TypedVariable::shared( f64::MAX );
+        let result = TypedVariable::shared( 0.0_f64 );
+
+        // Newton's iteration for the square root of 2.
+        graph.add_loop( 'h',
+            [ ( 'e', error.clone().into() ) ],
+            |_| Ok( () ),
+            |variables| variables.get_typed::<f64>( &'e' ).is_ok_and( |error| *error < 1e-12 ),
+            100
+        ).unwrap();
+        graph.add_operation( 'n',
+            [ ( 'x', x.clone().into() ), ( 'e', error.clone().into() ) ],
+            |variables| {
+                let mut x = variables.get_typed_mut::<f64>( &'x' )?;
+                let next = ( *x + 2.0 / *x ) / 2.0;
+                *variables.get_typed_mut::<f64>( &'e' )? = ( next - *x ).abs();
+                *x = next;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'r',
+            [ ( 'x', x.clone().into() ), ( 'r', result.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<f64>( &'r' )? = *variables.get_typed::<f64>( &'x' )?;
+                Ok( () )
+            }
+        ).unwrap();
+
+        graph.add_edge( 'h', 'n', true ).unwrap();
+        graph.add_edge( 'n', 'h', true ).unwrap();
+        graph.add_edge( 'n', 'r', true ).unwrap();
+
+        let report = graph.executor().run( 'h' );
+        let outcome = report.loop_outcome( &'h' ).copied().unwrap();
+        assert!( report.is_success() );
+        assert!( outcome.converged );
+        assert!( outcome.iterations > 1 && outcome.iterations < 100 );
+        assert!( ( *result.read() - 2.0_f64.sqrt() ).abs() < 1e-12 );
+    }
+
+    #[test]
+    fn test_loop_iteration_cap() {
+        let mut graph = FnGraph::<char, char>::new();
+        let count = TypedVariable::shared( 0 );
+
+        graph.add_loop( 'h', [ ( 'c', count.clone().into() ) ], |_| Ok( () ), |_| false, 5 ).unwrap();
+        graph.add_operation( 'b',
+            [ ( 'c', count.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'c' )? += 1;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_edge( 'h', 'b', true ).unwrap();
+        graph.add_edge( 'b', 'h', true ).unwrap();
+
+        let report = graph.executor().run( 'h' );
+        assert_eq!( report.loop_outcome( &'h' ), Some( &LoopOutcome { iterations: 5, converged: false } ) );
+        assert_eq!( *count.read(), 5 );
+    }
+
+    /// A reusable pipeline computing `y = x * 2 + 1`.
+    fn affine_pipeline( x: &TypedVariable<i32>, y: &TypedVariable<i32> ) -> Subgraph<char, char, char> {
+        let mut graph = FnGraph::<char, char>::new();
+        let t = TypedVariable::shared_cloneable( 0 );
+
+        graph.add_operation( 'm',
+            [ ( 'x', x.clone().into() ), ( 't', t.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'t' )? = *variables.get_typed::<i32>( &'x' )? * 2;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'a',
+            [ ( 't', t.clone().into() ), ( 'y', y.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'y' )? = *variables.get_typed::<i32>( &'t' )? + 1;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_edge( 'm', 'a', true ).unwrap();
+
+        Subgraph::new( graph, 'm' ).input( 'x', x.clone() ).output( 'y', y.clone() )
+    }
+
+    #[test]
+    fn test_subgraph_composition() {
+        let x = TypedVariable::shared( 0 );
+        let y = TypedVariable::shared( 0 );
+        let z = TypedVariable::shared( 0 );
+        let mut graph = FnGraph::<char, char>::new();
+
+        graph.add_operation( 'a',
+            [ ( 'x', x.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'x' )? = 3;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_subgraph( 'f', affine_pipeline( &x, &y ) ).unwrap();
+        graph.add_subgraph( 'g', affine_pipeline( &y, &z ) ).unwrap();
+        graph.add_edge( 'a', 'f', true ).unwrap();
+        graph.add_edge( 'f', 'g', true ).unwrap();
+
+        let report = graph.executor().run( 'a' );
+        assert!( report.is_success() );
+        assert_eq!( *y.read(), 7 );
+        assert_eq!( *z.read(), 15 );
+
+        let report = graph.executor().incremental( true ).run( 'a' );
+        assert_eq!( report.cached().count(), 3 );
+
+        // Writing a port makes every subgraph bound to it dirty.
+        *y.write() = 0;
+        let report = graph.executor().incremental( true ).run( 'a' );
+        assert_eq!( report.completed().copied().collect::<Vec<_>>(), vec![ 'f', 'g' ] );
+        assert_eq!( *z.read(), 15 );
+    }
+
+    #[test]
+    fn test_subgraph_failure() {
+        let c = TypedVariable::shared( 0 );
+        let mut graph = FnGraph::<char, char>::new();
+
+        graph.add_subgraph( 's', Subgraph::new( fallible_graph( &c ), 'a' ).output( 'c', c.clone() ) ).unwrap();
+        graph.add_operation( 't', [], |_| Ok( () ) ).unwrap();
+        graph.add_edge( 's', 't', true ).unwrap();
+
+        let report = graph.executor().policy( ExecutionPolicy::SkipDependents ).run( 's' );
+        assert_eq!( report.failed().map( |( id, _ )| *id ).collect::<Vec<_>>(), vec![ 's' ] );
+        assert_eq!( report.skipped().copied().collect::<Vec<_>>(), vec![ 't' ] );
+    }
+
+    #[test]
+    fn test_execution_trace() {
+        let c = TypedVariable::shared( 0 );
+        let graph = fallible_graph( &c );
+        let trace = Trace::new();
+        graph.executor().policy( ExecutionPolicy::Continue ).trace( &trace ).run( 'a' );
+
+        let spans = trace.spans();
+        assert_eq!( spans.len(), 4 );
+        let span = |id: char| spans.iter().find( |span| span.id == id ).unwrap();
+        assert!( span( 'b' ).failed );
+        assert_eq!( span( 'b' ).read, vec![ 'a' ] );
+        assert_eq!( span( 'c' ).read, Vec::<char>::new() );
+        assert_eq!( span( 'c' ).written, vec![ 'c' ] );
+        assert!( spans.iter().all( |span| span.start <= span.end ) );
+        assert_eq!( trace.durations().len(), 4 );
+
+        let json = trace.to_chrome_trace();
+        assert!( json.starts_with( "{\"traceEvents\":[" ) );
+        assert!( json.contains( "\"name\":\"c\",\"cat\":\"operation\",\"ph\":\"X\"" ) );
+        assert!( json.contains( "\"written\":[\"c\"]" ) );
+    }
+
+    #[test]
+    fn test_graph_deep_clone() {
+        let mut graph = FnGraph::<char, char>::new();
+        let x = TypedVariable::shared_cloneable( 1 );
+        let y = TypedVariable::shared_cloneable( 0 );
+
+        graph.add_operation( 'a',
+            [ ( 'x', x.clone().into() ), ( 'y', y.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'y' )? = *variables.get_typed::<i32>( &'x' )? * 10;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_operation( 'b',
+            [ ( 'y', y.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'y' )? += 1;
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+
+        let instance = graph.deep_clone().unwrap();
+        *instance.variable( 'a', &'x' ).unwrap().write_typed::<i32>().unwrap() = 2;
+        assert!( instance.executor().run( 'a' ).is_success() );
+        assert!( graph.executor().run( 'a' ).is_success() );
+
+        assert_eq!( *y.read(), 11 );
+        assert_eq!( *instance.variable( 'b', &'y' ).unwrap().read_typed::<i32>().unwrap(), 21 );
+
+        let mut graph = FnGraph::<char, char>::new();
+        graph.add_operation( 'a', [ ( 'x', Variable::shared( 1 ) ) ], |_| Ok( () ) ).unwrap();
+        assert!( graph.deep_clone().is_err() );
+    }
+
+    #[test]
+    fn test_subgraph_deep_clone() {
+        let x = TypedVariable::shared_cloneable( 3 );
+        let y = TypedVariable::shared_cloneable( 0 );
+        let mut graph = FnGraph::<char, char>::new();
+        graph.add_subgraph( 'f', affine_pipeline( &x, &y ) ).unwrap();
+
+        // The copy's subgraph runs its own inner graph, bound to the copies of the ports.
+        let instance = graph.deep_clone().unwrap();
+        *instance.variable( 'f', &'x' ).unwrap().write_typed::<i32>().unwrap() = 5;
+        assert!( instance.executor().run( 'f' ).is_success() );
+        assert_eq!( *instance.variable( 'f', &'y' ).unwrap().read_typed::<i32>().unwrap(), 11 );
+        assert_eq!( *y.read(), 0 );
+
+        assert!( graph.executor().run( 'f' ).is_success() );
+        assert_eq!( *y.read(), 7 );
+        assert_eq!( *instance.variable( 'f', &'y' ).unwrap().read_typed::<i32>().unwrap(), 11 );
+
+        // A copy of the copy is independent of both.
+        let copy = instance.deep_clone().unwrap();
+        *copy.variable( 'f', &'x' ).unwrap().write_typed::<i32>().unwrap() = 1;
+        assert!( copy.executor().run( 'f' ).is_success() );
+        assert_eq!( *copy.variable( 'f', &'y' ).unwrap().read_typed::<i32>().unwrap(), 3 );
+        assert_eq!( *instance.variable( 'f', &'y' ).unwrap().read_typed::<i32>().unwrap(), 11 );
+        assert_eq!( *y.read(), 7 );
+    }
+
+    #[test]
+    fn test_dot_export() {
+        let mut graph = FnGraph::<String, char>::new();
+        graph.add_operation( String::from( "say \"hi\"" ), [], |_| Ok( () ) ).unwrap();
+        graph.add_operation( String::from( "b" ), [], |_| Ok( () ) ).unwrap();
+        graph.add_edge( String::from( "say \"hi\"" ), String::from( "b" ), false ).unwrap();
+        graph.set_branch_label( String::from( "say \"hi\"" ), String::from( "b" ), "a\\b\nc" ).unwrap();
+
+        assert_eq!( graph.to_dot(), concat!(
+            "digraph G {\n",
+            " \"b\" [label=\"b\"];\n",
+            " \"say \\\"hi\\\"\" [label=\"say \\\"hi\\\"\"];\n",
+            " \"say \\\"hi\\\"\" -> \"b\" [label=\"a\\\\b\\nc\" color=\"red\"];\n",
+            "}\n"
+        ));
+    }
+
+    #[test]
+    fn test_export_write_errors() {
+        let graph = FnGraph::<char, char>::new();
+        let file_name = || String::from( "graphs/missing/graph.out" );
+        assert!( graph.generate_dot_to_file( file_name() ).is_err() );
+        assert!( graph.generate_mermaid_to_file( file_name() ).is_err() );
+        assert!( graph.generate_json_to_file( file_name(), json::Format::default() ).is_err() );
+    }
+
     #[test]
     fn test_mpsc_graph() {
         use crossbeam::channel::{ bounded, Sender, Receiver };
@@ -643,14 +1359,15 @@ mod tests {
                     variables.read( &"a" ).downcast_ref::<i32>(),
                     variables.read( &"a_sender" ).downcast_ref::<Sender<i32>>()
                 ) {
-                    a_sender.send( *a * 2 ).unwrap();
+                    a_sender.send( *a * 2 )?;
                 }
+                Ok( () )
             }
         ).unwrap();
 
         graph.add_operation( "b",
             [
-                ( "b_receiver", Variable::owned( b_receiver ) ),
+                ( "b_receiver", Variable::shared( b_receiver ) ),
                 ( "b_sender", Variable::owned( b_sender ) )
             ],
             |variables| {
@@ -659,15 +1376,16 @@ mod tests {
                     variables.read( &"b_sender" ).downcast_ref::<Sender<i32>>()
                 ) {
                     if let Ok( b ) = b_receiver.try_recv() {
-                        b_sender.send( b * 3 ).unwrap();
+                        b_sender.send( b * 3 )?;
                     }
                 }
+                Ok( () )
             }
         ).unwrap();
 
         graph.add_operation( "c",
             [
-                ( "c_receiver", Variable::owned( c_receiver ) ),
+                ( "c_receiver", Variable::shared( c_receiver ) ),
                 ( "c", c.clone() )
             ],
             |variables| {
@@ -679,6 +1397,7 @@ mod tests {
                         *c = recv + 1;
                     }
                 }
+                Ok( () )
             }
         ).unwrap();
 
@@ -686,13 +1405,13 @@ mod tests {
         graph.add_edge( "b", "c", true ).unwrap();
 
         let start = std::time::Instant::now();
-        graph.traverser().bfs( "a" );
+        graph.bfs( "a" ).unwrap();
         let duration = start.elapsed();
         println!( "Time taken to traverse the graph: {:?}", duration );
         println!( "a: {}", a.read().downcast_ref::<i32>().unwrap() );
         println!( "c: {}", c.read().downcast_ref::<i32>().unwrap() );
 
-        graph.generate_dot_to_file( "graphs/mpsc_graph.dot".to_string() );
+        graph.generate_dot_to_file( "graphs/mpsc_graph.dot".to_string() ).expect( "Failed to write the DOT file" );
 
         dbg!( "{}", graph );
     }
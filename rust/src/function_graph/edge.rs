@@ -0,0 +1,39 @@
+// Copyright 2024 Bewusstsein Labs
+
+/// An edge of an [`FnGraph`](crate::function_graph::FnGraph).
+///
+/// Disabled edges are kept but never followed. A labelled edge only fires when the [`Branch`] selected by its source
+/// admits the label, unlabelled edges fire for every branch that selects anything.
+///
+/// [`Branch`]: crate::function_graph::function::Branch
+///
+#[derive( Debug, Clone, Default, PartialEq, Eq )]
+pub struct Edge {
+    pub enabled: bool,
+    pub label: Option<String>
+}
+
+impl Edge {
+    pub fn new( enabled: bool ) -> Self {
+        Self { enabled, label: None }
+    }
+
+    pub fn with_label<L>( mut self, label: L ) -> Self
+    where
+        L: Into<String>
+    {
+        self.label = Some( label.into() );
+        self
+    }
+
+    /// The label the source's [`Branch`](crate::function_graph::function::Branch) must select for the edge to fire.
+    pub fn label( &self ) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+impl From<bool> for Edge {
+    fn from( enabled: bool ) -> Self {
+        Self::new( enabled )
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::{ BTreeMap, BTreeSet },
+    hash::Hash,
+    ops::ControlFlow
+};
+
+use crate::{
+    traits::{ GetNode, GetEdge, IterNodeIds },
+    function_graph::{
+        FnGraph,
+        function::Branch,
+        edge::Edge,
+        operation::{ Error as OperationError, Loop },
+        schedule::Schedule,
+        trace::{ Probe, Trace }
+    }
+};
+
+/// What the executor does once an operation has failed.
+#[derive( Debug, Clone, Copy, Default, PartialEq, Eq )]
+pub enum ExecutionPolicy {
+    /// Stop the run at the first failure. Operations that have not started yet are not executed.
+    #[default]
+    FailFast,
+    /// Keep running, but skip every operation that depends on a failed or skipped operation.
+    SkipDependents,
+    /// Keep running every operation regardless of failures upstream.
+    Continue
+}
+
+/// The outcome of a single operation in an execution run.
+#[derive( Debug )]
+pub enum Status {
+    Completed,
+    /// Not executed in an incremental run because none of its variables changed since its last execution.
+    Cached,
+    Failed( OperationError ),
+    Skipped,
+    /// Not executed because no predecessor selected a branch leading to it.
+    NotTaken
+}
+
+impl Status {
+    pub fn is_completed( &self ) -> bool {
+        matches!( self, Self::Completed )
+    }
+
+    pub fn is_cached( &self ) -> bool {
+        matches!( self, Self::Cached )
+    }
+
+    /// Whether the operation's outputs are up to date, either because it ran or because it was cached.
+    pub fn is_ok( &self ) -> bool {
+        matches!( self, Self::Completed | Self::Cached )
+    }
+
+    pub fn is_failed( &self ) -> bool {
+        matches!( self, Self::Failed( _ ) )
+    }
+
+    pub fn is_skipped( &self ) -> bool {
+        matches!( self, Self::Skipped )
+    }
+
+    pub fn is_not_taken( &self ) -> bool {
+        matches!( self, Self::NotTaken )
+    }
+
+    fn is_blocking( &self ) -> bool {
+        matches!( self, Self::Failed( _ ) | Self::Skipped )
+    }
+}
+
+/// How a loop ended.
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub struct LoopOutcome {
+    pub iterations: usize,
+    /// Whether the loop's predicate held when it stopped. `false` means the iteration cap was reached, or an
+    /// iteration failed.
+    pub converged: bool
+}
+
+/// The per operation outcome of an execution run, in execution order.
+///
+/// Operations that were never reached, because the run stopped early under [`ExecutionPolicy::FailFast`], have no
+/// entry. Operations inside a loop report the status of their last iteration.
+///
+#[derive( Debug )]
+pub struct ExecutionReport<I> {
+    order: Vec<I>,
+    statuses: BTreeMap<I, Status>,
+    loops: BTreeMap<I, LoopOutcome>,
+    completed: usize
+}
+
+impl<I> ExecutionReport<I>
+where
+    I: Clone + Ord
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            order: Vec::new(),
+            statuses: BTreeMap::new(),
+            loops: BTreeMap::new(),
+            completed: 0
+        }
+    }
+
+    pub(crate) fn record( &mut self, id: I, status: Status ) {
+        if status.is_completed() {
+            self.completed += 1;
+        }
+        if self.statuses.insert( id.clone(), status ).is_none() {
+            self.order.push( id );
+        }
+    }
+
+    pub(crate) fn record_loop( &mut self, header: I, outcome: LoopOutcome ) {
+        self.loops.insert( header, outcome );
+    }
+
+    /// The number of operation executions that completed, counting every loop iteration.
+    pub(crate) fn completed_count( &self ) -> usize {
+        self.completed
+    }
+
+    pub fn loop_outcome( &self, header: &I ) -> Option<&LoopOutcome> {
+        self.loops.get( header )
+    }
+
+    pub fn status( &self, id: &I ) -> Option<&Status> {
+        self.statuses.get( id )
+    }
+
+    pub fn iter( &self ) -> impl Iterator<Item = ( &I, &Status )> {
+        self.order.iter().filter_map( |id| self.statuses.get( id ).map( |status| ( id, status ) ) )
+    }
+
+    pub fn completed( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_completed() ).map( |( id, _ )| id )
+    }
+
+    pub fn failed( &self ) -> impl Iterator<Item = ( &I, &OperationError )> {
+        self.iter().filter_map( |( id, status )| match status {
+            Status::Failed( error ) => Some( ( id, error ) ),
+            _ => None
+        })
+    }
+
+    /// Consumes the report, returning the errors of the failed operations in execution order.
+    pub fn into_failures( mut self ) -> Vec<( I, OperationError )> {
+        self.order.into_iter().filter_map( |id| match self.statuses.remove( &id ) {
+            Some( Status::Failed( error ) ) => Some( ( id, error ) ),
+            _ => None
+        }).collect()
+    }
+
+    pub fn cached( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_cached() ).map( |( id, _ )| id )
+    }
+
+    pub fn skipped( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_skipped() ).map( |( id, _ )| id )
+    }
+
+    pub fn not_taken( &self ) -> impl Iterator<Item = &I> {
+        self.iter().filter( |( _, status )| status.is_not_taken() ).map( |( id, _ )| id )
+    }
+
+    pub fn is_success( &self ) -> bool {
+        !self.statuses.values().any( Status::is_blocking )
+    }
+
+    pub fn len( &self ) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty( &self ) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Runs the operations of a [`FnGraph`] reachable from a start node.
+///
+/// Operations are executed after all of their predecessors, following only enabled edges. An operation runs if the
+/// start node is its only dependency or at least one predecessor selected a [`Branch`] admitting it, so branches that
+/// were not taken are pruned together with everything only they lead to. In an incremental run, operations none of
+/// whose variables were written since their last successful execution are not executed again; writes made by
+/// upstream operations mark the operations downstream of them dirty in turn. Operations with a [`Loop`] repeat
+/// themselves and the operations on cycles back to them.
+///
+pub struct Executor<'a, I, J> {
+    graph: &'a FnGraph<I, J>,
+    policy: ExecutionPolicy,
+    incremental: bool,
+    trace: Option<&'a Trace<I, J>>
+}
+
+impl<'a, I, J> Executor<'a, I, J>
+where
+    I: 'a + Clone + Ord,
+    J: 'static + Clone + Ord + Hash
+{
+    pub fn new( graph: &'a FnGraph<I, J> ) -> Self {
+        Self {
+            graph,
+            policy: ExecutionPolicy::default(),
+            incremental: false,
+            trace: None
+        }
+    }
+
+    pub fn policy( mut self, policy: ExecutionPolicy ) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn incremental( mut self, incremental: bool ) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Records a [`Span`](super::trace::Span) in `trace` for every operation executed.
+    pub fn trace( mut self, trace: &'a Trace<I, J> ) -> Self {
+        self.trace = Some( trace );
+        self
+    }
+
+    pub fn run( &self, start: I ) -> ExecutionReport<I> {
+        let schedule = Schedule::new( start, |id| self.graph.enabled_successors( id ) );
+        let mut report = ExecutionReport::new();
+        self.graph.iter_node_ids().for_each( |( _, operation )| operation.set_active( false ) );
+        let within = schedule.order.iter().cloned().collect();
+        let _ = self.run_sequence( &schedule, &schedule.order, &within, None, &mut report );
+        report
+    }
+
+    /// Runs `sequence` in order, expanding loop headers other than `header` into loops over their bodies.
+    ///
+    /// Returns `ControlFlow::Break` once the run has to stop under [`ExecutionPolicy::FailFast`].
+    ///
+    fn run_sequence( &self, schedule: &Schedule<I>, sequence: &[ I ], within: &BTreeSet<I>, header: Option<&I>, report: &mut ExecutionReport<I> ) -> ControlFlow<()> {
+        let mut looped = BTreeSet::new();
+        for current_id in sequence.iter() {
+            if looped.contains( current_id ) {
+                continue;
+            }
+            let Some( operation ) = self.graph.node( current_id.clone() ) else {
+                continue;
+            };
+            match operation.repeat() {
+                Some( repeat ) if header != Some( current_id ) => {
+                    let body = schedule.cycle( current_id, within, |id| self.graph.enabled_successors( id ) );
+                    looped.extend( body.iter().cloned() );
+                    self.run_loop( schedule, current_id, body, repeat, report )?;
+                },
+                _ => self.run_operation( schedule, current_id, report )?
+            }
+        }
+        ControlFlow::Continue( () )
+    }
+
+    fn run_loop( &self, schedule: &Schedule<I>, header: &I, body: Vec<I>, repeat: &Loop<J>, report: &mut ExecutionReport<I> ) -> ControlFlow<()> {
+        let mut sequence = vec![ header.clone() ];
+        sequence.extend( body );
+        let within = sequence.iter().filter( |id| *id != header ).cloned().collect();
+        let mut outcome = LoopOutcome { iterations: 0, converged: false };
+        while outcome.iterations < repeat.max_iterations {
+            outcome.iterations += 1;
+            let completed = report.completed_count();
+            let flow = self.run_sequence( schedule, &sequence, &within, Some( header ), report );
+            let Some( operation ) = self.graph.node( header.clone() ) else {
+                break;
+            };
+            outcome.converged = repeat.is_converged( operation.variables() );
+            // Stop on failure, on convergence, or once an iteration leaves every operation cached.
+            if flow.is_break() {
+                report.record_loop( header.clone(), outcome );
+                return flow;
+            }
+            if !report.status( header ).is_some_and( Status::is_ok ) || outcome.converged || report.completed_count() == completed {
+                break;
+            }
+        }
+        report.record_loop( header.clone(), outcome );
+        ControlFlow::Continue( () )
+    }
+
+    fn run_operation( &self, schedule: &Schedule<I>, current_id: &I, report: &mut ExecutionReport<I> ) -> ControlFlow<()> {
+        let Some( operation ) = self.graph.node( current_id.clone() ) else {
+            return ControlFlow::Continue( () );
+        };
+        let predecessors = schedule.predecessors( current_id );
+        let blocked = predecessors.iter().any( |predecessor|
+            report.status( predecessor ).is_some_and( Status::is_blocking )
+        );
+        if blocked && self.policy == ExecutionPolicy::SkipDependents {
+            report.record( current_id.clone(), Status::Skipped );
+            return ControlFlow::Continue( () );
+        }
+        let taken = predecessors.is_empty() || predecessors.iter().any( |predecessor| match report.status( predecessor ) {
+            Some( status ) if status.is_ok() => self.graph.node( predecessor.clone() )
+                .and_then( |predecessor| predecessor.last_branch() )
+                .is_some_and( |branch: Branch| branch.admits(
+                    self.graph.edge( predecessor.clone(), current_id.clone() ).and_then( Edge::label )
+                )),
+            Some( status ) if status.is_blocking() => self.policy == ExecutionPolicy::Continue,
+            _ => false
+        });
+        if !taken {
+            report.record( current_id.clone(), Status::NotTaken );
+            return ControlFlow::Continue( () );
+        }
+        operation.set_active( true );
+        if self.incremental && !operation.is_dirty() {
+            report.record( current_id.clone(), Status::Cached );
+            return ControlFlow::Continue( () );
+        }
+        let probe = self.trace.map( |_| Probe::new( operation.variables() ) );
+        let result = operation.execute();
+        if let ( Some( trace ), Some( probe ) ) = ( self.trace, probe ) {
+            probe.finish( trace, current_id.clone(), result.is_err() );
+        }
+        match result {
+            Ok( _ ) => {
+                report.record( current_id.clone(), Status::Completed );
+                ControlFlow::Continue( () )
+            },
+            Err( error ) => {
+                report.record( current_id.clone(), Status::Failed( error ) );
+                match self.policy {
+                    ExecutionPolicy::FailFast => ControlFlow::Break( () ),
+                    _ => ControlFlow::Continue( () )
+                }
+            }
+        }
+    }
+}
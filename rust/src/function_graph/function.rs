@@ -1,11 +1,48 @@
 use std::{
+    collections::HashMap,
     ops::{ Deref, DerefMut },
     sync::Arc,
 };
 
-use crate::function_graph::variable::Variables;
+use crate::function_graph::variable::{ Variable, Variables, Error as VariableError };
 
-type Inner<I> = dyn Fn( &Variables<I> ) + Send + Sync;
+pub type FunctionError = Box<dyn std::error::Error + Send + Sync>;
+pub type FunctionResult = Result<(), FunctionError>;
+pub type BranchResult = Result<Branch, FunctionError>;
+
+type Inner<I> = dyn Fn( &Variables<I> ) -> BranchResult + Send + Sync;
+
+/// The outgoing edges an operation enables when it completes.
+///
+/// Successors are selected by their branch label. Successors without a label are unconditional and fire for every
+/// branch except [`Branch::None`].
+///
+#[derive( Debug, Clone, Default, PartialEq, Eq )]
+pub enum Branch {
+    #[default]
+    All,
+    None,
+    Labels( Vec<String> )
+}
+
+impl Branch {
+    pub fn to<L>( label: L ) -> Self
+    where
+        L: Into<String>
+    {
+        Self::Labels( vec![ label.into() ] )
+    }
+
+    /// Whether an edge with the given branch label fires.
+    pub fn admits( &self, label: Option<&str> ) -> bool {
+        match ( self, label ) {
+            ( Self::All, _ ) => true,
+            ( Self::None, _ ) => false,
+            ( Self::Labels( _ ), None ) => true,
+            ( Self::Labels( labels ), Some( label ) ) => labels.iter().any( |selected| selected == label )
+        }
+    }
+}
 
 #[derive( Clone )]
 pub struct Function<I>( Arc<Box<Inner<I>>> );
@@ -13,7 +50,15 @@ pub struct Function<I>( Arc<Box<Inner<I>>> );
 impl<I> Function<I> {
     pub fn new<T>( function: T ) -> Self
     where
-        T: 'static + Fn( &Variables<I> ) + Send + Sync
+        T: 'static + Fn( &Variables<I> ) -> FunctionResult + Send + Sync
+    {
+        Self( Arc::new( Box::new( move |variables: &Variables<I>| function( variables ).map( |_| Branch::All ) ) ) )
+    }
+
+    /// A function that decides at runtime which of its outgoing edges fire.
+    pub fn branch<T>( function: T ) -> Self
+    where
+        T: 'static + Fn( &Variables<I> ) -> BranchResult + Send + Sync
     {
         Self( Arc::new( Box::new( function ) ) )
     }
@@ -38,3 +83,80 @@ impl<I> std::fmt::Debug for Function<I> {
         write!( f, "Function" )
     }
 }
+
+type Predicate<I> = dyn Fn( &Variables<I> ) -> bool + Send + Sync;
+
+/// A predicate over the variables of an operation.
+#[derive( Clone )]
+pub struct Condition<I>( Arc<Box<Predicate<I>>> );
+
+impl<I> Condition<I> {
+    pub fn new<T>( condition: T ) -> Self
+    where
+        T: 'static + Fn( &Variables<I> ) -> bool + Send + Sync
+    {
+        Self( Arc::new( Box::new( condition ) ) )
+    }
+}
+
+impl<I> Deref for Condition<I> {
+    type Target = Arc<Box<Predicate<I>>>;
+
+    fn deref( &self ) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<I> std::fmt::Debug for Condition<I> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        write!( f, "Condition" )
+    }
+}
+
+type Rebinder<I> = dyn Fn( &mut HashMap<usize, Variable> ) -> Result<( Function<I>, Rebind<I> ), VariableError>
+    + Send + Sync;
+
+/// Rebuilds a function whose closure captures variables of its own, such as the inner graph of a subgraph.
+///
+/// The hook is given the deep clones made so far, keyed by the address of the original variable, and returns a
+/// function bound to copies of the captured state, together with the hook for that copy.
+///
+#[derive( Clone )]
+pub struct Rebind<I>( Arc<Box<Rebinder<I>>> );
+
+impl<I> Rebind<I> {
+    pub fn new<T>( rebind: T ) -> Self
+    where
+        T: 'static + Fn( &mut HashMap<usize, Variable> ) -> Result<( Function<I>, Rebind<I> ), VariableError> + Send + Sync
+    {
+        Self( Arc::new( Box::new( rebind ) ) )
+    }
+}
+
+impl<I> Deref for Rebind<I> {
+    type Target = Arc<Box<Rebinder<I>>>;
+
+    fn deref( &self ) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<I> std::fmt::Debug for Rebind<I> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        write!( f, "Rebind" )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_admits() {
+        assert!( Branch::All.admits( Some( "even" ) ) );
+        assert!( !Branch::None.admits( None ) );
+        assert!( Branch::to( "even" ).admits( Some( "even" ) ) );
+        assert!( Branch::to( "even" ).admits( None ) );
+        assert!( !Branch::to( "even" ).admits( Some( "odd" ) ) );
+    }
+}
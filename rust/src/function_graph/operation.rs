@@ -1,39 +1,132 @@
 use std::{
     any::Any,
+    collections::HashMap,
+    fmt::Debug,
     hash::Hash,
-    ops::Deref
+    ops::Deref,
+    sync::{ Mutex, atomic::{ AtomicBool, Ordering } }
 };
 
 use thiserror::Error;
 
 use crate::function_graph::{
-    variable::{ Variable, Variables },
-    function::Function
+    variable::{ Variable, Variables, Error as VariableError },
+    function::{ Branch, BranchResult, Condition, Function, FunctionError, FunctionResult, Rebind }
 };
 
 #[derive(Error, Debug)]
-pub enum Error {}
+pub enum Error {
+    #[error("Operation failed: {0}")]
+    Failed( FunctionError )
+}
+
+/// Makes an operation the header of a loop.
+///
+/// The loop body is every operation on a cycle of enabled edges back to the header. The header and its body are
+/// executed repeatedly until `until` holds for the header's variables or `max_iterations` is reached.
+///
+#[derive( Debug, Clone )]
+pub struct Loop<I> {
+    pub until: Condition<I>,
+    pub max_iterations: usize
+}
+
+impl<I> Loop<I> {
+    pub fn new<P>( until: P, max_iterations: usize ) -> Self
+    where
+        P: 'static + Fn( &Variables<I> ) -> bool + Send + Sync
+    {
+        Self {
+            until: Condition::new( until ),
+            max_iterations
+        }
+    }
+
+    pub fn is_converged( &self, variables: &Variables<I> ) -> bool {
+        ( self.until )( variables )
+    }
+}
 
 #[derive( Debug )]
 pub struct Operation<I> {
     variables: Variables<I>,
-    function: Function<I>
+    function: Function<I>,
+    rebind: Option<Rebind<I>>,
+    repeat: Option<Loop<I>>,
+    snapshot: Mutex<Option<HashMap<I, u64>>>,
+    branch: Mutex<Option<Branch>>,
+    active: AtomicBool
 }
 
-impl<'a, I> Operation<I>
+impl<I> Operation<I>
 where
-    I: 'a + Ord + Hash
+    I: Ord + Hash
 {
     pub fn new<const N: usize, F>( variables: [ ( I, Variable ); N ], function: F ) -> Self
     where
-        F: 'static + Fn( &Variables<I> ) + Send + Sync
+        F: 'static + Fn( &Variables<I> ) -> FunctionResult + Send + Sync
+    {
+        Self::from_function( variables, Function::new( function ) )
+    }
+
+    /// An operation whose function selects the outgoing edges that fire, see [`Branch`].
+    pub fn branch<const N: usize, F>( variables: [ ( I, Variable ); N ], function: F ) -> Self
+    where
+        F: 'static + Fn( &Variables<I> ) -> BranchResult + Send + Sync
     {
+        Self::from_function( variables, Function::branch( function ) )
+    }
+
+    fn from_function<const N: usize>( variables: [ ( I, Variable ); N ], function: Function<I> ) -> Self {
+        Self::from_parts( Variables::new( variables ), function )
+    }
+
+    pub(crate) fn from_parts( variables: Variables<I>, function: Function<I> ) -> Self {
         Self {
-            variables: Variables::new( variables ),
-            function: Function::new( function )
+            variables,
+            function,
+            rebind: None,
+            repeat: None,
+            snapshot: Mutex::new( None ),
+            branch: Mutex::new( None ),
+            active: AtomicBool::new( false )
         }
     }
 
+    /// Sets the hook that rebuilds the function for a deep clone, see [`Rebind`].
+    pub(crate) fn with_rebind( mut self, rebind: Rebind<I> ) -> Self {
+        self.rebind = Some( rebind );
+        self
+    }
+
+    pub fn with_loop( mut self, repeat: Loop<I> ) -> Self {
+        self.repeat = Some( repeat );
+        self
+    }
+
+    /// The loop this operation is the header of, if any.
+    pub fn repeat( &self ) -> Option<&Loop<I>> {
+        self.repeat.as_ref()
+    }
+
+    pub fn set_repeat( &mut self, repeat: Option<Loop<I>> ) {
+        self.repeat = repeat;
+    }
+
+    /// The branch selected by the last successful execution.
+    pub fn last_branch( &self ) -> Option<Branch> {
+        self.branch.lock().expect( "Failed to acquire branch lock" ).clone()
+    }
+
+    /// Whether the operation was executed, or reused from cache, in the last executor run.
+    pub fn was_active( &self ) -> bool {
+        self.active.load( Ordering::Acquire )
+    }
+
+    pub(crate) fn set_active( &self, active: bool ) {
+        self.active.store( active, Ordering::Release );
+    }
+
     pub fn variables( &self ) -> &Variables<I> {
         &self.variables
     }
@@ -46,18 +139,76 @@ where
         &self.function
     }
 
+    /// The function, for replacing it. A replaced function is shared with deep clones, like any other.
     pub fn function_mut( &mut self ) -> &mut Function<I> {
+        self.rebind = None;
         &mut self.function
     }
 
-    pub fn execute( &self ) -> Result<(), Error> {
-        ( self.function )( &self.variables );
-        Ok( () )
+    pub fn execute( &self ) -> Result<Branch, Error>
+    where
+        I: Clone
+    {
+        let result = ( self.function )( &self.variables ).map_err( Error::Failed );
+        self.record( result.as_ref().ok() );
+        result
     }
 
-    pub fn execute_mut( &mut self ) -> Result<(), Error> {
-        ( self.function )( &mut self.variables );
-        Ok( () )
+    pub fn execute_mut( &mut self ) -> Result<Branch, Error>
+    where
+        I: Clone
+    {
+        let result = ( self.function )( &mut self.variables ).map_err( Error::Failed );
+        self.record( result.as_ref().ok() );
+        result
+    }
+
+    /// A copy of the operation bound to deep clones of its variables, see [`Variables::deep_clone`].
+    ///
+    /// The copy shares the function, unless the operation has a [`Rebind`] hook that rebuilds it around copies of the
+    /// state the function captures. The copy has no execution history, so it is dirty and has no selected branch.
+    ///
+    pub(crate) fn deep_clone_with( &self, clones: &mut HashMap<usize, Variable> ) -> Result<Self, VariableError>
+    where
+        I: Clone + Debug
+    {
+        let variables = self.variables.deep_clone_with( clones )?;
+        let mut operation = match &self.rebind {
+            Some( rebind ) => {
+                let ( function, rebind ) = rebind( clones )?;
+                Self::from_parts( variables, function ).with_rebind( rebind )
+            }
+            None => Self::from_parts( variables, self.function.clone() )
+        };
+        operation.repeat = self.repeat.clone();
+        Ok( operation )
+    }
+
+    /// Whether any bound variable has been written since the last successful execution.
+    ///
+    /// An operation that has never run, or whose last run failed, is always dirty.
+    ///
+    pub fn is_dirty( &self ) -> bool {
+        let snapshot = self.snapshot.lock().expect( "Failed to acquire snapshot lock" );
+        match snapshot.as_ref() {
+            Some( versions ) => self.variables.iter().any( |( key, variable )|
+                versions.get( key ) != Some( &variable.version() )
+            ),
+            None => true
+        }
+    }
+
+    /// Forgets the last successful execution, so the operation is dirty until it runs again.
+    pub fn invalidate( &self ) {
+        *self.snapshot.lock().expect( "Failed to acquire snapshot lock" ) = None;
+    }
+
+    fn record( &self, branch: Option<&Branch> )
+    where
+        I: Clone
+    {
+        *self.snapshot.lock().expect( "Failed to acquire snapshot lock" ) = branch.map( |_| self.variables.versions() );
+        *self.branch.lock().expect( "Failed to acquire branch lock" ) = branch.cloned();
     }
 }
 
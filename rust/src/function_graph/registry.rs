@@ -0,0 +1,490 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    any::{ Any, TypeId },
+    collections::{ BTreeMap, HashMap },
+    fmt::{ Debug, Display, Write as _ },
+    hash::Hash,
+    str::FromStr,
+    sync::Arc
+};
+
+use thiserror::Error;
+
+use crate::{
+    traits::{ GetNode, GetNodeMut, AddNode, AddEdge, IterNodeIds, IterAdjacent },
+    function_graph::{
+        FnGraph,
+        variable::{ Variable, Variables },
+        function::{ BranchResult, Condition, Function, FunctionResult },
+        operation::{ Loop, Operation },
+        edge::Edge
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Operation {0} was not built from a registered function")]
+    UnregisteredOperation( String ),
+    #[error("Loop of operation {0} does not use a registered condition")]
+    UnregisteredCondition( String ),
+    #[error("Variable {0} holds a value of an unregistered type")]
+    UnregisteredType( String ),
+    #[error("{0:?} cannot be saved as an id or key, it is empty or contains whitespace")]
+    InvalidName( String )
+}
+
+type Parse = dyn Fn( &str, bool ) -> Result<Variable, String> + Send + Sync;
+type Format = dyn Fn( &( dyn Any + Send + Sync ) ) -> Option<String> + Send + Sync;
+
+struct ValueType {
+    name: String,
+    parse: Arc<Parse>,
+    format: Arc<Format>
+}
+
+/// Names for the functions, loop conditions and value types a [`FnGraph`] is built from.
+///
+/// Closures cannot be saved, so a graph is stored as the names of the registered functions its operations run, the
+/// variables they are bound to with the initial values of registered types, and its edges. Loading the text with the
+/// same registry rebuilds an equivalent graph. Only operations built by [`Registry::operation`] or loaded by the
+/// registry can be saved.
+///
+/// The format is line based, blank lines and lines starting with `#` are ignored:
+///
+/// ```text
+/// variable x i32 1
+/// owned y i32 0
+/// operation a scale x=x y=y
+/// loop a converged 10
+/// edge a b label even
+/// edge b c disabled
+/// ```
+///
+/// `variable` declares a shared variable and `owned` a variable that may be bound once, followed by its name, the
+/// name of its type and its value. `operation` adds an operation running a registered function, bound to variables
+/// as `key=variable`. `loop` makes an operation a loop header with a registered condition and an iteration cap.
+/// `edge` connects two operations, optionally followed by `disabled` and by `label` with the edge's branch label.
+/// Values and labels run to the end of the line, with backslashes and line breaks escaped as `\\`, `\n` and `\r`.
+/// Ids and keys are single words.
+///
+pub struct Registry<J> {
+    functions: BTreeMap<String, Function<J>>,
+    conditions: BTreeMap<String, Condition<J>>,
+    types: Vec<ValueType>,
+    type_ids: HashMap<TypeId, usize>
+}
+
+impl<J> Default for Registry<J> {
+    fn default() -> Self {
+        Self {
+            functions: BTreeMap::new(),
+            conditions: BTreeMap::new(),
+            types: Vec::new(),
+            type_ids: HashMap::new()
+        }
+    }
+}
+
+impl<J> Registry<J>
+where
+    J: 'static + Clone + Ord + Hash
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<N, F>( &mut self, name: N, function: F ) -> &mut Self
+    where
+        N: Into<String>,
+        F: 'static + Fn( &Variables<J> ) -> FunctionResult + Send + Sync
+    {
+        self.functions.insert( name.into(), Function::new( function ) );
+        self
+    }
+
+    /// Registers a function that selects the outgoing edges that fire, see [`Branch`](super::function::Branch).
+    pub fn register_branch<N, F>( &mut self, name: N, function: F ) -> &mut Self
+    where
+        N: Into<String>,
+        F: 'static + Fn( &Variables<J> ) -> BranchResult + Send + Sync
+    {
+        self.functions.insert( name.into(), Function::branch( function ) );
+        self
+    }
+
+    pub fn register_condition<N, P>( &mut self, name: N, condition: P ) -> &mut Self
+    where
+        N: Into<String>,
+        P: 'static + Fn( &Variables<J> ) -> bool + Send + Sync
+    {
+        self.conditions.insert( name.into(), Condition::new( condition ) );
+        self
+    }
+
+    /// Registers a value type, so variables holding a `T` can be saved and loaded.
+    ///
+    /// Loaded variables are cloneable, see [`Variable::deep_clone`].
+    ///
+    pub fn register_type<T, N>( &mut self, name: N ) -> &mut Self
+    where
+        T: Any + Clone + Display + FromStr + Send + Sync,
+        T::Err: Display,
+        N: Into<String>
+    {
+        let value_type = ValueType {
+            name: name.into(),
+            parse: Arc::new( |text: &str, shared: bool| match text.parse::<T>() {
+                Ok( value ) if shared => Ok( Variable::shared_cloneable( value ) ),
+                Ok( value ) => Ok( Variable::owned( value ) ),
+                Err( error ) => Err( error.to_string() )
+            }),
+            format: Arc::new( |value: &( dyn Any + Send + Sync )| value.downcast_ref::<T>().map( T::to_string ) )
+        };
+        let index = match self.types.iter().position( |registered| registered.name == value_type.name ) {
+            Some( index ) => {
+                self.types[ index ] = value_type;
+                index
+            },
+            None => {
+                self.types.push( value_type );
+                self.types.len() - 1
+            }
+        };
+        self.type_ids.insert( TypeId::of::<T>(), index );
+        self
+    }
+
+    /// An operation running the function registered as `name`, which the registry can save.
+    pub fn operation<const N: usize>( &self, name: &str, variables: [ ( J, Variable ); N ] ) -> Option<Operation<J>> {
+        self.functions.get( name ).map( |function| Operation::from_parts( Variables::new( variables ), function.clone() ) )
+    }
+
+    pub fn condition( &self, name: &str ) -> Option<&Condition<J>> {
+        self.conditions.get( name )
+    }
+
+    fn function_name( &self, function: &Function<J> ) -> Option<&str> {
+        self.functions.iter()
+            .find( |( _, registered )| Arc::ptr_eq( &**registered, &**function ) )
+            .map( |( name, _ )| name.as_str() )
+    }
+
+    fn condition_name( &self, condition: &Condition<J> ) -> Option<&str> {
+        self.conditions.iter()
+            .find( |( _, registered )| Arc::ptr_eq( &**registered, &**condition ) )
+            .map( |( name, _ )| name.as_str() )
+    }
+
+    /// Writes the structure and initial variable values of `graph` in the registry's text format.
+    pub fn save<I>( &self, graph: &FnGraph<I, J> ) -> Result<String, Error>
+    where
+        I: Clone + Ord + Display,
+        J: Display
+    {
+        let mut names: HashMap<usize, String> = HashMap::new();
+        let mut variables = String::new();
+        let mut operations = String::new();
+        let mut edges = String::new();
+        for ( id, operation ) in graph.iter_node_ids() {
+            let function = self.function_name( operation.function() ).ok_or_else( || Error::UnregisteredOperation( id.to_string() ) )?;
+            let _ = write!( operations, "operation {} {}", name( &id )?, function );
+
+            let mut bindings: Vec<_> = operation.variables().iter().collect();
+            bindings.sort_by_key( |( key, _ )| *key );
+            for ( key, variable ) in bindings {
+                let name = match names.get( &variable.address() ) {
+                    Some( name ) => name.clone(),
+                    None => {
+                        let name = format!( "v{}", names.len() );
+                        let value_type = self.type_ids.get( &variable.type_id() )
+                            .map( |index| &self.types[ *index ] )
+                            .ok_or_else( || Error::UnregisteredType( format!( "{}.{}", id, key ) ) )?;
+                        let value = ( value_type.format )( &*variable.read() ).ok_or_else( || Error::UnregisteredType( format!( "{}.{}", id, key ) ) )?;
+                        let kind = match variable {
+                            Variable::Owned( .. ) => "owned",
+                            Variable::Shared( .. ) => "variable"
+                        };
+                        let _ = writeln!( variables, "{} {} {} {}", kind, name, value_type.name, escape( &value ) );
+                        names.insert( variable.address(), name.clone() );
+                        name
+                    }
+                };
+                let _ = write!( operations, " {}={}", self::name( key )?, name );
+            }
+            operations.push( '\n' );
+
+            if let Some( repeat ) = operation.repeat() {
+                let condition = self.condition_name( &repeat.until ).ok_or_else( || Error::UnregisteredCondition( id.to_string() ) )?;
+                let _ = writeln!( operations, "loop {} {} {}", id, condition, repeat.max_iterations );
+            }
+            for ( next_id, edge ) in graph.iter_adjacent( id.clone() ) {
+                let _ = write!( edges, "edge {} {}{}", id, name( &next_id )?, if edge.enabled { "" } else { " disabled" } );
+                if let Some( label ) = edge.label() {
+                    let _ = write!( edges, " label {}", escape( label ) );
+                }
+                edges.push( '\n' );
+            }
+        }
+        Ok( variables + &operations + &edges )
+    }
+
+    /// Builds a graph from the registry's text format.
+    pub fn load<I>( &self, text: &str ) -> Result<FnGraph<I, J>, Error>
+    where
+        I: Clone + Ord + FromStr,
+        J: FromStr
+    {
+        let mut graph = FnGraph::<I, J>::new();
+        let mut variables: HashMap<&str, Variable> = HashMap::new();
+        for ( index, line ) in text.lines().enumerate() {
+            let error = |message: String| Error::Parse { line: index + 1, message };
+            let Some( ( command, rest ) ) = split_field( line ) else {
+                continue;
+            };
+            if command.starts_with( '#' ) {
+                continue;
+            }
+            let mut words = rest.split_whitespace();
+            let mut word = |what: &str| words.next().ok_or_else( || error( format!( "Missing {}", what ) ) );
+            let parse_id = |word: &str| word.parse::<I>().map_err( |_| error( format!( "Invalid id {}", word ) ) );
+            match command {
+                "variable" | "owned" => {
+                    let Some( ( name, type_name, value ) ) = split_field( rest )
+                        .and_then( |( name, rest )| split_field( rest ).map( |( type_name, value )| ( name, type_name, value ) ) ) else {
+                        return Err( error( String::from( "Expected a name, a type and a value" ) ) );
+                    };
+                    let value_type = self.types.iter()
+                        .find( |value_type| value_type.name == type_name )
+                        .ok_or_else( || error( format!( "Unknown type {}", type_name ) ) )?;
+                    let value = unescape( value );
+                    let variable = ( value_type.parse )( &value, command == "variable" ).map_err( error )?;
+                    if variables.insert( name, variable ).is_some() {
+                        return Err( error( format!( "Variable {} declared twice", name ) ) );
+                    }
+                },
+                "operation" => {
+                    let id = parse_id( word( "operation id" )? )?;
+                    let name = word( "function name" )?;
+                    let function = self.functions.get( name ).ok_or_else( || error( format!( "Unknown function {}", name ) ) )?;
+                    let mut bound = Variables::new( [] );
+                    for binding in words {
+                        let ( key, name ) = binding.split_once( '=' ).ok_or_else( || error( format!( "Expected key=variable, found {}", binding ) ) )?;
+                        let key = key.parse::<J>().map_err( |_| error( format!( "Invalid key {}", key ) ) )?;
+                        let variable = match variables.get( name ) {
+                            Some( variable @ Variable::Shared( .. ) ) => variable.clone(),
+                            Some( Variable::Owned( .. ) ) => variables.remove( name ).expect( "Variable is declared" ),
+                            None => return Err( error( format!( "Unknown or already bound variable {}", name ) ) )
+                        };
+                        bound.insert( key, variable );
+                    }
+                    graph.add_node( id, Operation::from_parts( bound, function.clone() ) );
+                },
+                "loop" => {
+                    let id = parse_id( word( "operation id" )? )?;
+                    let name = word( "condition" )?;
+                    let until = self.conditions.get( name ).ok_or_else( || error( format!( "Unknown condition {}", name ) ) )?;
+                    let max_iterations = word( "iteration cap" )?.parse::<usize>().map_err( |error_| error( error_.to_string() ) )?;
+                    graph.node_mut( id ).ok_or_else( || error( String::from( "Unknown operation" ) ) )?
+                        .set_repeat( Some( Loop { until: until.clone(), max_iterations } ) );
+                },
+                "edge" => {
+                    let ( id1, rest ) = split_field( rest ).ok_or_else( || error( String::from( "Missing source id" ) ) )?;
+                    let ( id2, mut rest ) = split_field( rest ).ok_or_else( || error( String::from( "Missing target id" ) ) )?;
+                    let ( id1, id2 ) = ( parse_id( id1 )?, parse_id( id2 )? );
+                    let mut edge = Edge::new( true );
+                    while let Some( ( option, tail ) ) = split_field( rest ) {
+                        match option {
+                            "disabled" => edge.enabled = false,
+                            "label" => {
+                                edge.label = Some( unescape( tail ) );
+                                break;
+                            },
+                            other => return Err( error( format!( "Unexpected {}", other ) ) )
+                        }
+                        rest = tail;
+                    }
+                    if graph.node( id1.clone() ).is_none() || graph.node( id2.clone() ).is_none() {
+                        return Err( error( String::from( "Unknown operation" ) ) );
+                    }
+                    AddEdge::add_edge( &mut graph, id1, id2, edge );
+                },
+                other => return Err( error( format!( "Unknown command {}", other ) ) )
+            }
+        }
+        Ok( graph )
+    }
+}
+
+impl<J> Debug for Registry<J> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        f.debug_struct( "Registry" )
+            .field( "functions", &self.functions.keys().collect::<Vec<_>>() )
+            .field( "conditions", &self.conditions.keys().collect::<Vec<_>>() )
+            .field( "types", &self.types.iter().map( |value_type| &value_type.name ).collect::<Vec<_>>() )
+            .finish()
+    }
+}
+
+/// Splits off the first field of `text`, skipping the whitespace before it.
+///
+/// The rest starts after the single whitespace character following the field, so a value at the end of a line keeps
+/// its own leading and trailing whitespace.
+///
+fn split_field( text: &str ) -> Option<( &str, &str )> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    let ( field, rest ) = text.split_at( text.find( char::is_whitespace ).unwrap_or( text.len() ) );
+    let mut rest = rest.chars();
+    rest.next();
+    Some( ( field, rest.as_str() ) )
+}
+
+/// The text of an id or key, which must be a single word to be read back.
+fn name<T>( value: &T ) -> Result<String, Error>
+where
+    T: Display
+{
+    let text = value.to_string();
+    match text.is_empty() || text.contains( char::is_whitespace ) {
+        true => Err( Error::InvalidName( text ) ),
+        false => Ok( text )
+    }
+}
+
+fn escape( value: &str ) -> String {
+    value.replace( '\\', "\\\\" ).replace( '\n', "\\n" ).replace( '\r', "\\r" )
+}
+
+fn unescape( value: &str ) -> String {
+    let mut unescaped = String::with_capacity( value.len() );
+    let mut characters = value.chars();
+    while let Some( character ) = characters.next() {
+        match ( character, character == '\\' ) {
+            ( _, true ) => match characters.next() {
+                Some( 'n' ) => unescaped.push( '\n' ),
+                Some( 'r' ) => unescaped.push( '\r' ),
+                Some( other ) => unescaped.push( other ),
+                None => unescaped.push( '\\' )
+            },
+            ( character, false ) => unescaped.push( character )
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::GetEdge;
+
+    fn registry() -> Registry<String> {
+        let mut registry = Registry::new();
+        registry
+            .register( "scale", |variables: &Variables<String>| {
+                let x = *variables.get_typed::<i32>( &String::from( "x" ) )?;
+                *variables.get_typed_mut::<i32>( &String::from( "y" ) )? = x * 2;
+                Ok( () )
+            })
+            .register( "greet", |variables: &Variables<String>| {
+                variables.get_typed_mut::<String>( &String::from( "s" ) )?.push_str( ", world" );
+                Ok( () )
+            })
+            .register_condition( "large", |variables: &Variables<String>| {
+                variables.get_typed::<i32>( &String::from( "y" ) ).is_ok_and( |y| *y > 10 )
+            })
+            .register_type::<i32, _>( "i32" )
+            .register_type::<String, _>( "string" );
+        registry
+    }
+
+    const PIPELINE: &str = "
+        # A two step pipeline
+        variable v0 i32 3
+        variable v1 i32 0
+        owned v2 string hello\\nthere
+        operation a scale x=v0 y=v1
+        loop a large 5
+        operation b greet s=v2
+        edge a b label even
+    ";
+
+    #[test]
+    fn test_registry_load_and_run() {
+        let graph = registry().load::<char>( PIPELINE ).unwrap();
+        assert!( graph.executor().run( 'a' ).is_success() );
+
+        let y = graph.variable( 'a', &String::from( "y" ) ).unwrap().read_typed::<i32>().unwrap();
+        assert_eq!( *y, 6 );
+        let s = graph.variable( 'b', &String::from( "s" ) ).unwrap().read_typed::<String>().unwrap();
+        assert_eq!( s.as_str(), "hello\nthere, world" );
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let registry = registry();
+        let graph = registry.load::<char>( PIPELINE ).unwrap();
+        let text = registry.save( &graph ).unwrap();
+        assert_eq!( registry.save( &registry.load::<char>( &text ).unwrap() ).unwrap(), text );
+        assert!( text.contains( "operation a scale x=v0 y=v1\n" ) );
+        assert!( text.contains( "loop a large 5\n" ) );
+        assert!( text.contains( "edge a b label even\n" ) );
+        assert!( text.contains( "owned v2 string hello\\nthere\n" ) );
+    }
+
+    #[test]
+    fn test_registry_value_whitespace() {
+        let registry = registry();
+        let graph = registry.load::<char>( "variable   v0\tstring  padded \noperation b greet s=v0" ).unwrap();
+        let s = graph.variable( 'b', &String::from( "s" ) ).unwrap().read_typed::<String>().unwrap();
+        assert_eq!( s.as_str(), " padded " );
+        drop( s );
+
+        let text = registry.save( &graph ).unwrap();
+        assert!( text.contains( "variable v0 string  padded \n" ) );
+        assert_eq!( registry.save( &registry.load::<char>( &text ).unwrap() ).unwrap(), text );
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        let registry = registry();
+        assert!( matches!( registry.load::<char>( "operation a missing" ), Err( Error::Parse { line: 1, .. } ) ) );
+        assert!( matches!( registry.load::<char>( "variable v0 i32 x" ), Err( Error::Parse { .. } ) ) );
+        assert!( matches!(
+            registry.load::<char>( "owned v0 i32 1\noperation a scale x=v0 y=v0" ),
+            Err( Error::Parse { line: 2, .. } )
+        ));
+
+        let mut graph = FnGraph::<char, String>::new();
+        graph.add_operation( 'a', [], |_| Ok( () ) ).unwrap();
+        assert!( matches!( registry.save( &graph ), Err( Error::UnregisteredOperation( _ ) ) ) );
+
+        let mut graph = FnGraph::<String, String>::new();
+        graph.add_node( String::from( "two words" ), registry.operation( "greet", [] ).unwrap() );
+        assert!( matches!( registry.save( &graph ), Err( Error::InvalidName( _ ) ) ) );
+
+        let mut graph = FnGraph::<char, String>::new();
+        graph.add_node( 'a', registry.operation( "greet", [ ( String::from( "s t" ), Variable::owned( String::new() ) ) ] ).unwrap() );
+        assert!( matches!( registry.save( &graph ), Err( Error::InvalidName( _ ) ) ) );
+    }
+
+    #[test]
+    fn test_registry_label_whitespace() {
+        let registry = registry();
+        let mut graph = registry.load::<char>( "variable v0 string s\noperation a greet s=v0\noperation b greet s=v0" ).unwrap();
+        graph.add_edge( 'a', 'b', false ).unwrap();
+        graph.set_branch_label( 'a', 'b', " large\\values\n" ).unwrap();
+
+        let text = registry.save( &graph ).unwrap();
+        assert!( text.contains( "edge a b disabled label  large\\\\values\\n\n" ) );
+        let loaded = registry.load::<char>( &text ).unwrap();
+        let edge = loaded.edge( 'a', 'b' ).unwrap();
+        assert_eq!( edge.label(), Some( " large\\values\n" ) );
+        assert!( !edge.enabled );
+        assert_eq!( registry.save( &loaded ).unwrap(), text );
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::collections::{ BTreeMap, BTreeSet };
+
+/// The order in which the operations reachable from a start node are executed.
+///
+/// Nodes are ordered so that every node comes after its predecessors. Edges that close a cycle back to a node that is
+/// still being visited are ignored, matching the behaviour of the visited set in the traversers.
+///
+#[derive( Debug, Clone )]
+pub(crate) struct Schedule<I> {
+    pub(crate) order: Vec<I>,
+    pub(crate) predecessors: BTreeMap<I, Vec<I>>
+}
+
+impl<I> Schedule<I>
+where
+    I: Clone + Ord
+{
+    pub(crate) fn new<F>( start: I, successors: F ) -> Self
+    where
+        F: Fn( &I ) -> Vec<I>
+    {
+        let mut postorder = Vec::new();
+        let mut predecessors: BTreeMap<I, Vec<I>> = BTreeMap::new();
+        let mut active = BTreeSet::new();
+        let mut finished = BTreeSet::new();
+        let mut stack = vec![ ( start.clone(), successors( &start ), 0 ) ];
+        active.insert( start.clone() );
+        predecessors.insert( start, Vec::new() );
+
+        while let Some( ( current_id, next_ids, index ) ) = stack.last_mut() {
+            if let Some( next_id ) = next_ids.get( *index ).cloned() {
+                *index += 1;
+                if active.contains( &next_id ) {
+                    continue;
+                }
+                predecessors.entry( next_id.clone() ).or_default().push( current_id.clone() );
+                if !finished.contains( &next_id ) {
+                    active.insert( next_id.clone() );
+                    let next_successors = successors( &next_id );
+                    stack.push( ( next_id, next_successors, 0 ) );
+                }
+            } else {
+                let ( current_id, _, _ ) = stack.pop().expect( "Stack is not empty" );
+                active.remove( &current_id );
+                finished.insert( current_id.clone() );
+                postorder.push( current_id );
+            }
+        }
+
+        postorder.reverse();
+        Self { order: postorder, predecessors }
+    }
+
+    pub(crate) fn predecessors( &self, id: &I ) -> &[ I ] {
+        self.predecessors.get( id ).map( Vec::as_slice ).unwrap_or( &[] )
+    }
+
+    /// The nodes in `within` that lie on a cycle through `header`, in schedule order, excluding the header itself.
+    pub(crate) fn cycle<F>( &self, header: &I, within: &BTreeSet<I>, successors: F ) -> Vec<I>
+    where
+        F: Fn( &I ) -> Vec<I>
+    {
+        let mut reverse: BTreeMap<I, Vec<I>> = BTreeMap::new();
+        let mut forward = BTreeSet::new();
+        let mut stack = vec![ header.clone() ];
+        while let Some( current_id ) = stack.pop() {
+            for next_id in successors( &current_id ).into_iter().filter( |next_id| within.contains( next_id ) ) {
+                reverse.entry( next_id.clone() ).or_default().push( current_id.clone() );
+                if forward.insert( next_id.clone() ) {
+                    stack.push( next_id );
+                }
+            }
+        }
+
+        let mut backward = BTreeSet::new();
+        let mut stack = vec![ header.clone() ];
+        while let Some( current_id ) = stack.pop() {
+            for previous_id in reverse.get( &current_id ).into_iter().flatten() {
+                if backward.insert( previous_id.clone() ) {
+                    stack.push( previous_id.clone() );
+                }
+            }
+        }
+
+        self.order.iter()
+            .filter( |id| *id != header && forward.contains( *id ) && backward.contains( *id ) )
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_orders_dependencies_first() {
+        let edges = BTreeMap::from( [
+            ( 'a', vec![ 'b', 'c' ] ),
+            ( 'b', vec![ 'd' ] ),
+            ( 'c', vec![ 'd' ] ),
+            ( 'd', vec![ 'a' ] )
+        ] );
+        let schedule = Schedule::new( 'a', |id| edges.get( id ).cloned().unwrap_or_default() );
+        let position = |id: char| schedule.order.iter().position( |order_id| *order_id == id ).unwrap();
+
+        assert_eq!( schedule.order.len(), 4 );
+        assert_eq!( position( 'a' ), 0 );
+        assert!( position( 'b' ) < position( 'd' ) );
+        assert!( position( 'c' ) < position( 'd' ) );
+        assert_eq!( schedule.predecessors( &'a' ), &[] );
+        assert_eq!( schedule.predecessors( &'d' ), &[ 'b', 'c' ] );
+    }
+
+    #[test]
+    fn test_schedule_cycle() {
+        let edges = BTreeMap::from( [
+            ( 'a', vec![ 'b' ] ),
+            ( 'b', vec![ 'c', 'e' ] ),
+            ( 'c', vec![ 'd' ] ),
+            ( 'd', vec![ 'b' ] )
+        ] );
+        let successors = |id: &char| edges.get( id ).cloned().unwrap_or_default();
+        let schedule = Schedule::new( 'a', successors );
+        let within = schedule.order.iter().copied().collect();
+
+        assert_eq!( schedule.cycle( &'b', &within, successors ), vec![ 'c', 'd' ] );
+        assert!( schedule.cycle( &'a', &within, successors ).is_empty() );
+    }
+}
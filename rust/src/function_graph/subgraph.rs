@@ -0,0 +1,139 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::Arc
+};
+
+use thiserror::Error;
+
+use crate::function_graph::{
+    FnGraph,
+    variable::Variable,
+    function::{ Function, Rebind },
+    operation::{ Operation, Error as OperationError },
+    executor::ExecutionPolicy
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Subgraph operation failed: {0}")]
+    Failed( OperationError )
+}
+
+/// A [`FnGraph`] packaged as a single operation of an enclosing graph.
+///
+/// Ports are the variables through which the subgraph exchanges data with the enclosing graph. They are bound to the
+/// outer operation under the port's key, so they are shared between the subgraph's own operations and the operations
+/// around it. In an incremental run the outer operation is dirty whenever a port has been written. A reusable
+/// pipeline is a function that builds its graph around the port variables it is given.
+///
+#[derive( Debug )]
+pub struct Subgraph<I, J, K> {
+    graph: FnGraph<I, J>,
+    start: I,
+    inputs: Vec<( K, Variable )>,
+    outputs: Vec<( K, Variable )>,
+    policy: ExecutionPolicy,
+    incremental: bool
+}
+
+impl<I, J, K> Subgraph<I, J, K>
+where
+    I: 'static + Clone + Ord + Send + Sync,
+    J: 'static + Clone + Ord + Hash + Send + Sync + Debug,
+    K: 'static + Ord + Hash
+{
+    pub fn new( graph: FnGraph<I, J>, start: I ) -> Self {
+        Self {
+            graph,
+            start,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            policy: ExecutionPolicy::default(),
+            incremental: false
+        }
+    }
+
+    pub fn input<V>( mut self, key: K, variable: V ) -> Self
+    where
+        V: Into<Variable>
+    {
+        self.inputs.push( ( key, variable.into() ) );
+        self
+    }
+
+    pub fn output<V>( mut self, key: K, variable: V ) -> Self
+    where
+        V: Into<Variable>
+    {
+        self.outputs.push( ( key, variable.into() ) );
+        self
+    }
+
+    pub fn policy( mut self, policy: ExecutionPolicy ) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Whether the inner graph is executed incrementally, see [`Executor::incremental`](super::executor::Executor::incremental).
+    pub fn incremental( mut self, incremental: bool ) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    pub fn inputs( &self ) -> impl Iterator<Item = &K> {
+        self.inputs.iter().map( |( key, _ )| key )
+    }
+
+    pub fn outputs( &self ) -> impl Iterator<Item = &K> {
+        self.outputs.iter().map( |( key, _ )| key )
+    }
+
+    /// An operation that runs the whole subgraph from its start node, failing with the first failed inner operation.
+    ///
+    /// A deep clone of the operation runs a deep clone of the inner graph, bound to the copies of the ports.
+    ///
+    pub fn into_operation( self ) -> Operation<K> {
+        let Self { graph, start, inputs, outputs, policy, incremental } = self;
+        let variables = inputs.into_iter().chain( outputs ).collect();
+        let ( function, rebind ) = bind( Arc::new( graph ), start, policy, incremental );
+        Operation::from_parts( variables, function ).with_rebind( rebind )
+    }
+}
+
+/// The function running `graph`, and the hook rebuilding it around a deep clone of `graph`.
+fn bind<I, J, K>( graph: Arc<FnGraph<I, J>>, start: I, policy: ExecutionPolicy, incremental: bool ) -> ( Function<K>, Rebind<K> )
+where
+    I: 'static + Clone + Ord + Send + Sync,
+    J: 'static + Clone + Ord + Hash + Send + Sync + Debug,
+    K: 'static
+{
+    let function = {
+        let graph = graph.clone();
+        let start = start.clone();
+        Function::new( move |_| {
+            let report = graph.executor().policy( policy ).incremental( incremental ).run( start.clone() );
+            match report.into_failures().into_iter().next() {
+                Some( ( _, error ) ) => Err( Box::new( Error::Failed( error ) ) ),
+                None => Ok( () )
+            }
+        })
+    };
+    let rebind = Rebind::new( move |clones| {
+        Ok( bind( Arc::new( graph.deep_clone_with( clones )? ), start.clone(), policy, incremental ) )
+    });
+    ( function, rebind )
+}
+
+impl<I, J, K> From<Subgraph<I, J, K>> for Operation<K>
+where
+    I: 'static + Clone + Ord + Send + Sync,
+    J: 'static + Clone + Ord + Hash + Send + Sync + Debug,
+    K: 'static + Ord + Hash
+{
+    fn from( subgraph: Subgraph<I, J, K> ) -> Self {
+        subgraph.into_operation()
+    }
+}
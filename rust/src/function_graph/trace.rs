@@ -0,0 +1,197 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    hash::Hash,
+    io::{ self, Write },
+    sync::Mutex,
+    thread::{ self, ThreadId },
+    time::{ Duration, Instant }
+};
+
+use crate::{
+    graph::quote,
+    function_graph::variable::{ Variables, Version }
+};
+
+/// One execution of an operation.
+///
+/// Accesses are attributed by comparing the variables' lock counters before and after the execution, so operations
+/// running concurrently on the same variables may be attributed each other's accesses.
+///
+#[derive( Debug, Clone )]
+pub struct Span<I, J> {
+    pub id: I,
+    pub thread: ThreadId,
+    pub thread_name: Option<String>,
+    /// Since the start of the trace.
+    pub start: Duration,
+    /// Since the start of the trace.
+    pub end: Duration,
+    pub read: Vec<J>,
+    pub written: Vec<J>,
+    pub failed: bool
+}
+
+impl<I, J> Span<I, J> {
+    pub fn duration( &self ) -> Duration {
+        self.end.saturating_sub( self.start )
+    }
+}
+
+/// Snapshot of an operation's variables taken when it starts, turned into a [`Span`] when it ends.
+///
+/// The start and the thread are those at creation, so the probe is created on the thread that executes the operation.
+///
+pub(crate) struct Probe<J> {
+    start: Instant,
+    thread: ThreadId,
+    thread_name: Option<String>,
+    counters: Vec<( J, Version, u64, u64 )>
+}
+
+impl<J> Probe<J>
+where
+    J: Clone + Ord + Hash
+{
+    pub(crate) fn new( variables: &Variables<J> ) -> Self {
+        let mut counters: Vec<_> = variables.iter().map( |( key, variable )| {
+            let version = variable.version_handle().clone();
+            let ( reads, writes ) = ( version.reads(), version.get() );
+            ( key.clone(), version, reads, writes )
+        }).collect();
+        counters.sort_by( |( key1, .. ), ( key2, .. )| key1.cmp( key2 ) );
+        let current = thread::current();
+        Self {
+            start: Instant::now(),
+            thread: current.id(),
+            thread_name: current.name().map( String::from ),
+            counters
+        }
+    }
+
+    pub(crate) fn finish<I>( self, trace: &Trace<I, J>, id: I, failed: bool ) {
+        let end = Instant::now();
+        let mut span = Span {
+            id,
+            thread: self.thread,
+            thread_name: self.thread_name,
+            start: self.start.saturating_duration_since( trace.epoch ),
+            end: end.saturating_duration_since( trace.epoch ),
+            read: Vec::new(),
+            written: Vec::new(),
+            failed
+        };
+        for ( key, version, reads, writes ) in self.counters {
+            if version.reads() != reads {
+                span.read.push( key.clone() );
+            }
+            if version.get() != writes {
+                span.written.push( key );
+            }
+        }
+        trace.spans.lock().expect( "Failed to acquire trace lock" ).push( span );
+    }
+}
+
+/// Records the operation executions of one or more runs.
+///
+/// Pass it to [`Executor::trace`](super::executor::Executor::trace) or
+/// [`Runner::run_traced`](crate::async_function_graph::runner::Runner::run_traced), then inspect the spans or export
+/// them with [`Trace::write_chrome_trace`] for viewing in `chrome://tracing` or Perfetto.
+///
+#[derive( Debug )]
+pub struct Trace<I, J> {
+    epoch: Instant,
+    spans: Mutex<Vec<Span<I, J>>>
+}
+
+impl<I, J> Default for Trace<I, J> {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            spans: Mutex::new( Vec::new() )
+        }
+    }
+}
+
+impl<I, J> Trace<I, J> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded spans, in the order the operations finished.
+    pub fn spans( &self ) -> Vec<Span<I, J>>
+    where
+        I: Clone,
+        J: Clone
+    {
+        self.spans.lock().expect( "Failed to acquire trace lock" ).clone()
+    }
+
+    /// The total time spent in each operation, over every execution.
+    pub fn durations( &self ) -> BTreeMap<I, Duration>
+    where
+        I: Clone + Ord
+    {
+        let mut durations = BTreeMap::new();
+        for span in self.spans.lock().expect( "Failed to acquire trace lock" ).iter() {
+            *durations.entry( span.id.clone() ).or_default() += span.duration();
+        }
+        durations
+    }
+
+    pub fn clear( &self ) {
+        self.spans.lock().expect( "Failed to acquire trace lock" ).clear();
+    }
+
+    /// Writes the spans as Chrome trace-event JSON, one complete event per span.
+    pub fn write_chrome_trace<W>( &self, mut writer: W ) -> io::Result<()>
+    where
+        W: Write,
+        I: Display,
+        J: Display
+    {
+        writer.write_all( self.to_chrome_trace().as_bytes() )
+    }
+
+    pub fn to_chrome_trace( &self ) -> String
+    where
+        I: Display,
+        J: Display
+    {
+        let spans = self.spans.lock().expect( "Failed to acquire trace lock" );
+        let mut threads: Vec<( ThreadId, Option<&str> )> = Vec::new();
+        let mut events = Vec::new();
+        for span in spans.iter() {
+            let tid = match threads.iter().position( |( thread, _ )| *thread == span.thread ) {
+                Some( tid ) => tid,
+                None => {
+                    threads.push( ( span.thread, span.thread_name.as_deref() ) );
+                    threads.len() - 1
+                }
+            };
+            let keys = |keys: &[ J ]| keys.iter().map( |key| quote::json( &key.to_string() ) ).collect::<Vec<_>>().join( "," );
+            events.push( format!(
+                "{{\"name\":{},\"cat\":\"operation\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":{},\"args\":{{\"read\":[{}],\"written\":[{}],\"failed\":{}}}}}",
+                quote::json( &span.id.to_string() ),
+                span.start.as_micros(),
+                span.duration().as_micros(),
+                tid,
+                keys( &span.read ),
+                keys( &span.written ),
+                span.failed
+            ));
+        }
+        for ( tid, ( thread, name ) ) in threads.iter().enumerate() {
+            let name = name.map_or_else( || format!( "{:?}", thread ), String::from );
+            events.push( format!(
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":{}}}}}",
+                tid,
+                quote::json( &name )
+            ));
+        }
+        format!( "{{\"traceEvents\":[{}]}}", events.join( "," ) )
+    }
+}
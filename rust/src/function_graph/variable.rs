@@ -1,106 +1,405 @@
 use std::{
-    any::Any, collections::HashMap, hash::Hash, ops::{ Deref, DerefMut }, sync::{ Arc, RwLock, RwLockReadGuard, RwLockWriteGuard }
+    any::{ Any, type_name }, collections::HashMap, fmt::Debug, hash::Hash, marker::PhantomData, ops::{ Deref, DerefMut }, sync::{ Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, atomic::{ AtomicU64, Ordering } }
 };
 
+use thiserror::Error;
+
 #[cfg(feature = "unstable")]
 use std::sync::{ MappedRwLockReadGuard, MappedRwLockWriteGuard };
 
 type Inner = dyn Any + Send + Sync;
 
+/// Copies the value of a variable into a new lock.
+type Cloner = fn( &Inner ) -> Box<RwLock<Inner>>;
+
+fn clone_value<T>( value: &Inner ) -> Box<RwLock<Inner>>
+where
+    T: Any + Clone + Send + Sync
+{
+    Box::new( RwLock::new( value.downcast_ref::<T>().expect( "Cloner matches the variable's type" ).clone() ) )
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("Variable not found: {0}")]
+    NotFound( String ),
+    #[error("Variable type mismatch: {key} is not a {expected}")]
+    TypeMismatch { key: String, expected: &'static str },
+    #[error("Variable locked twice in one lock set: {0}")]
+    Deadlock( String ),
+    #[error("Variable has no clone function: {0}")]
+    NotCloneable( String )
+}
+
+#[derive( Debug, Default )]
+struct Counters {
+    writes: AtomicU64,
+    reads: AtomicU64
+}
+
+/// A change counter bumped every time a [`Variable`] is locked for writing.
+///
+/// Shared variables share their version with every clone, so any holder observes a write. Read locks are counted
+/// separately, for tracing.
+///
+#[derive( Debug, Clone, Default )]
+pub struct Version( Arc<Counters> );
+
+impl Version {
+    #[inline(always)]
+    pub fn get( &self ) -> u64 {
+        self.0.writes.load( Ordering::Acquire )
+    }
+
+    /// The number of times the variable has been locked for reading.
+    #[inline(always)]
+    pub fn reads( &self ) -> u64 {
+        self.0.reads.load( Ordering::Acquire )
+    }
+
+    #[inline(always)]
+    fn bump( &self ) {
+        self.0.writes.fetch_add( 1, Ordering::AcqRel );
+    }
+
+    #[inline(always)]
+    fn bump_reads( &self ) {
+        self.0.reads.fetch_add( 1, Ordering::AcqRel );
+    }
+}
+
+/// A value bound to operations.
+///
+/// An owned variable belongs to a single binding, a shared one is shared by all of its clones. Owned variables and
+/// variables created with [`Variable::shared_cloneable`] carry a clone function, so their value can be copied into an
+/// independent variable with [`Variable::deep_clone`]. Cloning an owned variable copies its value, so owned values
+/// must be `Clone`; values that are not are bound through a shared variable.
+///
 pub enum Variable {
-    Owned( Box<RwLock<Inner>> ),
-    Shared( Arc<RwLock<Inner>> )
+    Owned( Box<RwLock<Inner>>, Version, Cloner ),
+    Shared( Arc<RwLock<Inner>>, Version, Option<Cloner> )
 }
 
 impl Variable {
     pub fn owned<T>( value: T ) -> Self
     where
-        T: Any + Send + Sync
+        T: Any + Clone + Send + Sync
     {
-        Self::Owned( Box::new( RwLock::new( value ) ) )
+        Self::Owned( Box::new( RwLock::new( value ) ), Version::default(), clone_value::<T> )
     }
 
     pub fn shared<T>( value: T ) -> Self
     where
         T: Any + Send + Sync
     {
-        Self::Shared( Arc::new( RwLock::new( value ) ) )
+        Self::Shared( Arc::new( RwLock::new( value ) ), Version::default(), None )
+    }
+
+    /// A shared variable whose value can be deep-cloned.
+    pub fn shared_cloneable<T>( value: T ) -> Self
+    where
+        T: Any + Clone + Send + Sync
+    {
+        Self::Shared( Arc::new( RwLock::new( value ) ), Version::default(), Some( clone_value::<T> ) )
+    }
+
+    pub fn is_cloneable( &self ) -> bool {
+        self.cloner().is_some()
+    }
+
+    /// A new variable of the same kind holding a copy of the value, independent of this one.
+    pub fn deep_clone( &self ) -> Result<Self, Error> {
+        let cloner = self.cloner().ok_or_else( || Error::NotCloneable( String::from( "<unnamed>" ) ) )?;
+        let value = cloner( &*self.lock().read().expect( "Failed to acquire read lock" ) );
+        Ok( match self {
+            Self::Owned( .. ) => Self::Owned( value, Version::default(), cloner ),
+            Self::Shared( .. ) => Self::Shared( Arc::from( value ), Version::default(), Some( cloner ) )
+        })
     }
 
     #[inline(always)]
-    pub fn read( &self ) -> RwLockReadGuard<Inner> {
-        match &self {
-            Self::Owned( value ) => value.read().expect( "Failed to acquire read lock" ),
-            Self::Shared( value ) => value.read().expect( "Failed to acquire read lock" )
+    fn cloner( &self ) -> Option<Cloner> {
+        match self {
+            Self::Owned( _, _, cloner ) => Some( *cloner ),
+            Self::Shared( _, _, cloner ) => *cloner
         }
     }
 
     #[inline(always)]
-    pub fn write( &self ) -> RwLockWriteGuard<Inner> {
-        match &self {
-            Self::Owned( value ) => value.write().expect( "Failed to acquire write lock" ),
-            Self::Shared( value ) => value.write().expect( "Failed to acquire write lock" )
+    fn lock( &self ) -> &RwLock<Inner> {
+        match self {
+            Self::Owned( value, _, _ ) => value,
+            Self::Shared( value, _, _ ) => value
+        }
+    }
+
+    #[inline(always)]
+    pub fn read( &self ) -> RwLockReadGuard<'_, Inner> {
+        let guard = self.lock().read().expect( "Failed to acquire read lock" );
+        self.version_handle().bump_reads();
+        guard
+    }
+
+    /// Acquires a write lock and bumps the variable's version.
+    #[inline(always)]
+    pub fn write( &self ) -> RwLockWriteGuard<'_, Inner> {
+        let guard = self.lock().write().expect( "Failed to acquire write lock" );
+        self.version_handle().bump();
+        guard
+    }
+
+    /// The number of times the variable has been locked for writing.
+    #[inline(always)]
+    pub fn version( &self ) -> u64 {
+        self.version_handle().get()
+    }
+
+    #[inline(always)]
+    pub(crate) fn version_handle( &self ) -> &Version {
+        match self {
+            Self::Owned( _, version, _ ) => version,
+            Self::Shared( _, version, _ ) => version
         }
     }
 
     #[cfg(feature = "unstable")]
     #[inline(always)]
-    pub fn read_as<T>(&self) -> Option<MappedRwLockReadGuard<T>>
+    pub fn read_as<T>(&self) -> Option<MappedRwLockReadGuard<'_, T>>
     where
         T: 'static + Send + Sync,
     {
         let guard = self.read();
-        RwLockReadGuard::try_map( guard, |inner| {
+        RwLockReadGuard::filter_map( guard, |inner| {
             inner.downcast_ref::<T>()
         }).ok()
     }
 
     #[cfg(feature = "unstable")]
     #[inline(always)]
-    pub fn write_as<T>(&self) -> Option<MappedRwLockWriteGuard<T>>
+    pub fn write_as<T>(&self) -> Option<MappedRwLockWriteGuard<'_, T>>
     where
         T: 'static + Send + Sync,
     {
-        let guard = self.write();
-        RwLockWriteGuard::try_map( guard, |inner| {
+        let guard = self.lock().write().expect( "Failed to acquire write lock" );
+        let guard = RwLockWriteGuard::filter_map( guard, |inner| {
             inner.downcast_mut::<T>()
-        }).ok()
+        }).ok()?;
+        self.version_handle().bump();
+        Some( guard )
+    }
+
+    /// Acquires a read lock and checks that the value is a `T`.
+    pub fn read_typed<T>( &self ) -> Result<ReadGuard<'_, T>, Error>
+    where
+        T: Any + Send + Sync
+    {
+        ReadGuard::new( self.read() ).ok_or_else( || Error::TypeMismatch {
+            key: String::from( "<unnamed>" ),
+            expected: type_name::<T>()
+        })
+    }
+
+    /// Acquires a write lock and checks that the value is a `T`. The version is only bumped if it is.
+    pub fn write_typed<T>( &self ) -> Result<WriteGuard<'_, T>, Error>
+    where
+        T: Any + Send + Sync
+    {
+        let guard = self.lock().write().expect( "Failed to acquire write lock" );
+        let guard = WriteGuard::new( guard ).ok_or_else( || Error::TypeMismatch {
+            key: String::from( "<unnamed>" ),
+            expected: type_name::<T>()
+        })?;
+        self.version_handle().bump();
+        Ok( guard )
     }
 
-    fn type_id( &self ) -> std::any::TypeId {
+    pub fn is<T>( &self ) -> bool
+    where
+        T: Any
+    {
+        self.read().is::<T>()
+    }
+
+    pub(crate) fn type_id( &self ) -> std::any::TypeId {
         self.read().deref().type_id()
     }
+
+    /// The address of the lock, which orders lock acquisition in a [`LockSet`].
+    pub(crate) fn address( &self ) -> usize {
+        self.lock() as *const RwLock<Inner> as *const () as usize
+    }
+}
+
+/// A read guard over a [`Variable`] whose type has already been checked.
+pub struct ReadGuard<'a, T> {
+    guard: RwLockReadGuard<'a, Inner>,
+    marker: PhantomData<T>
+}
+
+impl<'a, T> ReadGuard<'a, T>
+where
+    T: Any
+{
+    fn new( guard: RwLockReadGuard<'a, Inner> ) -> Option<Self> {
+        guard.is::<T>().then_some( Self { guard, marker: PhantomData } )
+    }
+}
+
+impl<T> Deref for ReadGuard<'_, T>
+where
+    T: Any
+{
+    type Target = T;
+
+    fn deref( &self ) -> &Self::Target {
+        self.guard.downcast_ref::<T>().expect( "Variable type checked on acquire" )
+    }
+}
+
+/// A write guard over a [`Variable`] whose type has already been checked.
+pub struct WriteGuard<'a, T> {
+    guard: RwLockWriteGuard<'a, Inner>,
+    marker: PhantomData<T>
+}
+
+impl<'a, T> WriteGuard<'a, T>
+where
+    T: Any
+{
+    fn new( guard: RwLockWriteGuard<'a, Inner> ) -> Option<Self> {
+        guard.is::<T>().then_some( Self { guard, marker: PhantomData } )
+    }
+}
+
+impl<T> Deref for WriteGuard<'_, T>
+where
+    T: Any
+{
+    type Target = T;
+
+    fn deref( &self ) -> &Self::Target {
+        self.guard.downcast_ref::<T>().expect( "Variable type checked on acquire" )
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T>
+where
+    T: Any
+{
+    fn deref_mut( &mut self ) -> &mut Self::Target {
+        self.guard.downcast_mut::<T>().expect( "Variable type checked on acquire" )
+    }
+}
+
+/// A [`Variable`] handle that remembers the type of the value it holds.
+///
+/// Converts into a plain `Variable` for binding into an operation, so typed and untyped variables can be stored
+/// side by side in the same [`Variables`].
+///
+pub struct TypedVariable<T> {
+    variable: Variable,
+    marker: PhantomData<fn() -> T>
+}
+
+impl<T> TypedVariable<T>
+where
+    T: Any + Send + Sync
+{
+    pub fn shared( value: T ) -> Self {
+        Self { variable: Variable::shared( value ), marker: PhantomData }
+    }
+
+    #[inline(always)]
+    pub fn read( &self ) -> ReadGuard<'_, T> {
+        self.variable.read_typed::<T>().expect( "TypedVariable holds a value of its own type" )
+    }
+
+    #[inline(always)]
+    pub fn write( &self ) -> WriteGuard<'_, T> {
+        self.variable.write_typed::<T>().expect( "TypedVariable holds a value of its own type" )
+    }
+
+    pub fn variable( &self ) -> &Variable {
+        &self.variable
+    }
+}
+
+impl<T> TypedVariable<T>
+where
+    T: Any + Clone + Send + Sync
+{
+    pub fn owned( value: T ) -> Self {
+        Self { variable: Variable::owned( value ), marker: PhantomData }
+    }
+
+    pub fn shared_cloneable( value: T ) -> Self {
+        Self { variable: Variable::shared_cloneable( value ), marker: PhantomData }
+    }
+}
+
+impl<T> Clone for TypedVariable<T> {
+    fn clone( &self ) -> Self {
+        Self { variable: self.variable.clone(), marker: PhantomData }
+    }
+}
+
+impl<T> std::fmt::Debug for TypedVariable<T> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        write!( f, "TypedVariable<{}>({:?})", type_name::<T>(), self.variable )
+    }
+}
+
+impl<T> From<TypedVariable<T>> for Variable {
+    fn from( value: TypedVariable<T> ) -> Self {
+        value.variable
+    }
+}
+
+impl<T> TryFrom<Variable> for TypedVariable<T>
+where
+    T: Any + Send + Sync
+{
+    type Error = Error;
+
+    fn try_from( variable: Variable ) -> Result<Self, Self::Error> {
+        if variable.is::<T>() {
+            Ok( Self { variable, marker: PhantomData } )
+        } else {
+            Err( Error::TypeMismatch { key: String::from( "<unnamed>" ), expected: type_name::<T>() } )
+        }
+    }
 }
 
 impl std::fmt::Debug for Variable {
     fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
         let type_id = self.type_id();
         match self {
-            Self::Owned( value ) => {
-                write!( f, "Owned(TypeId: {:?}, Location: {:p})", type_id, &**value )
+            Self::Owned( value, version, _ ) => {
+                write!( f, "Owned(TypeId: {:?}, Location: {:p}, Version: {})", type_id, &**value, version.get() )
             },
-            Self::Shared( value ) => {
-                write!( f, "Shared(TypeId: {:?}, Location: {:p})", type_id, &**value )
+            Self::Shared( value, version, _ ) => {
+                write!( f, "Shared(TypeId: {:?}, Location: {:p}, Version: {})", type_id, &**value, version.get() )
             }
         }
     }
 }
 
+/// The variable gets its own [`Version`]; writes through other handles to the same lock are not counted.
 impl<T> From <Arc<RwLock<T>>> for Variable
 where
     T: Any + Send + Sync
 {
     fn from( value: Arc<RwLock<T>> ) -> Self {
-        Self::Shared( value )
+        Self::Shared( value, Version::default(), None )
     }
 }
 
 impl<T> From <Box<RwLock<T>>> for Variable
 where
-    T: Any + Send + Sync
+    T: Any + Clone + Send + Sync
 {
     fn from( value: Box<RwLock<T>> ) -> Self {
-        Self::Owned( value )
+        Self::Owned( value, Version::default(), clone_value::<T> )
     }
 }
 
@@ -113,8 +412,10 @@ impl PartialEq for Variable {
 impl Clone for Variable {
     fn clone( &self ) -> Self {
         match self {
-            Self::Owned( _ ) => Self::Owned( Box::new( RwLock::new( () ) ) ),
-            Self::Shared( value ) => Self::Shared( value.clone() )
+            Self::Owned( value, _, cloner ) => {
+                Self::Owned( cloner( &*value.read().expect( "Failed to acquire read lock" ) ), Version::default(), *cloner )
+            },
+            Self::Shared( value, version, cloner ) => Self::Shared( value.clone(), version.clone(), *cloner )
         }
     }
 }
@@ -136,18 +437,125 @@ where
     }
 
     #[inline(always)]
-    pub fn read( &self, key: &I ) -> RwLockReadGuard<Inner> {
+    pub fn try_get( &self, key: &I ) -> Result<&Variable, Error>
+    where
+        I: Debug
+    {
+        self.0.get( key ).ok_or_else( || Error::NotFound( format!( "{:?}", key ) ) )
+    }
+
+    #[inline(always)]
+    pub fn read( &self, key: &I ) -> RwLockReadGuard<'_, Inner> {
         self.get( key ).read()
     }
 
+    /// The current version of every variable, keyed like the variables themselves.
+    pub fn versions( &self ) -> HashMap<I, u64>
+    where
+        I: Clone
+    {
+        self.0.iter().map( |( key, variable )| ( key.clone(), variable.version() ) ).collect()
+    }
+
     #[inline(always)]
-    pub fn write( &self, key: &I ) -> RwLockWriteGuard<Inner> {
+    pub fn write( &self, key: &I ) -> RwLockWriteGuard<'_, Inner> {
         self.get( key ).write()
     }
 
+    /// Locks the variables bound to `reads` for reading and those bound to `writes` for writing, all at once.
+    ///
+    /// Locks are acquired in order of their address rather than in the order the keys are given, so operations
+    /// locking overlapping sets of shared variables cannot deadlock each other. Locking the same variable twice, by
+    /// listing a key in both `reads` and `writes` or through two keys bound to the same shared variable, would
+    /// deadlock the calling thread and is reported as [`Error::Deadlock`].
+    ///
+    pub fn lock_set( &self, reads: &[ I ], writes: &[ I ] ) -> Result<LockSet<'_, I>, Error>
+    where
+        I: Clone + Debug
+    {
+        let mut requests = Vec::with_capacity( reads.len() + writes.len() );
+        for ( keys, write ) in [ ( reads, false ), ( writes, true ) ] {
+            for key in keys {
+                requests.push( ( self.try_get( key )?, key, write ) );
+            }
+        }
+        requests.sort_by_key( |( variable, _, _ )| variable.address() );
+        if let Some( pair ) = requests.windows( 2 ).find( |pair| pair[ 0 ].0.address() == pair[ 1 ].0.address() ) {
+            return Err( Error::Deadlock( format!( "{:?}", pair[ 1 ].1 ) ) );
+        }
+
+        let mut set = LockSet { reads: HashMap::new(), writes: HashMap::new() };
+        for ( variable, key, write ) in requests {
+            match write {
+                true => { set.writes.insert( key.clone(), variable.write() ); },
+                false => { set.reads.insert( key.clone(), variable.read() ); }
+            }
+        }
+        Ok( set )
+    }
+
+    /// Deep-clones every variable, see [`Variable::deep_clone`].
+    ///
+    /// Keys bound to the same shared variable stay bound to one shared copy.
+    ///
+    pub fn deep_clone( &self ) -> Result<Self, Error>
+    where
+        I: Clone + Debug
+    {
+        self.deep_clone_with( &mut HashMap::new() )
+    }
+
+    /// Deep-clones every variable, reusing the copies in `clones`, keyed by the address of the original variable, so
+    /// variables shared across several [`Variables`] stay shared among the copies.
+    pub(crate) fn deep_clone_with( &self, clones: &mut HashMap<usize, Variable> ) -> Result<Self, Error>
+    where
+        I: Clone + Debug
+    {
+        self.0.iter().map( |( key, variable )| {
+            let clone = match clones.get( &variable.address() ) {
+                Some( clone ) => clone.clone(),
+                None => {
+                    let clone = variable.deep_clone().map_err( |_| Error::NotCloneable( format!( "{:?}", key ) ) )?;
+                    if let Variable::Shared( .. ) = clone {
+                        clones.insert( variable.address(), clone.clone() );
+                    }
+                    clone
+                }
+            };
+            Ok( ( key.clone(), clone ) )
+        }).collect()
+    }
+
+    /// Reads the variable bound to `key` as a `T`.
+    ///
+    /// Unlike [`Variables::read`], a missing key or a value of another type is reported as an [`Error`].
+    ///
+    pub fn get_typed<T>( &self, key: &I ) -> Result<ReadGuard<'_, T>, Error>
+    where
+        I: Debug,
+        T: Any + Send + Sync
+    {
+        self.try_get( key )?.read_typed::<T>().map_err( |_| Error::TypeMismatch {
+            key: format!( "{:?}", key ),
+            expected: type_name::<T>()
+        })
+    }
+
+    /// Writes the variable bound to `key` as a `T`.
+    pub fn get_typed_mut<T>( &self, key: &I ) -> Result<WriteGuard<'_, T>, Error>
+    where
+        I: Debug,
+        T: Any + Send + Sync
+    {
+        self.try_get( key )?.write_typed::<T>().map_err( |_| Error::TypeMismatch {
+            key: format!( "{:?}", key ),
+            expected: type_name::<T>()
+        })
+    }
+
     #[cfg(feature = "unstable")]
     #[inline(always)]
-    pub fn read_as<T>( &self, key: &I ) -> Option<MappedRwLockReadGuard<T>>
+    pub fn read_as<T>( &self, key: &I ) -> Option<MappedRwLockReadGuard<'_, T>>
     where
         T: 'static + Send + Sync
     {
@@ -156,7 +564,7 @@ where
 
     #[cfg(feature = "unstable")]
     #[inline(always)]
-    pub fn write_as<T>( &self, key: &I ) -> Option<MappedRwLockWriteGuard<T>>
+    pub fn write_as<T>( &self, key: &I ) -> Option<MappedRwLockWriteGuard<'_, T>>
     where
         T: 'static + Send + Sync
     {
@@ -164,6 +572,59 @@ where
     }
 }
 
+/// Locks held on a set of variables, see [`Variables::lock_set`].
+pub struct LockSet<'a, I> {
+    reads: HashMap<I, RwLockReadGuard<'a, Inner>>,
+    writes: HashMap<I, RwLockWriteGuard<'a, Inner>>
+}
+
+impl<I> LockSet<'_, I>
+where
+    I: Eq + Hash + Debug
+{
+    /// The value of a variable locked for reading or writing.
+    pub fn get<T>( &self, key: &I ) -> Result<&T, Error>
+    where
+        T: Any
+    {
+        let value: &Inner = match ( self.reads.get( key ), self.writes.get( key ) ) {
+            ( Some( guard ), _ ) => guard.deref(),
+            ( None, Some( guard ) ) => guard.deref(),
+            ( None, None ) => return Err( Error::NotFound( format!( "{:?}", key ) ) )
+        };
+        value.downcast_ref::<T>().ok_or_else( || Error::TypeMismatch {
+            key: format!( "{:?}", key ),
+            expected: type_name::<T>()
+        })
+    }
+
+    /// The value of a variable locked for writing.
+    pub fn get_mut<T>( &mut self, key: &I ) -> Result<&mut T, Error>
+    where
+        T: Any
+    {
+        self.writes.get_mut( key )
+            .ok_or_else( || Error::NotFound( format!( "{:?}", key ) ) )?
+            .downcast_mut::<T>()
+            .ok_or_else( || Error::TypeMismatch {
+                key: format!( "{:?}", key ),
+                expected: type_name::<T>()
+            })
+    }
+}
+
+impl<I> FromIterator<( I, Variable )> for Variables<I>
+where
+    I: Ord + Hash
+{
+    fn from_iter<T>( variables: T ) -> Self
+    where
+        T: IntoIterator<Item = ( I, Variable )>
+    {
+        Self( variables.into_iter().collect() )
+    }
+}
+
 impl<I> Deref for Variables<I> {
     type Target = HashMap<I, Variable>;
 
@@ -197,6 +658,119 @@ mod tests {
         assert_eq!( *guard.downcast_ref::<i32>().expect( "Failed to downcast" ), 43 );
     }
 
+    #[test]
+    fn test_variable_version() {
+        let variable = Variable::shared( 42 );
+        let shared = variable.clone();
+        assert_eq!( variable.version(), 0 );
+
+        assert_eq!( *variable.read().downcast_ref::<i32>().expect( "Failed to downcast" ), 42 );
+        assert_eq!( variable.version(), 0 );
+
+        *shared.write().downcast_mut::<i32>().expect( "Failed to downcast" ) = 43;
+        assert_eq!( variable.version(), 1 );
+    }
+
+    #[test]
+    fn test_variable_version_on_type_mismatch() {
+        let variable = Variable::shared( 42 );
+        assert!( variable.write_typed::<String>().is_err() );
+        assert_eq!( variable.version(), 0 );
+
+        *variable.write_typed::<i32>().expect( "Failed to downcast" ) = 43;
+        assert_eq!( variable.version(), 1 );
+    }
+
+    #[test]
+    fn test_typed_variable() {
+        let variable = TypedVariable::shared( 42 );
+        *variable.write() += 1;
+        assert_eq!( *variable.read(), 43 );
+
+        let variables = Variables::new( [ ( 'a', variable.clone().into() ) ] );
+        assert_eq!( *variables.get_typed::<i32>( &'a' ).unwrap(), 43 );
+    }
+
+    #[test]
+    fn test_variables_get_typed_errors() {
+        let variables = Variables::new( [ ( 'a', Variable::shared( 42 ) ) ] );
+        assert_eq!(
+            variables.get_typed::<String>( &'a' ).err(),
+            Some( Error::TypeMismatch { key: String::from( "'a'" ), expected: "alloc::string::String" } )
+        );
+        assert_eq!( variables.get_typed::<i32>( &'b' ).err(), Some( Error::NotFound( String::from( "'b'" ) ) ) );
+    }
+
+    #[test]
+    fn test_typed_variable_try_from() {
+        assert!( TypedVariable::<i32>::try_from( Variable::owned( 42 ) ).is_ok() );
+        assert!( TypedVariable::<u8>::try_from( Variable::owned( 42 ) ).is_err() );
+    }
+
+    #[test]
+    fn test_variable_deep_clone() {
+        let owned = Variable::owned( vec![ 1, 2 ] );
+        let clone = owned.clone();
+        clone.write().downcast_mut::<Vec<i32>>().unwrap().push( 3 );
+        assert_eq!( owned.read().downcast_ref::<Vec<i32>>().unwrap(), &vec![ 1, 2 ] );
+        assert_eq!( clone.read().downcast_ref::<Vec<i32>>().unwrap(), &vec![ 1, 2, 3 ] );
+
+        let shared = Variable::shared_cloneable( 1 );
+        let copy = shared.deep_clone().unwrap();
+        *copy.write().downcast_mut::<i32>().unwrap() = 2;
+        assert_eq!( *shared.read().downcast_ref::<i32>().unwrap(), 1 );
+        assert_eq!( Variable::shared( 1 ).deep_clone().err(), Some( Error::NotCloneable( String::from( "<unnamed>" ) ) ) );
+    }
+
+    #[test]
+    fn test_variables_deep_clone() {
+        let shared = Variable::shared_cloneable( 1 );
+        let variables = Variables::new( [ ( 'a', shared.clone() ), ( 'b', shared ), ( 'c', Variable::owned( 2 ) ) ] );
+        let clone = variables.deep_clone().unwrap();
+
+        *clone.write( &'a' ).downcast_mut::<i32>().unwrap() = 3;
+        assert_eq!( *clone.read( &'b' ).downcast_ref::<i32>().unwrap(), 3 );
+        assert_eq!( *variables.read( &'b' ).downcast_ref::<i32>().unwrap(), 1 );
+        assert_eq!( *clone.read( &'c' ).downcast_ref::<i32>().unwrap(), 2 );
+
+        let variables = Variables::new( [ ( 'a', Variable::shared( 1 ) ) ] );
+        assert_eq!( variables.deep_clone().err(), Some( Error::NotCloneable( String::from( "'a'" ) ) ) );
+    }
+
+    #[test]
+    fn test_variables_lock_set() {
+        let a = Variable::shared( 2 );
+        let variables = Variables::new( [ ( 'a', a.clone() ), ( 'b', Variable::shared( 0 ) ), ( 'c', a ) ] );
+
+        let mut set = variables.lock_set( &[ 'a' ], &[ 'b' ] ).unwrap();
+        *set.get_mut::<i32>( &'b' ).unwrap() = *set.get::<i32>( &'a' ).unwrap() * 2;
+        assert_eq!( *set.get::<i32>( &'b' ).unwrap(), 4 );
+        assert!( set.get_mut::<i32>( &'a' ).is_err() );
+        assert!( set.get::<String>( &'a' ).is_err() );
+        drop( set );
+
+        assert_eq!( variables.lock_set( &[ 'a' ], &[ 'a' ] ).err(), Some( Error::Deadlock( String::from( "'a'" ) ) ) );
+        assert!( matches!( variables.lock_set( &[ 'a', 'c' ], &[] ), Err( Error::Deadlock( _ ) ) ) );
+        assert_eq!( variables.lock_set( &[ 'd' ], &[] ).err(), Some( Error::NotFound( String::from( "'d'" ) ) ) );
+    }
+
+    #[test]
+    fn test_variables_lock_set_order() {
+        let a = Variable::shared( 0 );
+        let b = Variable::shared( 0 );
+        let forward = Arc::new( Variables::new( [ ( 'a', a.clone() ), ( 'b', b.clone() ) ] ) );
+        let backward = Arc::new( Variables::new( [ ( 'a', b ), ( 'b', a ) ] ) );
+
+        let handles: Vec<_> = [ forward, backward ].into_iter().map( |variables| std::thread::spawn( move || {
+            for _ in 0..1000 {
+                let mut set = variables.lock_set( &[], &[ 'a', 'b' ] ).unwrap();
+                *set.get_mut::<i32>( &'a' ).unwrap() += 1;
+                *set.get_mut::<i32>( &'b' ).unwrap() += 1;
+            }
+        })).collect();
+        handles.into_iter().for_each( |handle| handle.join().unwrap() );
+    }
+
     #[cfg(feature = "unstable")]
     #[test]
     fn test_variable_read_as() {
@@ -0,0 +1,262 @@
+// Copyright 2024 Bewusstsein Labs
+
+pub mod parser;
+
+use std::{
+    collections::{ BTreeMap, BTreeSet },
+    fmt::{ Debug, Display },
+    io::{ self, Write }
+};
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical,
+        quote
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+/// Attribute `( name, value )` pairs of a node or edge, written as `name="value"`.
+pub type Attributes = Vec<( String, String )>;
+
+type NodeCallback<'a, I, N> = Box<dyn Fn( &I, &N ) -> Attributes + 'a>;
+type EdgeCallback<'a, I, E> = Box<dyn Fn( &I, &I, &E ) -> Attributes + 'a>;
+type ClusterCallback<'a, I, N> = Box<dyn Fn( &I, &N ) -> Option<String> + 'a>;
+
+/// Writes a [`Graph`] in the GraphViz DOT language.
+///
+/// Directed graphs are written as a `digraph` with `->` edges, undirected graphs as a `graph` with `--` edges, each
+/// edge once. Ids, names and attribute values are always quoted and escaped. Without callbacks nodes and edges are
+/// labelled with the `Debug` output of their data; with a callback, the attributes it returns replace the label.
+///
+/// ```ignore
+/// Dot::new()
+///     .node_attributes( |_, node| vec![ ( "shape".into(), "box".into() ), ( "label".into(), node.to_string() ) ] )
+///     .cluster( |id, _| ( *id < 4 ).then( || "inputs".to_string() ) )
+///     .write( &graph, File::create( "graph.dot" )? )?;
+/// ```
+///
+pub struct Dot<'a, I, N, E> {
+    name: String,
+    graph_attributes: Attributes,
+    node_attributes: Option<NodeCallback<'a, I, N>>,
+    edge_attributes: Option<EdgeCallback<'a, I, E>>,
+    cluster: Option<ClusterCallback<'a, I, N>>
+}
+
+impl<I, N, E> Default for Dot<'_, I, N, E> {
+    fn default() -> Self {
+        Self {
+            name: "G".to_string(),
+            graph_attributes: Attributes::new(),
+            node_attributes: None,
+            edge_attributes: None,
+            cluster: None
+        }
+    }
+}
+
+impl<'a, I, N, E> Dot<'a, I, N, E>
+where
+    I: Clone + Ord + Display,
+    N: Debug,
+    E: Debug
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name<S>( mut self, name: S ) -> Self
+    where
+        S: Into<String>
+    {
+        self.name = name.into();
+        self
+    }
+
+    /// Adds a graph-level attribute such as `rankdir`.
+    pub fn graph_attribute<K, V>( mut self, name: K, value: V ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        self.graph_attributes.push( ( name.into(), value.into() ) );
+        self
+    }
+
+    pub fn node_attributes<F>( mut self, attributes: F ) -> Self
+    where
+        F: Fn( &I, &N ) -> Attributes + 'a
+    {
+        self.node_attributes = Some( Box::new( attributes ) );
+        self
+    }
+
+    pub fn edge_attributes<F>( mut self, attributes: F ) -> Self
+    where
+        F: Fn( &I, &I, &E ) -> Attributes + 'a
+    {
+        self.edge_attributes = Some( Box::new( attributes ) );
+        self
+    }
+
+    /// Groups nodes into `cluster_` subgraphs by the name returned for them. Nodes without one stay at the top level.
+    pub fn cluster<F>( mut self, cluster: F ) -> Self
+    where
+        F: Fn( &I, &N ) -> Option<String> + 'a
+    {
+        self.cluster = Some( Box::new( cluster ) );
+        self
+    }
+
+    pub fn write<D, C, R, W>( &self, graph: &Graph<D, C, R>, mut writer: W ) -> io::Result<()>
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+        W: Write
+    {
+        let ( keyword, connector ) = if D::DIRECTED { ( "digraph", "->" ) } else { ( "graph", "--" ) };
+        writeln!( writer, "{} {} {{", keyword, quote::dot( &self.name ) )?;
+        for ( name, value ) in &self.graph_attributes {
+            writeln!( writer, "    {}={};", name, quote::dot( value ) )?;
+        }
+
+        let mut clusters: BTreeMap<String, Vec<( I, &N )>> = BTreeMap::new();
+        for ( id, node ) in graph.iter_node_ids() {
+            match self.cluster.as_ref().and_then( |cluster| cluster( &id, node ) ) {
+                Some( name ) => clusters.entry( name ).or_default().push( ( id, node ) ),
+                None => self.write_node( &mut writer, "    ", &id, node )?
+            }
+        }
+        for ( name, nodes ) in &clusters {
+            writeln!( writer, "    subgraph {} {{", quote::dot( &format!( "cluster_{}", name ) ) )?;
+            writeln!( writer, "        label={};", quote::dot( name ) )?;
+            for ( id, node ) in nodes {
+                self.write_node( &mut writer, "        ", id, node )?;
+            }
+            writeln!( writer, "    }}" )?;
+        }
+
+        let mut written = BTreeSet::new();
+        for ( id1, id2, edge ) in graph.iter_edge_ids() {
+            if !D::DIRECTED {
+                if written.contains( &( id2.clone(), id1.clone() ) ) {
+                    continue;
+                }
+                written.insert( ( id1.clone(), id2.clone() ) );
+            }
+            let attributes = match &self.edge_attributes {
+                Some( attributes ) => attributes( &id1, &id2, edge ),
+                None => vec![ ( "label".to_string(), format!( "{:?}", edge ) ) ]
+            };
+            writeln!( writer, "    {} {} {}{};",
+                quote::dot( &id1.to_string() ),
+                connector,
+                quote::dot( &id2.to_string() ),
+                format_attributes( &attributes )
+            )?;
+        }
+        writeln!( writer, "}}" )
+    }
+
+    pub fn to_dot<D, C, R>( &self, graph: &Graph<D, C, R> ) -> String
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>
+    {
+        let mut dot = Vec::new();
+        self.write( graph, &mut dot ).expect( "Failed to write to memory" );
+        String::from_utf8( dot ).expect( "DOT output is not valid UTF-8" )
+    }
+
+    fn write_node<W>( &self, writer: &mut W, indent: &str, id: &I, node: &N ) -> io::Result<()>
+    where
+        W: Write
+    {
+        let attributes = match &self.node_attributes {
+            Some( attributes ) => attributes( id, node ),
+            None => vec![ ( "label".to_string(), format!( "{:?}", node ) ) ]
+        };
+        writeln!( writer, "{}{}{};", indent, quote::dot( &id.to_string() ), format_attributes( &attributes ) )
+    }
+}
+
+fn format_attributes( attributes: &Attributes ) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+    let attributes: Vec<_> = attributes.iter()
+        .map( |( name, value )| format!( "{}={}", name, quote::dot( value ) ) )
+        .collect();
+    format!( " [{}]", attributes.join( ", " ) )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ BTreeRepr, NodeRepr }
+    };
+
+    use super::Dot;
+
+    fn repr( edges: &[ ( u32, u32 ) ] ) -> BTreeRepr<u32, i32, i32> {
+        let mut nodes = BTreeMap::new();
+        for ( id1, id2 ) in edges {
+            for id in [ id1, id2 ] {
+                nodes.entry( *id ).or_insert_with( || NodeRepr { node: *id as i32 * 10, adjs: BTreeMap::new() } );
+            }
+            nodes.get_mut( id1 ).unwrap().adjs.insert( *id2, 1 );
+        }
+        BTreeRepr( nodes )
+    }
+
+    #[test]
+    fn test_dot_directed() {
+        let graph: Graph<Directed, Cyclic, _> = Graph( repr( &[ ( 1, 2 ), ( 2, 1 ) ] ), Default::default() );
+        let dot = Dot::new()
+            .name( "say \"hi\"" )
+            .node_attributes( |id, node| vec![ ( "label".into(), format!( "{}\n{}", id, node ) ) ] )
+            .edge_attributes( |_, _, _| vec![ ( "color".into(), "red".into() ) ] )
+            .cluster( |id, _| ( *id == 2 ).then( || "b".to_string() ) )
+            .to_dot( &graph );
+        assert_eq!( dot, concat!(
+            "digraph \"say \\\"hi\\\"\" {\n",
+            "    \"1\" [label=\"1\\n10\"];\n",
+            "    subgraph \"cluster_b\" {\n",
+            "        label=\"b\";\n",
+            "        \"2\" [label=\"2\\n20\"];\n",
+            "    }\n",
+            "    \"1\" -> \"2\" [color=\"red\"];\n",
+            "    \"2\" -> \"1\" [color=\"red\"];\n",
+            "}\n"
+        ));
+    }
+
+    #[test]
+    fn test_dot_undirected() {
+        let graph: Graph<Undirected, Cyclic, _> = Graph( repr( &[ ( 1, 2 ), ( 2, 1 ), ( 2, 3 ), ( 3, 2 ) ] ), Default::default() );
+        let dot = Dot::new().to_dot( &graph );
+        assert_eq!( dot, concat!(
+            "graph \"G\" {\n",
+            "    \"1\" [label=\"10\"];\n",
+            "    \"2\" [label=\"20\"];\n",
+            "    \"3\" [label=\"30\"];\n",
+            "    \"1\" -- \"2\" [label=\"1\"];\n",
+            "    \"2\" -- \"3\" [label=\"1\"];\n",
+            "}\n"
+        ));
+    }
+}
@@ -0,0 +1,542 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical,
+        dot::Attributes
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Expected a {expected} but found a {found}")]
+    Direction { expected: &'static str, found: &'static str }
+}
+
+#[derive( Debug, Clone, PartialEq )]
+pub struct DotNode {
+    pub id: String,
+    pub attributes: Attributes,
+    /// The innermost named subgraph the node was declared in.
+    pub subgraph: Option<String>
+}
+
+#[derive( Debug, Clone, PartialEq )]
+pub struct DotEdge {
+    pub from: String,
+    pub to: String,
+    pub attributes: Attributes
+}
+
+/// A parsed DOT file, before it is turned into a [`Graph`].
+///
+/// Supports the statements of the DOT language except ports, which are accepted and ignored: node, edge and attribute
+/// statements, `ID = ID` assignments and nested subgraphs, which may also be edge endpoints. Nodes are kept in order
+/// of first appearance and hold every attribute set on them, including `node [ ... ]` defaults in scope when they
+/// were declared.
+///
+#[derive( Debug, Clone, Default, PartialEq )]
+pub struct DotGraph {
+    pub name: Option<String>,
+    pub directed: bool,
+    pub strict: bool,
+    pub attributes: Attributes,
+    pub nodes: Vec<DotNode>,
+    pub edges: Vec<DotEdge>
+}
+
+impl DotGraph {
+    pub fn parse( text: &str ) -> Result<Self, Error> {
+        Parser::new( text )?.graph()
+    }
+
+    pub fn node( &self, id: &str ) -> Option<&DotNode> {
+        self.nodes.iter().find( |node| node.id == id )
+    }
+
+    /// Builds a graph, mapping each node to its id from its index and DOT name, and node and edge attributes to data.
+    ///
+    /// Edges of an undirected DOT graph are added in both directions. Edges to nodes `id` has not been asked about
+    /// cannot occur, since every edge endpoint is a node of the DOT graph.
+    ///
+    pub fn build<D, C, R, I, N, E, FI, FN, FE>( &self, mut id: FI, mut node: FN, mut edge: FE ) -> Result<Graph<D, C, R>, Error>
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        I: Clone,
+        Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E>,
+        FI: FnMut( usize, &DotNode ) -> I,
+        FN: FnMut( &DotNode ) -> N,
+        FE: FnMut( &DotEdge ) -> E
+    {
+        if self.directed != D::DIRECTED {
+            let kind = |directed| if directed { "digraph" } else { "graph" };
+            return Err( Error::Direction { expected: kind( D::DIRECTED ), found: kind( self.directed ) } );
+        }
+        let mut graph = Graph::default();
+        let mut ids = HashMap::new();
+        for ( index, dot_node ) in self.nodes.iter().enumerate() {
+            let node_id = id( index, dot_node );
+            graph.add_node( node_id.clone(), node( dot_node ) );
+            ids.insert( dot_node.id.as_str(), node_id );
+        }
+        for dot_edge in &self.edges {
+            let ( from, to ) = ( ids[ dot_edge.from.as_str() ].clone(), ids[ dot_edge.to.as_str() ].clone() );
+            if !self.directed {
+                graph.add_edge( to.clone(), from.clone(), edge( dot_edge ) );
+            }
+            graph.add_edge( from, to, edge( dot_edge ) );
+        }
+        Ok( graph )
+    }
+
+    /// Adds a node with the defaults of the scope it first appears in.
+    fn declare( &mut self, id: &str, scope: &Scope ) {
+        if self.node( id ).is_none() {
+            self.nodes.push( DotNode {
+                id: id.to_string(),
+                attributes: scope.node.clone(),
+                subgraph: scope.subgraph.clone()
+            });
+        }
+    }
+
+    fn set_attributes( &mut self, id: &str, attributes: &Attributes ) {
+        if let Some( node ) = self.nodes.iter_mut().find( |node| node.id == id ) {
+            attributes.iter().for_each( |( name, value )| set( &mut node.attributes, name, value ) );
+        }
+    }
+}
+
+fn set( attributes: &mut Attributes, name: &str, value: &str ) {
+    match attributes.iter_mut().find( |( key, _ )| key == name ) {
+        Some( ( _, old ) ) => *old = value.to_string(),
+        None => attributes.push( ( name.to_string(), value.to_string() ) )
+    }
+}
+
+fn merge( defaults: &Attributes, attributes: &Attributes ) -> Attributes {
+    let mut merged = defaults.clone();
+    attributes.iter().for_each( |( name, value )| set( &mut merged, name, value ) );
+    merged
+}
+
+#[derive( Debug, Clone, PartialEq )]
+enum Token {
+    Id( String ),
+    /// A quoted or HTML string, never a keyword.
+    Literal( String ),
+    Punct( char ),
+    EdgeOp( bool )
+}
+
+fn tokenize( text: &str ) -> Result<Vec<( Token, usize )>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+    let error = |line, message: &str| Error::Parse { line, message: message.to_string() };
+    while let Some( character ) = chars.next() {
+        match character {
+            '\n' => { line += 1; line_start = true; continue; },
+            character if character.is_whitespace() => continue,
+            '#' if line_start => {
+                while chars.next_if( |character| *character != '\n' ).is_some() {}
+            },
+            '/' if chars.peek() == Some( &'/' ) => {
+                while chars.next_if( |character| *character != '\n' ).is_some() {}
+            },
+            '/' if chars.peek() == Some( &'*' ) => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some( '/' ) if previous == '*' => break,
+                        Some( character ) => {
+                            if character == '\n' { line += 1; }
+                            previous = character;
+                        },
+                        None => return Err( error( line, "Unterminated comment" ) )
+                    }
+                }
+            },
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some( '"' ) => break,
+                        Some( '\\' ) => match chars.next() {
+                            Some( '"' ) => value.push( '"' ),
+                            Some( '\n' ) => line += 1,
+                            Some( character ) => { value.push( '\\' ); value.push( character ); },
+                            None => return Err( error( line, "Unterminated string" ) )
+                        },
+                        Some( character ) => {
+                            if character == '\n' { line += 1; }
+                            value.push( character );
+                        },
+                        None => return Err( error( line, "Unterminated string" ) )
+                    }
+                }
+                tokens.push( ( Token::Literal( value ), line ) );
+            },
+            '<' => {
+                let mut value = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some( '<' ) => { depth += 1; value.push( '<' ); },
+                        Some( '>' ) => {
+                            depth -= 1;
+                            if depth == 0 { break; }
+                            value.push( '>' );
+                        },
+                        Some( character ) => {
+                            if character == '\n' { line += 1; }
+                            value.push( character );
+                        },
+                        None => return Err( error( line, "Unterminated HTML string" ) )
+                    }
+                }
+                tokens.push( ( Token::Literal( value ), line ) );
+            },
+            '-' if matches!( chars.peek(), Some( '>' ) | Some( '-' ) ) => {
+                tokens.push( ( Token::EdgeOp( chars.next() == Some( '>' ) ), line ) );
+            },
+            '+' => match ( tokens.pop(), chars.clone().find( |character| !character.is_whitespace() ) ) {
+                ( Some( ( Token::Literal( previous ), _ ) ), Some( '"' ) ) => {
+                    tokens.push( ( Token::Literal( previous ), line ) );
+                    tokens.push( ( Token::Punct( '+' ), line ) );
+                },
+                _ => return Err( error( line, "'+' must join two quoted strings" ) )
+            },
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' => tokens.push( ( Token::Punct( character ), line ) ),
+            character if character.is_alphanumeric() || matches!( character, '_' | '.' | '-' ) || !character.is_ascii() => {
+                let mut value = character.to_string();
+                while let Some( character ) = chars.next_if( |character| character.is_alphanumeric() || matches!( character, '_' | '.' ) || !character.is_ascii() ) {
+                    value.push( character );
+                }
+                tokens.push( ( Token::Id( value ), line ) );
+            },
+            character => return Err( error( line, &format!( "Unexpected character '{}'", character ) ) )
+        }
+        line_start = false;
+        // Join `"a" + "b"` now that the right-hand string has been read.
+        if let [ .., ( Token::Literal( _ ), _ ), ( Token::Punct( '+' ), _ ), ( Token::Literal( _ ), _ ) ] = tokens.as_slice() {
+            let ( Some( ( Token::Literal( right ), _ ) ), Some( _ ), Some( ( Token::Literal( mut left ), left_line ) ) ) = ( tokens.pop(), tokens.pop(), tokens.pop() ) else {
+                unreachable!()
+            };
+            left.push_str( &right );
+            tokens.push( ( Token::Literal( left ), left_line ) );
+        }
+    }
+    Ok( tokens )
+}
+
+/// Attribute defaults and the enclosing subgraph of a `{ ... }` block.
+#[derive( Clone, Default )]
+struct Scope {
+    node: Attributes,
+    edge: Attributes,
+    subgraph: Option<String>
+}
+
+struct Parser {
+    tokens: Vec<( Token, usize )>,
+    position: usize,
+    graph: DotGraph
+}
+
+impl Parser {
+    fn new( text: &str ) -> Result<Self, Error> {
+        Ok( Self { tokens: tokenize( text )?, position: 0, graph: DotGraph::default() } )
+    }
+
+    fn peek( &self ) -> Option<&Token> {
+        self.tokens.get( self.position ).map( |( token, _ )| token )
+    }
+
+    fn line( &self ) -> usize {
+        self.tokens.get( self.position ).or( self.tokens.last() ).map_or( 1, |( _, line )| *line )
+    }
+
+    fn error<T>( &self, message: &str ) -> Result<T, Error> {
+        Err( Error::Parse { line: self.line(), message: message.to_string() } )
+    }
+
+    fn next( &mut self ) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn is_keyword( &self, keyword: &str ) -> bool {
+        matches!( self.peek(), Some( Token::Id( id ) ) if id.eq_ignore_ascii_case( keyword ) )
+    }
+
+    fn is_punct( &self, punct: char ) -> bool {
+        self.peek() == Some( &Token::Punct( punct ) )
+    }
+
+    fn eat_punct( &mut self, punct: char ) -> bool {
+        let found = self.is_punct( punct );
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_punct( &mut self, punct: char ) -> Result<(), Error> {
+        if self.eat_punct( punct ) { Ok( () ) } else { self.error( &format!( "Expected '{}'", punct ) ) }
+    }
+
+    fn id( &mut self ) -> Result<String, Error> {
+        match self.peek() {
+            Some( Token::Id( _ ) ) if [ "graph", "digraph", "subgraph", "node", "edge", "strict" ].iter().any( |keyword| self.is_keyword( keyword ) ) => {
+                self.error( "Expected an id but found a keyword" )
+            },
+            Some( Token::Id( _ ) | Token::Literal( _ ) ) => match self.next() {
+                Some( Token::Id( id ) | Token::Literal( id ) ) => Ok( id ),
+                _ => unreachable!()
+            },
+            _ => self.error( "Expected an id" )
+        }
+    }
+
+    fn graph( mut self ) -> Result<DotGraph, Error> {
+        if self.is_keyword( "strict" ) {
+            self.position += 1;
+            self.graph.strict = true;
+        }
+        self.graph.directed = if self.is_keyword( "digraph" ) {
+            true
+        } else if self.is_keyword( "graph" ) {
+            false
+        } else {
+            return self.error( "Expected 'graph' or 'digraph'" );
+        };
+        self.position += 1;
+        if !self.is_punct( '{' ) {
+            self.graph.name = Some( self.id()? );
+        }
+        self.expect_punct( '{' )?;
+        self.statements( &mut Scope::default(), true )?;
+        if self.peek().is_some() {
+            return self.error( "Unexpected content after the graph" );
+        }
+        Ok( self.graph )
+    }
+
+    /// Parses statements up to and including the closing `}`.
+    fn statements( &mut self, scope: &mut Scope, root: bool ) -> Result<Vec<String>, Error> {
+        let mut nodes = Vec::new();
+        while !self.eat_punct( '}' ) {
+            if self.peek().is_none() {
+                return self.error( "Expected '}'" );
+            }
+            let kind = [ "graph", "node", "edge" ].into_iter().find( |keyword| self.is_keyword( keyword ) );
+            if let Some( kind ) = kind {
+                self.position += 1;
+                let attributes = self.attribute_lists()?;
+                match kind {
+                    "node" => attributes.iter().for_each( |( name, value )| set( &mut scope.node, name, value ) ),
+                    "edge" => attributes.iter().for_each( |( name, value )| set( &mut scope.edge, name, value ) ),
+                    _ if root => attributes.iter().for_each( |( name, value )| set( &mut self.graph.attributes, name, value ) ),
+                    _ => {}
+                }
+            } else if matches!( self.tokens.get( self.position + 1 ), Some( ( Token::Punct( '=' ), _ ) ) ) {
+                let name = self.id()?;
+                self.position += 1;
+                let value = self.id()?;
+                if root {
+                    set( &mut self.graph.attributes, &name, &value );
+                }
+            } else {
+                let mut endpoints = self.endpoint( scope, &mut nodes )?;
+                let mut edges = Vec::new();
+                while let Some( Token::EdgeOp( directed ) ) = self.peek().cloned() {
+                    if directed != self.graph.directed {
+                        return self.error( if directed { "'->' in an undirected graph" } else { "'--' in a directed graph" } );
+                    }
+                    self.position += 1;
+                    let next = self.endpoint( scope, &mut nodes )?;
+                    for from in &endpoints {
+                        edges.extend( next.iter().map( |to| ( from.clone(), to.clone() ) ) );
+                    }
+                    endpoints = next;
+                }
+                let attributes = if self.is_punct( '[' ) { self.attribute_lists()? } else { Attributes::new() };
+                if edges.is_empty() {
+                    if let [ node ] = endpoints.as_slice() {
+                        self.graph.set_attributes( node, &attributes );
+                    }
+                }
+                for ( from, to ) in edges {
+                    let attributes = merge( &scope.edge, &attributes );
+                    let duplicate = self.graph.strict && self.graph.edges.iter().any( |edge| {
+                        ( edge.from == from && edge.to == to ) || ( !self.graph.directed && edge.from == to && edge.to == from )
+                    });
+                    if !duplicate {
+                        self.graph.edges.push( DotEdge { from, to, attributes } );
+                    }
+                }
+            }
+            self.eat_punct( ';' );
+        }
+        Ok( nodes )
+    }
+
+    /// A node id or a subgraph, returning the nodes it stands for.
+    fn endpoint( &mut self, scope: &Scope, nodes: &mut Vec<String> ) -> Result<Vec<String>, Error> {
+        if self.is_keyword( "subgraph" ) || self.is_punct( '{' ) {
+            let mut inner = scope.clone();
+            if self.is_keyword( "subgraph" ) {
+                self.position += 1;
+                if !self.is_punct( '{' ) {
+                    inner.subgraph = Some( self.id()? );
+                }
+            }
+            self.expect_punct( '{' )?;
+            let members = self.statements( &mut inner, false )?;
+            nodes.extend( members.iter().cloned() );
+            return Ok( members );
+        }
+        let id = self.id()?;
+        // Ports, `:port` or `:port:compass`, do not affect the graph structure.
+        while self.eat_punct( ':' ) {
+            self.id()?;
+        }
+        self.graph.declare( &id, scope );
+        nodes.push( id.clone() );
+        Ok( vec![ id ] )
+    }
+
+    fn attribute_lists( &mut self ) -> Result<Attributes, Error> {
+        let mut attributes = Attributes::new();
+        if !self.is_punct( '[' ) {
+            return self.error( "Expected '['" );
+        }
+        while self.eat_punct( '[' ) {
+            while !self.eat_punct( ']' ) {
+                let name = self.id()?;
+                let value = if self.eat_punct( '=' ) { self.id()? } else { "true".to_string() };
+                set( &mut attributes, &name, &value );
+                if !self.eat_punct( ',' ) {
+                    self.eat_punct( ';' );
+                }
+            }
+        }
+        Ok( attributes )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::BTreeRepr,
+        traits::IterEdgeIds
+    };
+
+    use super::{ DotGraph, Error };
+
+    type CharGraph = Graph<Undirected, Cyclic, BTreeRepr<char, (), ()>>;
+
+    fn load( text: &str ) -> CharGraph {
+        DotGraph::parse( text ).unwrap().build(
+            |_, node| node.id.chars().next().unwrap(),
+            |_| (),
+            |_| ()
+        ).unwrap()
+    }
+
+    fn edges( graph: &CharGraph ) -> Vec<( char, char )> {
+        graph.iter_edge_ids().map( |( from, to, _ )| ( from, to ) ).collect()
+    }
+
+    #[test]
+    fn test_dot_fixtures() {
+        let graph = load( include_str!( "../../../../cpp/test/graph.dot" ) );
+        assert_eq!( edges( &graph ), vec![
+            ( 'A', 'C' ), ( 'A', 'D' ), ( 'B', 'C' ), ( 'B', 'D' ), ( 'C', 'A' ),
+            ( 'C', 'B' ), ( 'C', 'D' ), ( 'D', 'A' ), ( 'D', 'B' ), ( 'D', 'C' )
+        ]);
+
+        let graph = load( include_str!( "../../../../cpp/test/graph_1.dot" ) );
+        assert_eq!( edges( &graph ), vec![
+            ( 'A', 'B' ), ( 'A', 'D' ), ( 'B', 'A' ), ( 'B', 'C' ),
+            ( 'C', 'B' ), ( 'C', 'D' ), ( 'D', 'A' ), ( 'D', 'C' )
+        ]);
+
+        let graph = load( include_str!( "../../../../cpp/test/graph_3.dot" ) );
+        assert_eq!( edges( &graph ).len(), 12 );
+        assert!( edges( &graph ).iter().all( |( from, to )| from != to ) );
+    }
+
+    #[test]
+    fn test_dot_statements() {
+        let dot = DotGraph::parse( r#"
+            # generated
+            strict digraph "pipeline" {
+                rankdir = LR; // layout
+                node [ shape=box ];
+                a [ label="in \"a\"" ];
+                subgraph cluster_work {
+                    node [ color=red ]
+                    b; c
+                }
+                a -> { b c } -> d:port:n [ weight=2 ];
+                a -> b;
+                /* multi
+                   line */
+                e [ label="x" + "y" ] [ style=dashed ]
+            }
+        "# ).unwrap();
+        assert!( dot.directed && dot.strict );
+        assert_eq!( dot.name.as_deref(), Some( "pipeline" ) );
+        assert_eq!( dot.attributes, vec![ ( "rankdir".to_string(), "LR".to_string() ) ] );
+        assert_eq!( dot.nodes.iter().map( |node| node.id.as_str() ).collect::<Vec<_>>(), vec![ "a", "b", "c", "d", "e" ] );
+        assert_eq!( dot.node( "a" ).unwrap().attributes, vec![
+            ( "shape".to_string(), "box".to_string() ),
+            ( "label".to_string(), "in \"a\"".to_string() )
+        ]);
+        let b = dot.node( "b" ).unwrap();
+        assert_eq!( b.subgraph.as_deref(), Some( "cluster_work" ) );
+        assert_eq!( b.attributes[ 1 ], ( "color".to_string(), "red".to_string() ) );
+        assert_eq!( dot.node( "d" ).unwrap().attributes, vec![ ( "shape".to_string(), "box".to_string() ) ] );
+        assert_eq!( &dot.node( "e" ).unwrap().attributes[ 1.. ], &[
+            ( "label".to_string(), "xy".to_string() ),
+            ( "style".to_string(), "dashed".to_string() )
+        ]);
+        assert_eq!( dot.edges.iter().map( |edge| ( edge.from.as_str(), edge.to.as_str() ) ).collect::<Vec<_>>(),
+            vec![ ( "a", "b" ), ( "a", "c" ), ( "b", "d" ), ( "c", "d" ) ]
+        );
+        assert!( dot.edges.iter().all( |edge| edge.attributes == vec![ ( "weight".to_string(), "2".to_string() ) ] ) );
+
+        let graph: Result<Graph<Undirected, Cyclic, BTreeRepr<usize, (), ()>>, _> = dot.build( |index, _| index, |_| (), |_| () );
+        assert!( matches!( graph, Err( Error::Direction { expected: "graph", found: "digraph" } ) ) );
+        let graph: Graph<Directed, Cyclic, BTreeRepr<usize, (), ()>> = dot.build( |index, _| index, |_| (), |_| () ).unwrap();
+        assert_eq!( graph.iter_edge_ids().count(), 4 );
+    }
+
+    #[test]
+    fn test_dot_errors() {
+        assert!( matches!( DotGraph::parse( "graph { a -> b }" ), Err( Error::Parse { line: 1, .. } ) ) );
+        assert!( matches!( DotGraph::parse( "digraph {\n a -> \n}" ), Err( Error::Parse { line: 3, .. } ) ) );
+        assert!( matches!( DotGraph::parse( "graph { a [ label=\"x ] }" ), Err( Error::Parse { .. } ) ) );
+    }
+}
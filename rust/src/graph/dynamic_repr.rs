@@ -1,16 +1,12 @@
 // Copyright 2024 Bewusstsein Labs
 
-//: Standard
-use std::{
-    cmp::{ Eq, Ord, PartialEq },
-    collections::{ BTreeMap, HashMap },
-    marker::PhantomData,
-    ops::{ Deref, DerefMut, Not }
-};
-
 use crate::{
-    graph::Graph,
-    graph_repr::DynRepr,
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::{ DynRepr, NodeRepr },
     traits::{
         GetNode,
         GetNodeMut,
@@ -20,14 +16,15 @@ use crate::{
         RemoveNode,
         AddEdge,
         RemoveEdge,
-        ContainsNode,
-        ContainsEdge,
         ClearNodes,
         ClearEdges,
         IterNodes,
         IterNodesMut,
         IterEdges,
         IterEdgesMut,
+        IterNodeIds,
+        IterEdgeIds,
+        IterAdjacent,
         IsComplete,
         IsEmpty,
         IsTrivial,
@@ -44,309 +41,414 @@ use crate::{
     }
 };
 
-impl<N, E> GetNode<usize, N> for Graph<DynRepr<N, E>>
+/// Grows the nodes to `size`, filling the gap with default nodes and every adjacency list with absent edges.
+fn grow<N, E>( nodes: &mut Vec<NodeRepr<N, Vec<Option<E>>>>, size: usize )
 where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
+{
+    if size > nodes.len() {
+        nodes.iter_mut().for_each( |node| node.adjs.resize( size, None ) );
+        nodes.resize_with( size, || NodeRepr { node: N::default(), adjs: vec![ None; size ] } );
+    }
+}
+
+impl<D, C, N, E> GetNode<usize, N> for Graph<D, C, DynRepr<N, Option<E>>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
 {
     fn node( &self, id: usize ) -> Option<&N> {
-        self.0.nodes[ id ].as_ref()
+        self.0.0.get( id ).map( |node| &node.node )
     }
 }
 
-impl<N, E> GetNodeMut<usize, N> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> GetNodeMut<usize, N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn node_mut( &mut self, id: usize ) -> Option<&mut N> {
-        self.0.nodes[ id ].as_mut()
+        self.0.0.get_mut( id ).map( |node| &mut node.node )
     }
 }
 
-impl<N, E> GetEdge<usize, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> GetEdge<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn edge( &self, id1: usize, id2: usize ) -> Option<&E> {
-        self.0.edges[[ id1, id2 ]].as_ref()
+        self.0.0.get( id1 ).and_then( |node| node.adjs.get( id2 ) ).and_then( Option::as_ref )
     }
 }
 
-impl<N, E> GetEdgeMut<usize, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> GetEdgeMut<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn edge_mut( &mut self, id1: usize, id2: usize ) -> Option<&mut E> {
-        self.0.edges[[ id1, id2 ]].as_mut()
+        self.0.0.get_mut( id1 ).and_then( |node| node.adjs.get_mut( id2 ) ).and_then( Option::as_mut )
     }
 }
 
-impl<N, E> AddNode<usize, N> for Graph<DynRepr<N, E>>
+/// Adding a node past the end grows the graph, filling the gap with default nodes.
+impl<D, C, N, E> AddNode<usize, N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn add_node( &mut self, id: usize, node: N ) {
-        self.0.nodes[ id ] = Some( node );
+        grow( &mut self.0.0, id + 1 );
+        self.0.0[ id ].node = node;
     }
 }
 
-impl<N, E> RemoveNode<usize, N> for Graph<DynRepr<N, E>>
+/// Ids are positions, so removing a node resets it to the default node and keeps the ids of the others.
+impl<D, C, N, E> RemoveNode<usize, N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn remove_node( &mut self, id: usize ) -> Option<N> {
-        self.0.nodes[ id ].take()
+        self.0.0.get_mut( id ).map( |node| std::mem::take( &mut node.node ) )
     }
 }
 
-impl<N, E> AddEdge<usize, E> for Graph<DynRepr<N, E>>
+/// Adding an edge to or from a node past the end grows the graph the same way.
+impl<D, C, N, E> AddEdge<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn add_edge( &mut self, id1: usize, id2: usize, edge: E ) {
-        self.0.edges[[ id1, id2 ]] = Some( edge );
+        grow( &mut self.0.0, id1.max( id2 ) + 1 );
+        self.0.0[ id1 ].adjs[ id2 ] = Some( edge );
     }
 }
 
-impl<N, E> RemoveEdge<usize, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> RemoveEdge<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn remove_edge( &mut self, id1: usize, id2: usize ) -> Option<E> {
-        self.0.edges[[ id1, id2 ]].take()
+        self.0.0.get_mut( id1 ).and_then( |node| node.adjs.get_mut( id2 ) ).and_then( Option::take )
     }
 }
 
-impl<N, E> ContainsNode<usize> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> ClearNodes for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn contains_node( &self, id: usize ) -> bool {
-        self.0.nodes[ id ].is_some()
+    fn clear_nodes( &mut self ) {
+        self.0.0.clear();
     }
 }
 
-impl<N, E> ContainsEdge<usize> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> ClearEdges for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn contains_edge( &self, id1: usize, id2: usize ) -> bool {
-        self.0.edges[[ id1, id2 ]].is_some()
+    fn clear_edges( &mut self ) {
+        self.0.0.iter_mut().for_each( |node| node.adjs.fill( None ) );
     }
 }
 
-impl<N, E> ClearNodes for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterNodes<N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn clear_nodes( &mut self ) {
-        self.0.nodes.fill( None );
+    fn iter_nodes<'a>( &'a self ) -> impl Iterator<Item = Option<&'a N>>
+    where
+        N: 'a
+    {
+        self.0.0.iter().map( |node| Some( &node.node ) )
     }
 }
 
-impl<N, E> ClearEdges for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterNodesMut<N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn clear_edges( &mut self ) {
-        self.0.edges.fill( None );
+    fn iter_nodes_mut<'a>( &'a mut self ) -> impl Iterator<Item = Option<&'a mut N>>
+    where
+        N: 'a
+    {
+        self.0.0.iter_mut().map( |node| Some( &mut node.node ) )
     }
 }
 
-impl<'a, N, E> IterNodes<'a, N> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterEdges<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
-    N: 'a + Clone + Copy + Default + std::fmt::Debug,
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_nodes( &self ) -> impl Iterator<Item = Option<&N>> {
-        self.0.nodes.iter().map( |node| node.as_ref() )
+    fn iter_edges<'a>( &'a self, id: usize ) -> impl Iterator<Item = Option<&'a E>>
+    where
+        E: 'a
+    {
+        self.0.0.get( id ).into_iter().flat_map( |node| node.adjs.iter().map( Option::as_ref ) )
     }
 }
 
-impl<'a, N, E> IterNodesMut<'a, N> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterEdgesMut<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
-    N: 'a + Clone + Copy + Default + std::fmt::Debug,
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_nodes_mut( &mut self ) -> impl Iterator<Item = Option<&mut N>> {
-        self.0.nodes.iter_mut().map( |node| node.as_mut() )
+    fn iter_edges_mut<'a>( &'a mut self, id: usize ) -> impl Iterator<Item = Option<&'a mut E>>
+    where
+        E: 'a
+    {
+        self.0.0.get_mut( id ).into_iter().flat_map( |node| node.adjs.iter_mut().map( Option::as_mut ) )
     }
 }
 
-impl<'a, N, E> IterEdges<'a, usize, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterNodeIds<usize, N> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
-    E: 'a + Clone + Copy + Default + std::fmt::Debug
+    E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_edges( &self, id: usize ) -> impl Iterator<Item = Option<&E>> {
-        self.0.edges.iter_col( id ).map( |edge| edge.as_ref() )
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( usize, &'a N )>
+    where
+        N: 'a
+    {
+        self.0.0.iter().enumerate().map( |( id, node )| ( id, &node.node ) )
     }
 }
 
-impl<'a, N, E> IterEdgesMut<'a, usize, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterEdgeIds<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
-    E: 'a + Clone + Copy + Default + std::fmt::Debug
+    E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_edges_mut( &mut self, id: usize ) -> impl Iterator<Item = Option<&mut E>> {
-        self.0.edges.iter_col_mut( id ).map( |edge| edge.as_mut() )
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( usize, usize, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.iter().enumerate().flat_map( |( id1, node )| node.adjs.iter().enumerate()
+            .filter_map( move |( id2, edge )| edge.as_ref().map( |edge| ( id1, id2, edge ) ) )
+        )
     }
 }
 
-impl<N, E> IsComplete<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IterAdjacent<usize, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    fn iter_adjacent<'a>( &'a self, id: usize ) -> impl Iterator<Item = ( usize, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.get( id ).into_iter().flat_map( |node| node.adjs.iter().enumerate()
+            .filter_map( |( id, edge )| edge.as_ref().map( |edge| ( id, edge ) ) )
+        )
+    }
+}
+
+impl<D, C, N, E> IsComplete<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
+where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_complete( &self ) -> bool {
-        for ( node, neighbors ) in self.0.data.iter() {
-            if neighbors.adjacencies().len() != self.0.data.len() - 1 {
-                return false;
-            }
-            for neighbor in neighbors.adjacencies().keys() {
-                if !self.0.data.node( neighbor ).is_some_and( |n| n.adjacencies().contains_key( node ) ) {
-                    return false;
-                }
-            }
-        }
-        true
+        self.0.0.iter().enumerate().all( |( id1, node )| node.adjs.iter().enumerate()
+            .all( |( id2, edge )| id1 == id2 || edge.is_some() )
+        )
     }
 }
 
-impl<N, E> IsEmpty<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsEmpty<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_empty( &self ) -> bool {
-        self.0.data.values().all( |neighbors| neighbors.adjacencies().is_empty() )
+        self.0.0.iter().all( |node| node.adjs.iter().all( Option::is_none ) )
     }
 }
 
-impl<N, E> IsTrivial<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsTrivial<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_trivial( &self ) -> bool {
-        self.0.data.len() == 1 && self.data().values().next().is_some_and( |neighbors| neighbors.adjacencies().is_empty())
+        self.0.0.len() == 1 && self.is_empty()
     }
 }
 
-impl<'a, N, E> IsNull<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsNull<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_null( &self ) -> bool {
-        self.0.data.is_empty()
+        self.0.0.is_empty()
     }
 }
 
-impl<N, E> IsChildNode<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsChildNode<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_child_node( &self, node_1: usize ) -> bool {
-        self.0.data.contains_node( node_1 )
+        node_1 < self.0.0.len()
     }
 }
 
-impl<N, E> IsSubgraph<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsSubgraph<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn is_subgraph(&self, subgraph: &Self) -> bool {
-        subgraph.0.data.iter().all( |(node, neighbors)| {
-            self.0.data.get( node ).is_some_and( |graph_node| {
-                neighbors.adjacencies().keys().all( |key| graph_node.adjacencies().contains_key( key ) )
-            })
-        })
+    fn is_subgraph( &self, subgraph: &Self ) -> bool {
+        subgraph.0.0.len() <= self.0.0.len() && subgraph.0.0.iter().enumerate().all( |( id1, node )| node.adjs.iter().enumerate()
+            .all( |( id2, edge )| edge.is_none() || self.edge( id1, id2 ).is_some() )
+        )
     }
 }
 
-impl<N, E> IsProperSubgraph<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsProperSubgraph<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_proper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data != subgraph.0.data && self.is_subgraph( subgraph )
+        self.is_subgraph( subgraph ) && ( self.order() != subgraph.order() || self.size() != subgraph.size() )
     }
 }
 
-impl<N, E> IsImproperSubgraph<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsImproperSubgraph<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_improper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data == subgraph.0.data
+        self.is_subgraph( subgraph ) && self.order() == subgraph.order() && self.size() == subgraph.size()
     }
 }
 
-impl<N, E> IsSpanningSubgraph<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> IsSpanningSubgraph<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_spanning_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data.len() == subgraph.data().len() && self.is_subgraph( subgraph )
+        self.order() == subgraph.order() && self.is_subgraph( subgraph )
     }
 }
 
-impl<N, E> AreAdjacentNodes<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> AreAdjacentNodes<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn are_adjacent_nodes( &self, node_1: usize, node_2: usize ) -> bool {
-        self.is_child_node( node_1.clone() )
-            && self.is_child_node( node_2.clone() )
-            && self.0.data.get( &node_1 ).unwrap().adjacencies().contains_key( &node_2 )
+        self.edge( node_1, node_2 ).is_some()
     }
 }
 
-impl<N, E> AreAdjacentEdges<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> AreAdjacentEdges<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn are_adjacent_edges( &self, node_1: usize, node_2: usize, node_3: usize ) -> bool {
-        self.are_adjacent_nodes( node_1, node_2.clone() )
+        self.are_adjacent_nodes( node_1, node_2 )
             && self.are_adjacent_nodes( node_2, node_3 )
     }
 }
 
-impl<N, E> Order<usize, N, E> for Graph<DynRepr<N, E>>
+impl<D, C, N, E> Order<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn order( &self ) -> usize {
-        self.0.data.len()
+        self.0.0.len()
     }
 }
 
-impl<N, E> Size<usize, N, E> for Graph<DynRepr<N, E>>
+/// Undirected edges are stored in both directions and count once.
+impl<D, C, N, E> Size<usize, N, E> for Graph<D, C, DynRepr<N, Option<E>>>
 where
+    D: Directional,
+    C: Cyclical,
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn size( &self ) -> usize {
-        self.0.data.values().map( |neighbors| neighbors.adjacencies().len() ).sum::<usize>() / 2
+        let edges = self.0.0.iter().map( |node| node.adjs.iter().filter( |edge| edge.is_some() ).count() ).sum::<usize>();
+        match D::DIRECTED {
+            true => edges,
+            false => ( edges + self.0.0.iter().enumerate().filter( |( id, node )| node.adjs.get( *id ).is_some_and( Option::is_some ) ).count() ) / 2
+        }
     }
 }
@@ -0,0 +1,557 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! Standard graphs built in any representation.
+//!
+//! Nodes are numbered `0..n` and hold `N::default()`, edges hold `E::default()`. Undirected graphs get every edge in
+//! both directions. Directed graphs get each edge pointing from the lower to the higher id unless a generator says
+//! otherwise, so that paths, trees and lattices come out acyclic. The random generators draw from [`Rng`], so the
+//! same seed builds the same graph on every platform and version.
+//!
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directed,
+        Acyclic,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Invalid parameter {name}: {message}")]
+    Parameter { name: &'static str, message: String }
+}
+
+fn check( valid: bool, name: &'static str, message: &str ) -> Result<(), Error> {
+    if valid { Ok( () ) } else { Err( Error::Parameter { name, message: message.to_string() } ) }
+}
+
+/// A small seedable random number generator (xoshiro256**).
+///
+/// It is deliberately self-contained rather than a wrapper around an external crate, so that generated fixtures never
+/// change when a dependency does.
+///
+#[derive( Debug, Clone )]
+pub struct Rng( [ u64; 4 ] );
+
+impl Rng {
+    pub fn new( seed: u64 ) -> Self {
+        // Expand the seed with SplitMix64, which never yields the all-zero state.
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add( 0x9e37_79b9_7f4a_7c15 );
+            let mut z = state;
+            z = ( z ^ ( z >> 30 ) ).wrapping_mul( 0xbf58_476d_1ce4_e5b9 );
+            z = ( z ^ ( z >> 27 ) ).wrapping_mul( 0x94d0_49bb_1331_11eb );
+            z ^ ( z >> 31 )
+        };
+        Self( [ next(), next(), next(), next() ] )
+    }
+
+    pub fn next_u64( &mut self ) -> u64 {
+        let s = &mut self.0;
+        let result = s[ 1 ].wrapping_mul( 5 ).rotate_left( 7 ).wrapping_mul( 9 );
+        let t = s[ 1 ] << 17;
+        s[ 2 ] ^= s[ 0 ];
+        s[ 3 ] ^= s[ 1 ];
+        s[ 1 ] ^= s[ 2 ];
+        s[ 0 ] ^= s[ 3 ];
+        s[ 2 ] ^= t;
+        s[ 3 ] = s[ 3 ].rotate_left( 45 );
+        result
+    }
+
+    /// A uniform value in `[0, 1)`.
+    pub fn next_f64( &mut self ) -> f64 {
+        ( self.next_u64() >> 11 ) as f64 / ( 1u64 << 53 ) as f64
+    }
+
+    /// A uniform value in `0..bound`, without modulo bias. Fails for an empty range.
+    pub fn below( &mut self, bound: usize ) -> Result<usize, Error> {
+        check( bound > 0, "bound", "must be positive" )?;
+        Ok( self.index( bound ) )
+    }
+
+    /// [`Rng::below`] for a `bound` the caller knows to be positive.
+    fn index( &mut self, bound: usize ) -> usize {
+        let bound = bound as u64;
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return ( value % bound ) as usize;
+            }
+        }
+    }
+
+    pub fn chance( &mut self, p: f64 ) -> bool {
+        self.next_f64() < p
+    }
+
+    pub fn shuffle<T>( &mut self, values: &mut [ T ] ) {
+        for i in ( 1..values.len() ).rev() {
+            values.swap( i, self.index( i + 1 ) );
+        }
+    }
+}
+
+fn nodes<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    let mut graph = Graph::default();
+    for id in 0..n {
+        graph.add_node( id, N::default() );
+    }
+    graph
+}
+
+/// Adds the edge from `id1` to `id2`, and back for undirected graphs.
+fn connect<D, C, R, E>( graph: &mut Graph<D, C, R>, id1: usize, id2: usize )
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    E: Default,
+    Graph<D, C, R>: AddEdge<usize, E>
+{
+    graph.add_edge( id1, id2, E::default() );
+    if !D::DIRECTED {
+        graph.add_edge( id2, id1, E::default() );
+    }
+}
+
+fn from_edges<D, C, R, N, E, T>( n: usize, edges: T ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>,
+    T: IntoIterator<Item = ( usize, usize )>
+{
+    let mut graph = nodes::<D, C, R, N, E>( n );
+    for ( id1, id2 ) in edges {
+        connect::<D, C, R, E>( &mut graph, id1, id2 );
+    }
+    graph
+}
+
+/// Every pair of distinct nodes joined, in both directions for directed graphs.
+pub fn complete<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    let mut graph = nodes::<D, C, R, N, E>( n );
+    for id1 in 0..n {
+        for id2 in 0..n {
+            if id1 != id2 {
+                graph.add_edge( id1, id2, E::default() );
+            }
+        }
+    }
+    graph
+}
+
+/// `0 - 1 - ... - n-1`.
+pub fn path<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    from_edges::<D, C, R, N, E, _>( n, ( 1..n ).map( |id| ( id - 1, id ) ) )
+}
+
+/// A [`path`] closed by an edge from `n-1` back to `0`. With fewer than three nodes this is just the path.
+pub fn cycle<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    if n < 3 {
+        return path::<D, C, R, N, E>( n );
+    }
+    from_edges::<D, C, R, N, E, _>( n, ( 0..n ).map( |id| ( id, ( id + 1 ) % n ) ) )
+}
+
+/// Node `0` joined to each of the other `n - 1` nodes.
+pub fn star<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    from_edges::<D, C, R, N, E, _>( n, ( 1..n ).map( |id| ( 0, id ) ) )
+}
+
+/// A [`star`] whose outer nodes `1..n` also form a [`cycle`].
+pub fn wheel<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    let rim = n.saturating_sub( 1 );
+    let spokes = ( 1..n ).map( |id| ( 0, id ) );
+    let cycle = ( 0..rim ).map( |id| ( 1 + id, 1 + ( id + 1 ) % rim ) );
+    if rim < 3 {
+        return from_edges::<D, C, R, N, E, _>( n, spokes.chain( ( rim == 2 ).then_some( ( 1, 2 ) ) ) );
+    }
+    from_edges::<D, C, R, N, E, _>( n, spokes.chain( cycle ) )
+}
+
+/// A lattice with the given extent along each dimension.
+///
+/// The node at coordinates `( x0, x1, .. )` has id `x0 + d0 * ( x1 + d1 * ( .. ) )` and is joined to its successor
+/// along each dimension. With `periodic` the last node along a dimension wraps around to the first, making a torus.
+///
+pub fn lattice<D, C, R, N, E>( dimensions: &[ usize ], periodic: bool ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    let n: usize = dimensions.iter().product();
+    let mut edges = BTreeSet::new();
+    for id in 0..n {
+        let mut stride = 1;
+        for &extent in dimensions {
+            let coordinate = id / stride % extent;
+            if coordinate + 1 < extent {
+                edges.insert( ( id, id + stride ) );
+            } else if periodic && extent > 2 {
+                edges.insert( ( id, id - coordinate * stride ) );
+            }
+            stride *= extent;
+        }
+    }
+    from_edges::<D, C, R, N, E, _>( n, edges )
+}
+
+/// A `rows` by `columns` [`lattice`], numbered row by row.
+pub fn grid<D, C, R, N, E>( rows: usize, columns: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    lattice::<D, C, R, N, E>( &[ columns, rows ], false )
+}
+
+/// The `dimension`-dimensional hypercube: `2^dimension` nodes, joined when their ids differ in one bit.
+///
+/// Fails unless `2^dimension` fits in a `usize`.
+///
+pub fn hypercube<D, C, R, N, E>( dimension: u32 ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    check( dimension < usize::BITS, "dimension", "must be less than the number of bits of usize" )?;
+    let n = 1usize << dimension;
+    Ok( from_edges::<D, C, R, N, E, _>( n, ( 0..n ).flat_map( |id| ( 0..dimension )
+        .map( move |bit| ( id, id | 1 << bit ) )
+        .filter( |( id1, id2 )| id1 != id2 )
+    )))
+}
+
+/// Nodes `0..m` each joined to every node of `m..m + n`.
+pub fn complete_bipartite<D, C, R, N, E>( m: usize, n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    from_edges::<D, C, R, N, E, _>( m + n, ( 0..m ).flat_map( |id1| ( m..m + n ).map( move |id2| ( id1, id2 ) ) ) )
+}
+
+/// A binary tree of `n` nodes filled level by level: the children of `i` are `2i + 1` and `2i + 2`.
+pub fn binary_tree<D, C, R, N, E>( n: usize ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    from_edges::<D, C, R, N, E, _>( n, ( 1..n ).map( |id| ( ( id - 1 ) / 2, id ) ) )
+}
+
+/// The Erdős–Rényi graph G(n, p): each possible edge present independently with probability `p`.
+///
+/// For directed graphs both directions of a pair are drawn separately.
+///
+pub fn erdos_renyi<D, C, R, N, E>( n: usize, p: f64, rng: &mut Rng ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    check( ( 0.0..=1.0 ).contains( &p ), "p", "must be a probability" )?;
+    let mut graph = nodes::<D, C, R, N, E>( n );
+    for id1 in 0..n {
+        let ids = if D::DIRECTED { 0..n } else { id1 + 1..n };
+        for id2 in ids {
+            if id1 != id2 && rng.chance( p ) {
+                connect::<D, C, R, E>( &mut graph, id1, id2 );
+            }
+        }
+    }
+    Ok( graph )
+}
+
+/// A Barabási–Albert preferential attachment graph of `n` nodes.
+///
+/// Starts from a [`star`] of `m + 1` nodes; every further node joins `m` distinct earlier nodes chosen with probability
+/// proportional to their degree. Directed edges point from the new node to the earlier ones.
+///
+pub fn barabasi_albert<D, C, R, N, E>( n: usize, m: usize, rng: &mut Rng ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    check( m >= 1 && m < n, "m", "must be at least 1 and less than n" )?;
+    let mut edges: Vec<_> = ( 1..=m ).map( |id| ( id, 0 ) ).collect();
+    // Every node once per incident edge, so a uniform pick is a pick by degree.
+    let mut ends: Vec<_> = edges.iter().flat_map( |&( id1, id2 )| [ id1, id2 ] ).collect();
+    for id in m + 1..n {
+        let mut targets = BTreeSet::new();
+        while targets.len() < m {
+            targets.insert( ends[ rng.index( ends.len() ) ] );
+        }
+        for target in targets {
+            edges.push( ( id, target ) );
+            ends.extend( [ id, target ] );
+        }
+    }
+    Ok( from_edges::<D, C, R, N, E, _>( n, edges ) )
+}
+
+/// A Watts–Strogatz small-world graph.
+///
+/// Starts from a ring of `n` nodes each joined to its `k / 2` nearest neighbours on either side, then rewires the far
+/// end of each edge to a uniformly chosen node with probability `p`, avoiding self-loops and duplicate edges.
+///
+pub fn watts_strogatz<D, C, R, N, E>( n: usize, k: usize, p: f64, rng: &mut Rng ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    check( k.is_multiple_of( 2 ) && k < n, "k", "must be even and less than n" )?;
+    check( ( 0.0..=1.0 ).contains( &p ), "p", "must be a probability" )?;
+    let key = |id1: usize, id2: usize| ( id1.min( id2 ), id1.max( id2 ) );
+    let mut edges: BTreeSet<_> = ( 0..n )
+        .flat_map( |id| ( 1..=k / 2 ).map( move |offset| key( id, ( id + offset ) % n ) ) )
+        .collect();
+    let mut degrees = vec![ k; n ];
+    for offset in 1..=k / 2 {
+        for id in 0..n {
+            let far = ( id + offset ) % n;
+            if !rng.chance( p ) || !edges.contains( &key( id, far ) ) {
+                continue;
+            }
+            // A node already joined to every other keeps its edges.
+            if degrees[ id ] == n - 1 {
+                continue;
+            }
+            let target = loop {
+                let target = rng.index( n );
+                if target != id && !edges.contains( &key( id, target ) ) {
+                    break target;
+                }
+            };
+            edges.remove( &key( id, far ) );
+            edges.insert( key( id, target ) );
+            degrees[ far ] -= 1;
+            degrees[ target ] += 1;
+        }
+    }
+    Ok( from_edges::<D, C, R, N, E, _>( n, edges ) )
+}
+
+/// A random directed acyclic graph.
+///
+/// The nodes are put in a random order and each edge from an earlier node to a later one is present independently
+/// with probability `p`, so the topological order is not simply the order of the ids.
+///
+pub fn random_dag<R, N, E>( n: usize, p: f64, rng: &mut Rng ) -> Result<Graph<Directed, Acyclic, R>, Error>
+where
+    R: GraphRepr,
+    N: Default,
+    E: Default,
+    Graph<Directed, Acyclic, R>: Default + AddNode<usize, N> + AddEdge<usize, E>
+{
+    check( ( 0.0..=1.0 ).contains( &p ), "p", "must be a probability" )?;
+    let mut order: Vec<_> = ( 0..n ).collect();
+    rng.shuffle( &mut order );
+    let mut edges = Vec::new();
+    for i in 0..n {
+        for j in i + 1..n {
+            if rng.chance( p ) {
+                edges.push( ( order[ i ], order[ j ] ) );
+            }
+        }
+    }
+    Ok( from_edges::<Directed, Acyclic, R, N, E, _>( n, edges ) )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic, Acyclic },
+        graph_repr::{ BTreeRepr, DynRepr },
+        traits::IterEdgeIds
+    };
+
+    use super::*;
+
+    type Undir = Graph<Undirected, Cyclic, BTreeRepr<usize, (), ()>>;
+    type Dir = Graph<Directed, Cyclic, BTreeRepr<usize, (), ()>>;
+
+    fn edges<G>( graph: &G ) -> Vec<( usize, usize )>
+    where
+        G: IterEdgeIds<usize, ()>
+    {
+        graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect()
+    }
+
+    fn undirected( graph: &Undir ) -> Vec<( usize, usize )> {
+        edges( graph ).into_iter().filter( |( id1, id2 )| id1 < id2 ).collect()
+    }
+
+    #[test]
+    fn test_deterministic() {
+        assert_eq!( edges( &complete::<Directed, Cyclic, BTreeRepr<usize, (), ()>, (), ()>( 3 ) ).len(), 6 );
+        assert_eq!( edges( &path::<Directed, Cyclic, BTreeRepr<usize, (), ()>, (), ()>( 3 ) ), vec![ ( 0, 1 ), ( 1, 2 ) ] );
+        let triangle: Dir = cycle::<_, _, _, (), ()>( 3 );
+        assert_eq!( edges( &triangle ), vec![ ( 0, 1 ), ( 1, 2 ), ( 2, 0 ) ] );
+        let pair: Undir = cycle::<_, _, _, (), ()>( 2 );
+        assert_eq!( edges( &pair ), vec![ ( 0, 1 ), ( 1, 0 ) ] );
+        let hub: Undir = star::<_, _, _, (), ()>( 4 );
+        assert_eq!( undirected( &hub ), vec![ ( 0, 1 ), ( 0, 2 ), ( 0, 3 ) ] );
+        let spoked: Undir = wheel::<_, _, _, (), ()>( 5 );
+        assert_eq!( undirected( &spoked ).len(), 8 );
+        let rectangle: Undir = grid::<_, _, _, (), ()>( 2, 3 );
+        assert_eq!( undirected( &rectangle ), vec![ ( 0, 1 ), ( 0, 3 ), ( 1, 2 ), ( 1, 4 ), ( 2, 5 ), ( 3, 4 ), ( 4, 5 ) ] );
+        let torus: Undir = lattice::<_, _, _, (), ()>( &[ 3, 3 ], true );
+        assert_eq!( undirected( &torus ).len(), 18 );
+        let cube: Undir = hypercube::<_, _, _, (), ()>( 3 ).unwrap();
+        assert_eq!( undirected( &cube ).len(), 12 );
+        let bipartite: Undir = complete_bipartite::<_, _, _, (), ()>( 2, 3 );
+        assert_eq!( undirected( &bipartite ).len(), 6 );
+        let tree: Dir = binary_tree::<_, _, _, (), ()>( 6 );
+        assert_eq!( edges( &tree ), vec![ ( 0, 1 ), ( 0, 2 ), ( 1, 3 ), ( 1, 4 ), ( 2, 5 ) ] );
+        let dynamic: Graph<Undirected, Cyclic, DynRepr<(), Option<()>>> = complete::<_, _, _, (), ()>( 4 );
+        assert_eq!( dynamic.iter_edge_ids().count(), 12 );
+    }
+
+    #[test]
+    fn test_random() {
+        let first: Undir = erdos_renyi::<_, _, _, (), ()>( 20, 0.3, &mut Rng::new( 7 ) ).unwrap();
+        let second: Undir = erdos_renyi::<_, _, _, (), ()>( 20, 0.3, &mut Rng::new( 7 ) ).unwrap();
+        assert_eq!( edges( &first ), edges( &second ) );
+        assert!( erdos_renyi::<Undirected, Cyclic, BTreeRepr<usize, (), ()>, (), ()>( 3, 1.5, &mut Rng::new( 7 ) ).is_err() );
+
+        let scale_free: Undir = barabasi_albert::<_, _, _, (), ()>( 30, 2, &mut Rng::new( 1 ) ).unwrap();
+        assert_eq!( undirected( &scale_free ).len(), 2 + 27 * 2 );
+
+        let small_world: Undir = watts_strogatz::<_, _, _, (), ()>( 20, 4, 0.2, &mut Rng::new( 3 ) ).unwrap();
+        assert_eq!( undirected( &small_world ).len(), 40 );
+        assert!( edges( &small_world ).iter().all( |( id1, id2 )| id1 != id2 ) );
+
+        let dag: Graph<Directed, Acyclic, BTreeRepr<usize, (), ()>> = random_dag::<_, (), ()>( 15, 0.4, &mut Rng::new( 5 ) ).unwrap();
+        // Peel off nodes without incoming edges; an acyclic graph is used up completely.
+        let mut remaining = edges( &dag );
+        let mut nodes: Vec<usize> = ( 0..15 ).collect();
+        while let Some( index ) = nodes.iter().position( |node| remaining.iter().all( |( _, id2 )| id2 != node ) ) {
+            let node = nodes.remove( index );
+            remaining.retain( |( id1, _ )| *id1 != node );
+        }
+        assert!( nodes.is_empty() );
+    }
+
+    #[test]
+    fn test_parameters() {
+        assert!( matches!( Rng::new( 1 ).below( 0 ), Err( Error::Parameter { name: "bound", .. } ) ) );
+        assert!( Rng::new( 1 ).below( 1 ).is_ok_and( |value| value == 0 ) );
+        assert!( matches!(
+            hypercube::<Undirected, Cyclic, BTreeRepr<usize, (), ()>, (), ()>( usize::BITS ),
+            Err( Error::Parameter { name: "dimension", .. } )
+        ));
+        assert!( hypercube::<Undirected, Cyclic, BTreeRepr<usize, (), ()>, (), ()>( u32::MAX ).is_err() );
+    }
+
+    #[test]
+    fn test_watts_strogatz_dense() {
+        // The ring is complete, so no edge can be rewired and the tracked degrees must say so.
+        for seed in 0..20 {
+            let graph: Undir = watts_strogatz::<_, _, _, (), ()>( 7, 6, 1.0, &mut Rng::new( seed ) ).unwrap();
+            assert_eq!( undirected( &graph ).len(), 21 );
+        }
+        let mut rng = Rng::new( 9 );
+        let graph: Undir = watts_strogatz::<_, _, _, (), ()>( 50, 4, 1.0, &mut rng ).unwrap();
+        let pairs = undirected( &graph );
+        assert_eq!( pairs.len(), 100 );
+        assert!( pairs.iter().all( |( id1, id2 )| id1 != id2 ) );
+    }
+}
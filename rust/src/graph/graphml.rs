@@ -0,0 +1,736 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::{ BTreeMap, BTreeSet, HashMap },
+    fmt::Display,
+    io::{ self, Write },
+    str::FromStr
+};
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Line {line}: {message}")]
+    Xml { line: usize, message: String },
+    #[error("Missing attribute '{attribute}' on <{element}>")]
+    MissingAttribute { element: String, attribute: String },
+    #[error("Expected an {expected} graph but found an {found} one")]
+    Direction { expected: &'static str, found: &'static str },
+    #[error("Unknown key type '{0}'")]
+    UnknownType( String ),
+    #[error("Undeclared key '{0}'")]
+    UndeclaredKey( String ),
+    #[error("Invalid {kind} value '{value}' for key '{key}'")]
+    InvalidValue { key: String, kind: &'static str, value: String },
+    #[error("Invalid node id '{0}'")]
+    InvalidId( String ),
+    #[error("Edge references the undeclared node '{0}'")]
+    UnknownNode( String ),
+    #[error("Invalid data: {0}")]
+    Data( String ),
+    #[error("No <graph> element")]
+    MissingGraph
+}
+
+/// The GraphML `attr.type` of a key.
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub enum KeyType {
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    String
+}
+
+impl KeyType {
+    pub fn name( &self ) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::Float => "float",
+            Self::Double => "double",
+            Self::String => "string"
+        }
+    }
+
+    fn parse( name: &str ) -> Result<Self, Error> {
+        match name {
+            "boolean" => Ok( Self::Boolean ),
+            "int" => Ok( Self::Int ),
+            "long" => Ok( Self::Long ),
+            "float" => Ok( Self::Float ),
+            "double" => Ok( Self::Double ),
+            "string" => Ok( Self::String ),
+            name => Err( Error::UnknownType( name.to_string() ) )
+        }
+    }
+
+    fn value( &self, key: &str, text: &str ) -> Result<Value, Error> {
+        let trimmed = text.trim();
+        let invalid = || Error::InvalidValue { key: key.to_string(), kind: self.name(), value: trimmed.to_string() };
+        Ok( match self {
+            Self::Boolean => Value::Boolean( match trimmed.to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => return Err( invalid() )
+            }),
+            Self::Int => Value::Int( trimmed.parse().map_err( |_| invalid() )? ),
+            Self::Long => Value::Long( trimmed.parse().map_err( |_| invalid() )? ),
+            Self::Float => Value::Float( trimmed.parse().map_err( |_| invalid() )? ),
+            Self::Double => Value::Double( trimmed.parse().map_err( |_| invalid() )? ),
+            Self::String => Value::String( text.to_string() )
+        })
+    }
+}
+
+#[derive( Debug, Clone, PartialEq )]
+pub enum Value {
+    Boolean( bool ),
+    Int( i32 ),
+    Long( i64 ),
+    Float( f32 ),
+    Double( f64 ),
+    String( String )
+}
+
+impl Value {
+    pub fn key_type( &self ) -> KeyType {
+        match self {
+            Self::Boolean( _ ) => KeyType::Boolean,
+            Self::Int( _ ) => KeyType::Int,
+            Self::Long( _ ) => KeyType::Long,
+            Self::Float( _ ) => KeyType::Float,
+            Self::Double( _ ) => KeyType::Double,
+            Self::String( _ ) => KeyType::String
+        }
+    }
+
+    pub fn as_bool( &self ) -> Option<bool> {
+        match self {
+            Self::Boolean( value ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    /// Integer values of either width.
+    pub fn as_i64( &self ) -> Option<i64> {
+        match self {
+            Self::Int( value ) => Some( *value as i64 ),
+            Self::Long( value ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    /// Numeric values of any type.
+    pub fn as_f64( &self ) -> Option<f64> {
+        match self {
+            Self::Int( value ) => Some( *value as f64 ),
+            Self::Long( value ) => Some( *value as f64 ),
+            Self::Float( value ) => Some( *value as f64 ),
+            Self::Double( value ) => Some( *value ),
+            _ => None
+        }
+    }
+
+    pub fn as_str( &self ) -> Option<&str> {
+        match self {
+            Self::String( value ) => Some( value ),
+            _ => None
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        match self {
+            Self::Boolean( value ) => write!( f, "{}", value ),
+            Self::Int( value ) => write!( f, "{}", value ),
+            Self::Long( value ) => write!( f, "{}", value ),
+            Self::Float( value ) => write!( f, "{}", value ),
+            Self::Double( value ) => write!( f, "{}", value ),
+            Self::String( value ) => write!( f, "{}", value )
+        }
+    }
+}
+
+/// A `<key>` declaration: an attribute of node or edge data.
+#[derive( Debug, Clone, PartialEq )]
+pub struct Key {
+    pub name: String,
+    pub key_type: KeyType,
+    pub default: Option<Value>
+}
+
+impl Key {
+    pub fn new<S>( name: S, key_type: KeyType ) -> Self
+    where
+        S: Into<String>
+    {
+        Self { name: name.into(), key_type, default: None }
+    }
+
+    pub fn default( mut self, default: Value ) -> Self {
+        self.default = Some( default );
+        self
+    }
+}
+
+/// The data attributes of one node or edge, by key name.
+pub type Data = BTreeMap<String, Value>;
+
+/// Node or edge data that can be stored as GraphML `<data>` elements.
+///
+/// `from_data` receives every attribute of the element, with key defaults filled in for missing ones, including
+/// attributes of keys that `keys` does not declare.
+///
+pub trait GraphMlData: Sized {
+    fn keys() -> Vec<Key>;
+    fn to_data( &self ) -> Data;
+    fn from_data( data: &Data ) -> Result<Self, Error>;
+}
+
+impl GraphMlData for () {
+    fn keys() -> Vec<Key> {
+        Vec::new()
+    }
+
+    fn to_data( &self ) -> Data {
+        Data::new()
+    }
+
+    fn from_data( _: &Data ) -> Result<Self, Error> {
+        Ok( () )
+    }
+}
+
+macro_rules! impl_graphml_data {
+    ( $( $ty:ty => $key_type:ident, $variant:ident, $accessor:ident );* $(;)? ) => { $(
+        /// Stored under the key `value`.
+        impl GraphMlData for $ty {
+            fn keys() -> Vec<Key> {
+                vec![ Key::new( "value", KeyType::$key_type ) ]
+            }
+
+            fn to_data( &self ) -> Data {
+                Data::from( [ ( "value".to_string(), Value::$variant( *self as _ ) ) ] )
+            }
+
+            fn from_data( data: &Data ) -> Result<Self, Error> {
+                match data.get( "value" ) {
+                    Some( value ) => value.$accessor().map( |value| value as $ty ).ok_or_else( || Error::InvalidValue {
+                        key: "value".to_string(),
+                        kind: KeyType::$key_type.name(),
+                        value: value.to_string()
+                    }),
+                    None => Ok( <$ty>::default() )
+                }
+            }
+        }
+    )* };
+}
+
+impl_graphml_data! {
+    bool => Boolean, Boolean, as_bool;
+    i32 => Int, Int, as_i64;
+    i64 => Long, Long, as_i64;
+    usize => Long, Long, as_i64;
+    f32 => Float, Float, as_f64;
+    f64 => Double, Double, as_f64
+}
+
+/// Writes `graph` as a GraphML document.
+///
+/// The `edgedefault` of the graph comes from `D`. Undirected graphs, which store each edge in both directions, have
+/// each edge written once.
+///
+pub fn write<D, C, R, I, N, E, W>( graph: &Graph<D, C, R>, mut writer: W ) -> io::Result<()>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord + Display,
+    N: GraphMlData,
+    E: GraphMlData,
+    Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+    W: Write
+{
+    writeln!( writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>" )?;
+    writeln!( writer, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">" )?;
+    let mut key_ids = HashMap::new();
+    for ( domain, keys ) in [ ( "node", N::keys() ), ( "edge", E::keys() ) ] {
+        for key in keys {
+            let id = format!( "d{}", key_ids.len() );
+            write!( writer, "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"", id, domain, escape( &key.name ), key.key_type.name() )?;
+            match &key.default {
+                Some( default ) => writeln!( writer, ">\n    <default>{}</default>\n  </key>", escape( &default.to_string() ) )?,
+                None => writeln!( writer, "/>" )?
+            }
+            key_ids.insert( ( domain, key.name ), id );
+        }
+    }
+    writeln!( writer, "  <graph id=\"G\" edgedefault=\"{}\">", if D::DIRECTED { "directed" } else { "undirected" } )?;
+    let write_data = |writer: &mut W, domain: &'static str, data: Data| -> io::Result<()> {
+        for ( name, value ) in data {
+            if let Some( id ) = key_ids.get( &( domain, name ) ) {
+                writeln!( writer, "      <data key=\"{}\">{}</data>", id, escape( &value.to_string() ) )?;
+            }
+        }
+        Ok( () )
+    };
+    for ( id, node ) in graph.iter_node_ids() {
+        writeln!( writer, "    <node id=\"{}\">", escape( &id.to_string() ) )?;
+        write_data( &mut writer, "node", node.to_data() )?;
+        writeln!( writer, "    </node>" )?;
+    }
+    let mut written = BTreeSet::new();
+    for ( id1, id2, edge ) in graph.iter_edge_ids() {
+        if !D::DIRECTED {
+            if written.contains( &( id2.clone(), id1.clone() ) ) {
+                continue;
+            }
+            written.insert( ( id1.clone(), id2.clone() ) );
+        }
+        writeln!( writer, "    <edge source=\"{}\" target=\"{}\">", escape( &id1.to_string() ), escape( &id2.to_string() ) )?;
+        write_data( &mut writer, "edge", edge.to_data() )?;
+        writeln!( writer, "    </edge>" )?;
+    }
+    writeln!( writer, "  </graph>" )?;
+    writeln!( writer, "</graphml>" )
+}
+
+pub fn to_string<D, C, R, I, N, E>( graph: &Graph<D, C, R> ) -> String
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord + Display,
+    N: GraphMlData,
+    E: GraphMlData,
+    Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    let mut graphml = Vec::new();
+    write( graph, &mut graphml ).expect( "Failed to write to memory" );
+    String::from_utf8( graphml ).expect( "GraphML output is not valid UTF-8" )
+}
+
+/// Reads the first `<graph>` of a GraphML document.
+///
+/// Node ids are parsed with `I::from_str`. The document's `edgedefault` must match `D`; edges of an undirected graph
+/// are added in both directions. Nested graphs and hyperedges are skipped.
+///
+pub fn read<D, C, R, I, N, E>( text: &str ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + FromStr,
+    N: GraphMlData,
+    E: GraphMlData,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E>
+{
+    let root = Element::parse( text )?;
+    let mut keys: HashMap<&str, ( &str, Key )> = HashMap::new();
+    for key in root.children( "key" ) {
+        let id = key.attribute( "id" )?;
+        let key_type = KeyType::parse( key.attribute_or( "attr.type", "string" ) )?;
+        let name = key.attribute_or( "attr.name", id );
+        let default = key.children( "default" ).next()
+            .map( |default| key_type.value( name, &default.text ) )
+            .transpose()?;
+        keys.insert( id, ( key.attribute_or( "for", "all" ), Key { name: name.to_string(), key_type, default } ) );
+    }
+    let data = |element: &Element, domain: &str| -> Result<Data, Error> {
+        let mut data: Data = keys.values()
+            .filter( |( target, _ )| *target == domain || *target == "all" )
+            .filter_map( |( _, key )| key.default.clone().map( |default| ( key.name.clone(), default ) ) )
+            .collect();
+        for element in element.children( "data" ) {
+            let id = element.attribute( "key" )?;
+            let ( _, key ) = keys.get( id ).ok_or_else( || Error::UndeclaredKey( id.to_string() ) )?;
+            data.insert( key.name.clone(), key.key_type.value( &key.name, &element.text )? );
+        }
+        Ok( data )
+    };
+
+    let graph_element = root.children( "graph" ).next().ok_or( Error::MissingGraph )?;
+    let directed = match graph_element.attribute( "edgedefault" )? {
+        "directed" => true,
+        "undirected" => false,
+        value => return Err( Error::Xml { line: graph_element.line, message: format!( "Invalid edgedefault '{}'", value ) } )
+    };
+    let kind = |directed| if directed { "directed" } else { "undirected" };
+    if directed != D::DIRECTED {
+        return Err( Error::Direction { expected: kind( D::DIRECTED ), found: kind( directed ) } );
+    }
+
+    let mut graph = Graph::default();
+    let mut ids = HashMap::new();
+    for node in graph_element.children( "node" ) {
+        let name = node.attribute( "id" )?;
+        let id = I::from_str( name ).map_err( |_| Error::InvalidId( name.to_string() ) )?;
+        graph.add_node( id.clone(), N::from_data( &data( node, "node" )? )? );
+        ids.insert( name, id );
+    }
+    for edge in graph_element.children( "edge" ) {
+        let edge_directed = match edge.attributes.iter().find( |( name, _ )| name == "directed" ) {
+            Some( ( _, value ) ) => value == "true",
+            None => directed
+        };
+        if edge_directed != directed {
+            return Err( Error::Direction { expected: kind( directed ), found: kind( edge_directed ) } );
+        }
+        let endpoint = |attribute| -> Result<I, Error> {
+            let name = edge.attribute( attribute )?;
+            ids.get( name ).cloned().ok_or_else( || Error::UnknownNode( name.to_string() ) )
+        };
+        let ( source, target ) = ( endpoint( "source" )?, endpoint( "target" )? );
+        let edge_data = data( edge, "edge" )?;
+        if !directed {
+            graph.add_edge( target.clone(), source.clone(), E::from_data( &edge_data )? );
+        }
+        graph.add_edge( source, target, E::from_data( &edge_data )? );
+    }
+    Ok( graph )
+}
+
+fn escape( value: &str ) -> String {
+    let mut escaped = String::with_capacity( value.len() );
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str( "&amp;" ),
+            '<' => escaped.push_str( "&lt;" ),
+            '>' => escaped.push_str( "&gt;" ),
+            '"' => escaped.push_str( "&quot;" ),
+            '\'' => escaped.push_str( "&apos;" ),
+            character => escaped.push( character )
+        }
+    }
+    escaped
+}
+
+/// The subset of XML GraphML needs: elements, attributes and text, without namespaces.
+#[derive( Debug, Default )]
+struct Element {
+    name: String,
+    attributes: Vec<( String, String )>,
+    children: Vec<Element>,
+    text: String,
+    line: usize
+}
+
+impl Element {
+    fn parse( text: &str ) -> Result<Self, Error> {
+        let mut reader = Reader { text, position: 0 };
+        reader.skip_misc()?;
+        let root = reader.element()?;
+        reader.skip_misc()?;
+        if reader.position < text.len() {
+            return reader.error( "Unexpected content after the root element" );
+        }
+        if root.name != "graphml" {
+            return Err( Error::Xml { line: 1, message: format!( "Expected <graphml> but found <{}>", root.name ) } );
+        }
+        Ok( root )
+    }
+
+    fn children<'a>( &'a self, name: &'a str ) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter( move |child| child.name == name )
+    }
+
+    fn attribute( &self, name: &str ) -> Result<&str, Error> {
+        self.attributes.iter()
+            .find( |( key, _ )| key == name )
+            .map( |( _, value )| value.as_str() )
+            .ok_or_else( || Error::MissingAttribute { element: self.name.clone(), attribute: name.to_string() } )
+    }
+
+    fn attribute_or<'a>( &'a self, name: &str, default: &'a str ) -> &'a str {
+        self.attribute( name ).unwrap_or( default )
+    }
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    position: usize
+}
+
+impl Reader<'_> {
+    fn rest( &self ) -> &str {
+        &self.text[ self.position.. ]
+    }
+
+    fn line( &self ) -> usize {
+        self.text[ ..self.position ].matches( '\n' ).count() + 1
+    }
+
+    fn error<T>( &self, message: &str ) -> Result<T, Error> {
+        Err( Error::Xml { line: self.line(), message: message.to_string() } )
+    }
+
+    fn skip_whitespace( &mut self ) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips past `end`, which must occur.
+    fn skip_past( &mut self, end: &str ) -> Result<(), Error> {
+        match self.rest().find( end ) {
+            Some( index ) => {
+                self.position += index + end.len();
+                Ok( () )
+            },
+            None => self.error( &format!( "Expected '{}'", end ) )
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctypes.
+    fn skip_misc( &mut self ) -> Result<(), Error> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with( "<?" ) {
+                self.skip_past( "?>" )?;
+            } else if self.rest().starts_with( "<!--" ) {
+                self.skip_past( "-->" )?;
+            } else if self.rest().starts_with( "<!" ) && !self.rest().starts_with( "<![CDATA[" ) {
+                self.skip_past( ">" )?;
+            } else {
+                return Ok( () );
+            }
+        }
+    }
+
+    fn name( &mut self ) -> Result<String, Error> {
+        let rest = self.rest();
+        let length = rest.find( |character: char| character.is_whitespace() || matches!( character, '/' | '>' | '=' ) ).unwrap_or( rest.len() );
+        if length == 0 {
+            return self.error( "Expected a name" );
+        }
+        // Namespace prefixes are dropped, GraphML documents use a single namespace.
+        let name = rest[ ..length ].rsplit( ':' ).next().unwrap_or_default().to_string();
+        self.position += length;
+        Ok( name )
+    }
+
+    fn element( &mut self ) -> Result<Element, Error> {
+        let line = self.line();
+        if !self.rest().starts_with( '<' ) {
+            return self.error( "Expected an element" );
+        }
+        self.position += 1;
+        let mut element = Element { name: self.name()?, line, ..Element::default() };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with( "/>" ) {
+                self.position += 2;
+                return Ok( element );
+            }
+            if self.rest().starts_with( '>' ) {
+                self.position += 1;
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with( '=' ) {
+                return self.error( "Expected '='" );
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some( quote @ ( '"' | '\'' ) ) => quote,
+                _ => return self.error( "Expected a quoted attribute value" )
+            };
+            self.position += 1;
+            let Some( length ) = self.rest().find( quote ) else {
+                return self.error( "Unterminated attribute value" );
+            };
+            let value = self.unescape( &self.rest()[ ..length ] )?;
+            self.position += length + 1;
+            element.attributes.push( ( name, value ) );
+        }
+        loop {
+            if self.rest().starts_with( "</" ) {
+                self.position += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return self.error( &format!( "Expected </{}> but found </{}>", element.name, name ) );
+                }
+                self.skip_whitespace();
+                return self.skip_past( ">" ).map( |_| element );
+            } else if self.rest().starts_with( "<![CDATA[" ) {
+                self.position += 9;
+                let Some( length ) = self.rest().find( "]]>" ) else {
+                    return self.error( "Unterminated CDATA section" );
+                };
+                element.text.push_str( &self.rest()[ ..length ] );
+                self.position += length + 3;
+            } else if self.rest().starts_with( "<!--" ) || self.rest().starts_with( "<?" ) {
+                self.skip_misc()?;
+            } else if self.rest().starts_with( '<' ) {
+                element.children.push( self.element()? );
+            } else if self.rest().is_empty() {
+                return self.error( &format!( "Unterminated <{}>", element.name ) );
+            } else {
+                let length = self.rest().find( '<' ).unwrap_or( self.rest().len() );
+                let text = self.unescape( &self.rest()[ ..length ] )?;
+                element.text.push_str( &text );
+                self.position += length;
+            }
+        }
+    }
+
+    fn unescape( &self, value: &str ) -> Result<String, Error> {
+        let mut unescaped = String::with_capacity( value.len() );
+        let mut rest = value;
+        while let Some( start ) = rest.find( '&' ) {
+            unescaped.push_str( &rest[ ..start ] );
+            let Some( end ) = rest[ start.. ].find( ';' ) else {
+                return self.error( "Unterminated entity" );
+            };
+            let entity = &rest[ start + 1..start + end ];
+            let character = match entity {
+                "amp" => Some( '&' ),
+                "lt" => Some( '<' ),
+                "gt" => Some( '>' ),
+                "quot" => Some( '"' ),
+                "apos" => Some( '\'' ),
+                entity => match entity.strip_prefix( "#x" ).or( entity.strip_prefix( "#X" ) ) {
+                    Some( hex ) => u32::from_str_radix( hex, 16 ).ok().and_then( char::from_u32 ),
+                    None => entity.strip_prefix( '#' ).and_then( |decimal| decimal.parse().ok() ).and_then( char::from_u32 )
+                }
+            };
+            match character {
+                Some( character ) => unescaped.push( character ),
+                None => return self.error( &format!( "Unknown entity '&{};'", entity ) )
+            }
+            rest = &rest[ start + end + 1.. ];
+        }
+        unescaped.push_str( rest );
+        Ok( unescaped )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::BTreeRepr,
+        traits::{ AddNode, AddEdge, IterNodeIds, IterEdgeIds }
+    };
+
+    use super::{ Data, Error, GraphMlData, Key, KeyType, Value, read, to_string };
+
+    #[derive( Debug, Clone, Copy, Default, PartialEq )]
+    struct Station {
+        load: f64,
+        active: bool
+    }
+
+    impl GraphMlData for Station {
+        fn keys() -> Vec<Key> {
+            vec![
+                Key::new( "load", KeyType::Double ),
+                Key::new( "active", KeyType::Boolean ).default( Value::Boolean( true ) )
+            ]
+        }
+
+        fn to_data( &self ) -> Data {
+            Data::from( [
+                ( "load".to_string(), Value::Double( self.load ) ),
+                ( "active".to_string(), Value::Boolean( self.active ) )
+            ])
+        }
+
+        fn from_data( data: &Data ) -> Result<Self, Error> {
+            Ok( Self {
+                load: data.get( "load" ).and_then( Value::as_f64 ).unwrap_or_default(),
+                active: data.get( "active" ).and_then( Value::as_bool ).ok_or_else( || Error::Data( "missing active".to_string() ) )?
+            })
+        }
+    }
+
+    type Network = Graph<Directed, Cyclic, BTreeRepr<u32, Station, i32>>;
+
+    #[test]
+    fn test_graphml_round_trip() {
+        let mut graph = Network::default();
+        graph.add_node( 1, Station { load: 0.5, active: true } );
+        graph.add_node( 2, Station { load: 1.25, active: false } );
+        graph.add_node( 3, Station::default() );
+        graph.add_edge( 1, 2, 7 );
+        graph.add_edge( 2, 3, -1 );
+
+        let text = to_string( &graph );
+        assert!( text.contains( "edgedefault=\"directed\"" ) );
+        assert!( text.contains( "<key id=\"d2\" for=\"edge\" attr.name=\"value\" attr.type=\"int\"/>" ) );
+        let loaded: Network = read( &text ).unwrap();
+        assert_eq!( loaded.iter_node_ids().collect::<Vec<_>>(), graph.iter_node_ids().collect::<Vec<_>>() );
+        assert_eq!( loaded.iter_edge_ids().collect::<Vec<_>>(), graph.iter_edge_ids().collect::<Vec<_>>() );
+
+        assert!( matches!( read::<Undirected, Cyclic, BTreeRepr<u32, Station, i32>, _, _, _>( &text ), Err( Error::Direction { .. } ) ) );
+    }
+
+    #[test]
+    fn test_graphml_undirected() {
+        let mut graph = Graph::<Undirected, Cyclic, BTreeRepr<u32, (), f32>>::default();
+        for id in 0..3 {
+            graph.add_node( id, () );
+        }
+        for ( id1, id2, weight ) in [ ( 0, 1, 0.5 ), ( 1, 2, 2.0 ) ] {
+            graph.add_edge( id1, id2, weight );
+            graph.add_edge( id2, id1, weight );
+        }
+        let text = to_string( &graph );
+        assert_eq!( text.matches( "<edge " ).count(), 2 );
+        let loaded: Graph<Undirected, Cyclic, BTreeRepr<u32, (), f32>> = read( &text ).unwrap();
+        assert_eq!( loaded.iter_edge_ids().collect::<Vec<_>>(), graph.iter_edge_ids().collect::<Vec<_>>() );
+    }
+
+    #[test]
+    fn test_graphml_read() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <!-- exported -->
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+              <key id="l" for="node" attr.name="load" attr.type="float"><default>2.5</default></key>
+              <key id="a" for="node" attr.name="active" attr.type="boolean"/>
+              <key id="c" for="node" attr.name="comment" attr.type="string"/>
+              <key id="w" for="edge" attr.name="value" attr.type="int"/>
+              <graph id="G" edgedefault="directed">
+                <node id="1"><data key="a">false</data><data key="c">a &amp; b &#x3C; c</data></node>
+                <node id="2"><data key="l">0.5</data><data key="a">true</data></node>
+                <edge source="1" target="2"><data key="w">3</data></edge>
+              </graph>
+            </graphml>
+        "#;
+        let graph: Network = read( text ).unwrap();
+        assert_eq!( graph.iter_node_ids().collect::<Vec<_>>(), vec![
+            ( 1, &Station { load: 2.5, active: false } ),
+            ( 2, &Station { load: 0.5, active: true } )
+        ]);
+        assert_eq!( graph.iter_edge_ids().collect::<Vec<_>>(), vec![ ( 1, 2, &3 ) ] );
+
+        let missing = text.replace( "<data key=\"a\">false</data>", "" );
+        assert!( matches!( read::<Directed, Cyclic, BTreeRepr<u32, Station, i32>, _, _, _>( &missing ), Err( Error::Data( _ ) ) ) );
+        let invalid = text.replace( ">3<", ">three<" );
+        assert!( matches!( read::<Directed, Cyclic, BTreeRepr<u32, Station, i32>, _, _, _>( &invalid ), Err( Error::InvalidValue { .. } ) ) );
+        let unknown = text.replace( "target=\"2\"", "target=\"9\"" );
+        assert!( matches!( read::<Directed, Cyclic, BTreeRepr<u32, Station, i32>, _, _, _>( &unknown ), Err( Error::UnknownNode( _ ) ) ) );
+    }
+}
@@ -2,15 +2,17 @@
 
 //: Standard
 use std::{
-    cmp::{ Eq, Ord, PartialEq },
-    collections::{ BTreeMap, HashMap },
-    marker::PhantomData,
-    ops::{ Deref, DerefMut, Not }
+    collections::HashMap,
+    hash::Hash
 };
 
 use crate::{
-    graph::Graph,
-    graph_repr::HashRepr,
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::{ HashRepr, NodeRepr },
     traits::{
         GetNode,
         GetNodeMut,
@@ -21,13 +23,15 @@ use crate::{
         AddEdge,
         RemoveEdge,
         ContainsNode,
-        ContainsEdge,
         ClearNodes,
         ClearEdges,
         IterNodes,
         IterNodesMut,
         IterEdges,
         IterEdgesMut,
+        IterNodeIds,
+        IterEdgeIds,
+        IterAdjacent,
         IsComplete,
         IsEmpty,
         IsTrivial,
@@ -44,313 +48,340 @@ use crate::{
     }
 };
 
-impl<I, N, E> GetNode<I, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> GetNode<I, N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn node( &self, id: I ) -> Option<&N> {
-        self.0.data.get( &id ).map( |node| &node.0 )
+        self.0.0.get( &id ).map( |node| &node.node )
     }
 }
 
-impl<I, N, E> GetNodeMut<I, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> GetNodeMut<I, N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn node_mut( &mut self, id: I ) -> Option<&mut N> {
-        self.0.data.get_mut( &id ).map( |node| &mut node.0 )
+        self.0.0.get_mut( &id ).map( |node| &mut node.node )
     }
 }
 
-impl<I, N, E> GetEdge<I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> GetEdge<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn edge( &self, id1: I, id2: I ) -> Option<&E> {
-        self.0.data.get( &id1 ).and_then( |node| node.1.get( &id2 ) )
+        self.0.0.get( &id1 ).and_then( |node| node.adjs.get( &id2 ) )
     }
 }
 
-impl<I, N, E> GetEdgeMut<I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> GetEdgeMut<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn edge_mut( &mut self, id1: I, id2: I ) -> Option<&mut E> {
-        self.0.data.get_mut( &id1 ).and_then( |node| node.1.get_mut( &id2 ) )
+        self.0.0.get_mut( &id1 ).and_then( |node| node.adjs.get_mut( &id2 ) )
     }
 }
 
-impl<I, N, E> AddNode<I, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> AddNode<I, N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn add_node( &mut self, id: I, node: N ) {
-        self.0.data.insert( id, ( node, HashMap::default() ) );
+        self.0.0.insert( id, NodeRepr { node, adjs: HashMap::default() } );
     }
 }
 
-impl<I, N, E> RemoveNode<I, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> RemoveNode<I, N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn remove_node( &mut self, id: I ) -> Option<N> {
-        self.0.data.remove( &id ).map( |node| node.0 )
+        self.0.0.remove( &id ).map( |node| node.node )
     }
 }
 
-impl<I, N, E> AddEdge<I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> AddEdge<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn add_edge( &mut self, id1: I, id2: I, edge: E ) {
-        self.0.data.get_mut( &id1 ).map( |node| node.1.insert( id2, edge ) );
+        if let Some( node ) = self.0.0.get_mut( &id1 ) {
+            node.adjs.insert( id2, edge );
+        }
     }
 }
 
-impl<I, N, E> RemoveEdge<I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> RemoveEdge<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn remove_edge( &mut self, id1: I, id2: I ) -> Option<E> {
-        self.0.data.get_mut( &id1 ).and_then( |node| node.1.remove( &id2 ) )
+        self.0.0.get_mut( &id1 ).and_then( |node| node.adjs.remove( &id2 ) )
     }
 }
 
-impl<I, N, E> ContainsNode<I> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> ClearNodes for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn contains_node( &self, id: I ) -> bool {
-        self.0.data.contains_key( &id )
+    fn clear_nodes( &mut self ) {
+        self.0.0.clear();
     }
 }
 
-impl<I, N, E> ContainsEdge<I> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> ClearEdges for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn contains_edge( &self, id1: I, id2: I ) -> bool {
-        self.0.data.get( &id1 ).is_some_and( |node| node.1.contains_key( &id2 ) )
+    fn clear_edges( &mut self ) {
+        self.0.0.values_mut().for_each( |node| node.adjs.clear() );
     }
 }
 
-impl<I, N, E> ClearNodes for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterNodes<N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn clear_nodes( &mut self ) {
-        self.0.data.clear();
+    fn iter_nodes<'a>( &'a self ) -> impl Iterator<Item = Option<&'a N>>
+    where
+        N: 'a
+    {
+        self.0.0.values().map( |node| Some( &node.node ) )
     }
 }
 
-impl<I, N, E> ClearEdges for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterNodesMut<N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn clear_edges( &mut self ) {
-        self.0.data.iter_mut().for_each( |node| node.1.1.clear() );
+    fn iter_nodes_mut<'a>( &'a mut self ) -> impl Iterator<Item = Option<&'a mut N>>
+    where
+        N: 'a
+    {
+        self.0.0.values_mut().map( |node| Some( &mut node.node ) )
+    }
+}
+
+impl<D, C, I, N, E> IterEdges<I, E> for Graph<D, C, HashRepr<I, N, E>>
+where
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
+{
+    fn iter_edges<'a>( &'a self, id: I ) -> impl Iterator<Item = Option<&'a E>>
+    where
+        E: 'a
+    {
+        self.0.0.get( &id ).into_iter().flat_map( |node| node.adjs.values().map( Some ) )
     }
 }
 
-impl<'a, I, N, E> IterNodes<'a, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterEdgesMut<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: 'a + Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn iter_nodes( &self ) -> impl Iterator<Item = Option<&N>> {
-        self.0.data.iter().map( |node| Some( &node.1.0 ) )
+    fn iter_edges_mut<'a>( &'a mut self, id: I ) -> impl Iterator<Item = Option<&'a mut E>>
+    where
+        E: 'a
+    {
+        self.0.0.get_mut( &id ).into_iter().flat_map( |node| node.adjs.values_mut().map( Some ) )
     }
 }
 
-impl<'a, I, N, E> IterNodesMut<'a, N> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterNodeIds<I, N> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: 'a + Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Clone + Eq + Hash
 {
-    fn iter_nodes_mut( &mut self ) -> impl Iterator<Item = Option<&mut N>> {
-        self.0.data.iter_mut().map( |node| Some( &mut node.1.0 ) )
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, &'a N )>
+    where
+        N: 'a
+    {
+        self.0.0.iter().map( |( id, node )| ( id.clone(), &node.node ) )
     }
 }
 
-impl<'a, I, N, E> IterEdges<'a, I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterEdgeIds<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: 'a + Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Clone + Eq + Hash
 {
-    fn iter_edges( &self, id: I ) -> impl Iterator<Item = Option<&E>> {
-        self.0.data.get( &id ).map( |node| node.1.iter().map( |edge| Some( edge.1 ) ) ).into_iter().flatten()
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, I, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.iter().flat_map( |( id1, node )| node.adjs.iter()
+            .map( move |( id2, edge )| ( id1.clone(), id2.clone(), edge ) )
+        )
     }
 }
 
-impl<'a, I, N, E> IterEdgesMut<'a, I, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IterAdjacent<I, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Ord + std::hash::Hash,
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: 'a + Clone + Copy + Default + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Clone + Eq + Hash
 {
-    fn iter_edges_mut( &mut self, id: I ) -> impl Iterator<Item = Option<&mut E>> {
-        self.0.data.get_mut( &id ).map( |node| node.1.iter_mut().map( |edge| Some( edge.1 ) ) ).into_iter().flatten()
+    fn iter_adjacent<'a>( &'a self, id: I ) -> impl Iterator<Item = ( I, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.get( &id ).into_iter().flat_map( |node| node.adjs.iter().map( |( id, edge )| ( id.clone(), edge ) ) )
     }
 }
 
-impl<I, N, E> IsComplete<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsComplete<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_complete( &self ) -> bool {
-        for ( node, neighbors ) in self.0.data.iter() {
-            if neighbors.adjacencies().len() != self.0.data.len() - 1 {
-                return false;
-            }
-            for neighbor in neighbors.adjacencies().keys() {
-                if !self.0.data.node( neighbor ).is_some_and( |n| n.adjacencies().contains_key( node ) ) {
-                    return false;
-                }
-            }
-        }
-        true
+        self.0.0.iter().all( |( id1, node )| self.0.0.keys()
+            .filter( |id2| *id2 != id1 )
+            .all( |id2| node.adjs.contains_key( id2 ) )
+        )
     }
 }
 
-impl<I, N, E> IsEmpty<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsEmpty<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_empty( &self ) -> bool {
-        self.0.data.values().all( |neighbors| neighbors.adjacencies().is_empty() )
+        self.0.0.values().all( |node| node.adjs.is_empty() )
     }
 }
 
-impl<I, N, E> IsTrivial<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsTrivial<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_trivial( &self ) -> bool {
-        self.0.data.len() == 1 && self.data().values().next().is_some_and( |neighbors| neighbors.adjacencies().is_empty())
+        self.0.0.len() == 1 && self.0.0.values().next().is_some_and( |node| node.adjs.is_empty() )
     }
 }
 
-impl<I, N, E> IsNull<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsNull<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_null( &self ) -> bool {
-        self.0.data.is_empty()
+        self.0.0.is_empty()
     }
 }
 
-impl<I, N, E> IsChildNode<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsChildNode<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_child_node( &self, node_1: I ) -> bool {
-        self.0.data.contains_node( node_1 )
+        self.contains_node( node_1 )
     }
 }
 
-impl<I, N, E> IsSubgraph<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsSubgraph<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
-    fn is_subgraph(&self, subgraph: &Self) -> bool {
-        subgraph.0.data.iter().all( |(node, neighbors)| {
-            self.0.data.get( node ).is_some_and( |graph_node| {
-                neighbors.adjacencies().keys().all( |key| graph_node.adjacencies().contains_key( key ) )
+    fn is_subgraph( &self, subgraph: &Self ) -> bool {
+        subgraph.0.0.iter().all( |( id, node )| {
+            self.0.0.get( id ).is_some_and( |graph_node| {
+                node.adjs.keys().all( |key| graph_node.adjs.contains_key( key ) )
             })
         })
     }
 }
 
-impl<I, N, E> IsProperSubgraph<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsProperSubgraph<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_proper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data != subgraph.0.data && self.is_subgraph( subgraph )
+        self.is_subgraph( subgraph ) && ( self.order() != subgraph.order() || self.size() != subgraph.size() )
     }
 }
 
-impl<I, N, E> IsImproperSubgraph<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsImproperSubgraph<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_improper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data == subgraph.0.data
+        self.is_subgraph( subgraph ) && self.order() == subgraph.order() && self.size() == subgraph.size()
     }
 }
 
-impl<I, N, E> IsSpanningSubgraph<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> IsSpanningSubgraph<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn is_spanning_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data.len() == subgraph.data().len() && self.is_subgraph( subgraph )
+        self.order() == subgraph.order() && self.is_subgraph( subgraph )
     }
 }
 
-impl<I, N, E> AreAdjacentNodes<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> AreAdjacentNodes<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn are_adjacent_nodes( &self, node_1: I, node_2: I ) -> bool {
-        self.is_child_node( node_1.clone() )
-            && self.is_child_node( node_2.clone() )
-            && self.0.data.get( &node_1 ).unwrap().adjacencies().contains_key( &node_2 )
+        self.0.0.contains_key( &node_2 ) && self.0.0.get( &node_1 ).is_some_and( |node| node.adjs.contains_key( &node_2 ) )
     }
 }
 
-impl<I, N, E> AreAdjacentEdges<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> AreAdjacentEdges<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Clone + Eq + Hash
 {
     fn are_adjacent_edges( &self, node_1: I, node_2: I, node_3: I ) -> bool {
         self.are_adjacent_nodes( node_1, node_2.clone() )
@@ -358,24 +389,29 @@ where
     }
 }
 
-impl<I, N, E> Order<I, N, E> for Graph<HashRepr<I, N, E>>
+impl<D, C, I, N, E> Order<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn order( &self ) -> usize {
-        self.0.data.len()
+        self.0.0.len()
     }
 }
 
-impl<I, N, E> Size<I, N, E> for Graph<HashRepr<I, N, E>>
+/// Undirected edges are stored in both directions and count once.
+impl<D, C, I, N, E> Size<I, N, E> for Graph<D, C, HashRepr<I, N, E>>
 where
-    I: Clone + Ord + std::hash::Hash,
-    N: Copy + Default + PartialEq + std::fmt::Debug,
-    E: Copy + Default + PartialEq + std::fmt::Debug
+    D: Directional,
+    C: Cyclical,
+    I: Eq + Hash
 {
     fn size( &self ) -> usize {
-        self.0.data.values().map( |neighbors| neighbors.adjacencies().len() ).sum::<usize>() / 2
+        let edges = self.0.0.values().map( |node| node.adjs.len() ).sum::<usize>();
+        match D::DIRECTED {
+            true => edges,
+            false => ( edges + self.0.0.iter().filter( |( id, node )| node.adjs.contains_key( *id ) ).count() ) / 2
+        }
     }
 }
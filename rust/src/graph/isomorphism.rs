@@ -0,0 +1,451 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::collections::BTreeMap;
+
+use crate::traits::{
+    IterNodeIds,
+    IterEdgeIds
+};
+
+/// Maps the ids of the first graph, or the pattern, to ids of the second graph.
+pub type Mapping<I1, I2> = BTreeMap<I1, I2>;
+
+type NodeMatch<'a, N1, N2> = Box<dyn Fn( &N1, &N2 ) -> bool + 'a>;
+type EdgeMatch<'a, E1, E2> = Box<dyn Fn( &E1, &E2 ) -> bool + 'a>;
+
+/// Finds isomorphisms and subgraph isomorphisms between graphs with the VF2 algorithm.
+///
+/// Graphs are compared by structure alone, so their ids may differ in value and type. Without closures any node may
+/// map to any node and any edge to any edge; with them, mapped nodes and edges must also match. Anything implementing
+/// [`IterNodeIds`] and [`IterEdgeIds`] can be searched, including [`View`](crate::graph::view::View)s. Undirected
+/// graphs store each edge both ways and are matched as such.
+///
+/// A subgraph isomorphism here is a monomorphism: every edge of the pattern must map to an edge of the target, but
+/// the target may have further edges between the mapped nodes.
+///
+/// ```ignore
+/// let motifs = Vf2::new()
+///     .node_match( |pattern: &Kind, node: &Kind| pattern == node )
+///     .subgraph_isomorphisms( &motif, &pipeline );
+/// ```
+///
+pub struct Vf2<'a, N1, N2, E1, E2> {
+    node_match: Option<NodeMatch<'a, N1, N2>>,
+    edge_match: Option<EdgeMatch<'a, E1, E2>>
+}
+
+impl<N1, N2, E1, E2> Default for Vf2<'_, N1, N2, E1, E2> {
+    fn default() -> Self {
+        Self {
+            node_match: None,
+            edge_match: None
+        }
+    }
+}
+
+impl<'a, N1, N2, E1, E2> Vf2<'a, N1, N2, E1, E2> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_match<F>( mut self, node_match: F ) -> Self
+    where
+        F: Fn( &N1, &N2 ) -> bool + 'a
+    {
+        self.node_match = Some( Box::new( node_match ) );
+        self
+    }
+
+    pub fn edge_match<F>( mut self, edge_match: F ) -> Self
+    where
+        F: Fn( &E1, &E2 ) -> bool + 'a
+    {
+        self.edge_match = Some( Box::new( edge_match ) );
+        self
+    }
+
+    pub fn is_isomorphic<G1, G2, I1, I2>( &self, graph1: &G1, graph2: &G2 ) -> bool
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.isomorphism( graph1, graph2 ).is_some()
+    }
+
+    /// An isomorphism from `graph1` to `graph2`, if there is one.
+    pub fn isomorphism<G1, G2, I1, I2>( &self, graph1: &G1, graph2: &G2 ) -> Option<Mapping<I1, I2>>
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.search( graph1, graph2, false, true ).pop()
+    }
+
+    /// Every isomorphism from `graph1` to `graph2`.
+    pub fn isomorphisms<G1, G2, I1, I2>( &self, graph1: &G1, graph2: &G2 ) -> Vec<Mapping<I1, I2>>
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.search( graph1, graph2, false, false )
+    }
+
+    pub fn is_subgraph_isomorphic<G1, G2, I1, I2>( &self, pattern: &G1, target: &G2 ) -> bool
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.subgraph_isomorphism( pattern, target ).is_some()
+    }
+
+    /// A mapping of `pattern` into `target`, if `target` contains it.
+    pub fn subgraph_isomorphism<G1, G2, I1, I2>( &self, pattern: &G1, target: &G2 ) -> Option<Mapping<I1, I2>>
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.search( pattern, target, true, true ).pop()
+    }
+
+    /// Every mapping of `pattern` into `target`. A symmetric pattern is found once per automorphism.
+    pub fn subgraph_isomorphisms<G1, G2, I1, I2>( &self, pattern: &G1, target: &G2 ) -> Vec<Mapping<I1, I2>>
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        self.search( pattern, target, true, false )
+    }
+
+    fn search<G1, G2, I1, I2>( &self, graph1: &G1, graph2: &G2, mono: bool, first: bool ) -> Vec<Mapping<I1, I2>>
+    where
+        G1: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+        G2: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+        I1: Clone + Ord,
+        I2: Clone + Ord
+    {
+        let dense1 = Dense::new( graph1 );
+        let dense2 = Dense::new( graph2 );
+        let fits = match mono {
+            true => dense1.len() <= dense2.len() && dense1.size() <= dense2.size(),
+            false => dense1.len() == dense2.len() && dense1.size() == dense2.size()
+        };
+        let mut mappings = Vec::new();
+        if fits {
+            let mut matcher = Matcher {
+                vf2: self,
+                mono,
+                side1: Side::new( dense1.len() ),
+                side2: Side::new( dense2.len() ),
+                graph1: dense1,
+                graph2: dense2,
+                depth: 0
+            };
+            matcher.extend( &mut |mapping| {
+                mappings.push( mapping );
+                first
+            });
+        }
+        mappings
+    }
+}
+
+/// A graph indexed `0..n` in id order, for constant time lookups during the search.
+struct Dense<'g, I, N, E> {
+    ids: Vec<I>,
+    nodes: Vec<&'g N>,
+    succs: Vec<BTreeMap<usize, &'g E>>,
+    preds: Vec<Vec<usize>>
+}
+
+impl<'g, I, N, E> Dense<'g, I, N, E>
+where
+    I: Clone + Ord
+{
+    fn new<G>( graph: &'g G ) -> Self
+    where
+        G: IterNodeIds<I, N> + IterEdgeIds<I, E>
+    {
+        let mut entries: Vec<_> = graph.iter_node_ids().collect();
+        entries.sort_by( |( id1, _ ), ( id2, _ )| id1.cmp( id2 ) );
+        let index: BTreeMap<_, _> = entries.iter().enumerate().map( |( index, ( id, _ ) )| ( id.clone(), index ) ).collect();
+        let mut succs = vec![ BTreeMap::new(); entries.len() ];
+        let mut preds = vec![ Vec::new(); entries.len() ];
+        for ( id1, id2, edge ) in graph.iter_edge_ids() {
+            if let ( Some( &index1 ), Some( &index2 ) ) = ( index.get( &id1 ), index.get( &id2 ) ) {
+                succs[ index1 ].insert( index2, edge );
+                preds[ index2 ].push( index1 );
+            }
+        }
+        let ( ids, nodes ) = entries.into_iter().unzip();
+        Self { ids, nodes, succs, preds }
+    }
+
+    fn len( &self ) -> usize {
+        self.ids.len()
+    }
+
+    fn size( &self ) -> usize {
+        self.succs.iter().map( BTreeMap::len ).sum()
+    }
+
+    fn edge( &self, index1: usize, index2: usize ) -> Option<&'g E> {
+        self.succs[ index1 ].get( &index2 ).copied()
+    }
+}
+
+/// The search state of one graph.
+///
+/// `ins` and `outs` hold, for every node with an edge into or out of the mapped nodes, the depth at which it gained
+/// it, and `0` otherwise. The unmapped ones among them are the terminal sets of VF2.
+///
+struct Side {
+    core: Vec<Option<usize>>,
+    ins: Vec<usize>,
+    outs: Vec<usize>
+}
+
+impl Side {
+    fn new( len: usize ) -> Self {
+        Self { core: vec![ None; len ], ins: vec![ 0; len ], outs: vec![ 0; len ] }
+    }
+
+    fn terminal<'s>( marks: &'s [ usize ], core: &'s [ Option<usize> ] ) -> impl Iterator<Item = usize> + 's {
+        ( 0..core.len() ).filter( move |&index| core[ index ].is_none() && marks[ index ] > 0 )
+    }
+
+    /// How many of the `neighbors` of `index` are in the terminal set given by `marks`.
+    fn count( neighbors: &[ usize ], index: usize, core: &[ Option<usize> ], marks: &[ usize ] ) -> usize {
+        neighbors.iter().filter( |&&node| node != index && core[ node ].is_none() && marks[ node ] > 0 ).count()
+    }
+
+    /// How many of the `neighbors` of `index` are neither mapped nor in a terminal set.
+    fn untouched( &self, neighbors: &[ usize ], index: usize ) -> usize {
+        neighbors.iter().filter( |&&node| node != index && self.core[ node ].is_none() && self.ins[ node ] == 0 && self.outs[ node ] == 0 ).count()
+    }
+
+    fn add<I, N, E>( &mut self, graph: &Dense<I, N, E>, index: usize, other: usize, depth: usize ) {
+        self.core[ index ] = Some( other );
+        for node in graph.preds[ index ].iter().copied().chain( [ index ] ) {
+            if self.ins[ node ] == 0 {
+                self.ins[ node ] = depth;
+            }
+        }
+        for node in graph.succs[ index ].keys().copied().chain( [ index ] ) {
+            if self.outs[ node ] == 0 {
+                self.outs[ node ] = depth;
+            }
+        }
+    }
+
+    fn remove<I, N, E>( &mut self, graph: &Dense<I, N, E>, index: usize, depth: usize ) {
+        self.core[ index ] = None;
+        let neighbors = graph.succs[ index ].keys().chain( &graph.preds[ index ] );
+        for node in neighbors.copied().chain( [ index ] ) {
+            if self.ins[ node ] == depth {
+                self.ins[ node ] = 0;
+            }
+            if self.outs[ node ] == depth {
+                self.outs[ node ] = 0;
+            }
+        }
+    }
+}
+
+struct Matcher<'m, 'a, 'g1, 'g2, I1, I2, N1, N2, E1, E2> {
+    vf2: &'m Vf2<'a, N1, N2, E1, E2>,
+    mono: bool,
+    graph1: Dense<'g1, I1, N1, E1>,
+    graph2: Dense<'g2, I2, N2, E2>,
+    side1: Side,
+    side2: Side,
+    depth: usize
+}
+
+impl<I1, I2, N1, N2, E1, E2> Matcher<'_, '_, '_, '_, I1, I2, N1, N2, E1, E2>
+where
+    I1: Clone + Ord,
+    I2: Clone + Ord
+{
+    /// Extends the current partial mapping in every feasible way, passing each complete one to `found` until it
+    /// returns `true`. Returns whether the search was stopped.
+    fn extend( &mut self, found: &mut dyn FnMut( Mapping<I1, I2> ) -> bool ) -> bool {
+        if self.depth == self.graph1.len() {
+            let mapping = self.side1.core.iter().enumerate()
+                .map( |( index1, index2 )| ( self.graph1.ids[ index1 ].clone(), self.graph2.ids[ index2.expect( "Complete mapping has a gap" ) ].clone() ) )
+                .collect();
+            return found( mapping );
+        }
+        let ( index1, candidates ) = self.candidates();
+        for index2 in candidates {
+            if !self.is_feasible( index1, index2 ) {
+                continue;
+            }
+            self.depth += 1;
+            self.side1.add( &self.graph1, index1, index2, self.depth );
+            self.side2.add( &self.graph2, index2, index1, self.depth );
+            let stopped = self.extend( found );
+            self.side1.remove( &self.graph1, index1, self.depth );
+            self.side2.remove( &self.graph2, index2, self.depth );
+            self.depth -= 1;
+            if stopped {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The next node of the first graph to map, and the nodes of the second it may map to.
+    ///
+    /// Nodes joined to the mapped ones by outgoing edges come first, then those joined by incoming edges, then any.
+    ///
+    fn candidates( &self ) -> ( usize, Vec<usize> ) {
+        let ( side1, side2 ) = ( &self.side1, &self.side2 );
+        if let Some( index1 ) = Side::terminal( &side1.outs, &side1.core ).next() {
+            return ( index1, Side::terminal( &side2.outs, &side2.core ).collect() );
+        }
+        if let Some( index1 ) = Side::terminal( &side1.ins, &side1.core ).next() {
+            return ( index1, Side::terminal( &side2.ins, &side2.core ).collect() );
+        }
+        let index1 = side1.core.iter().position( Option::is_none ).expect( "Incomplete mapping has no unmapped node" );
+        ( index1, ( 0..self.graph2.len() ).filter( |&index2| side2.core[ index2 ].is_none() ).collect() )
+    }
+
+    fn edges_match( &self, edge1: &E1, edge2: &E2 ) -> bool {
+        self.vf2.edge_match.as_ref().is_none_or( |edge_match| edge_match( edge1, edge2 ) )
+    }
+
+    fn is_feasible( &self, index1: usize, index2: usize ) -> bool {
+        let ( graph1, graph2 ) = ( &self.graph1, &self.graph2 );
+        if let Some( node_match ) = &self.vf2.node_match {
+            if !node_match( graph1.nodes[ index1 ], graph2.nodes[ index2 ] ) {
+                return false;
+            }
+        }
+
+        // Edges to mapped nodes, including self-loops, must be preserved.
+        let mapped = |index: usize| if index == index1 { Some( index2 ) } else { self.side1.core[ index ] };
+        for ( &next1, edge1 ) in &graph1.succs[ index1 ] {
+            if let Some( next2 ) = mapped( next1 ) {
+                if !graph2.edge( index2, next2 ).is_some_and( |edge2| self.edges_match( edge1, edge2 ) ) {
+                    return false;
+                }
+            }
+        }
+        for &previous1 in &graph1.preds[ index1 ] {
+            if let Some( previous2 ) = mapped( previous1 ) {
+                let edge1 = graph1.edge( previous1, index1 ).expect( "Predecessor has no edge" );
+                if !graph2.edge( previous2, index2 ).is_some_and( |edge2| self.edges_match( edge1, edge2 ) ) {
+                    return false;
+                }
+            }
+        }
+        // And for an isomorphism, no edges may be gained.
+        if !self.mono {
+            let mapped = |index: usize| if index == index2 { Some( index1 ) } else { self.side2.core[ index ] };
+            if graph2.succs[ index2 ].keys().any( |&next2| mapped( next2 ).is_some_and( |next1| graph1.edge( index1, next1 ).is_none() ) )
+                || graph2.preds[ index2 ].iter().any( |&previous2| mapped( previous2 ).is_some_and( |previous1| graph1.edge( previous1, index1 ).is_none() ) )
+            {
+                return false;
+            }
+        }
+
+        // Look ahead: the first graph must not need more terminal neighbours than the second has, nor, for an
+        // isomorphism, differ in how many untouched neighbours it has.
+        let neighbors1 = [ graph1.succs[ index1 ].keys().copied().collect(), graph1.preds[ index1 ].clone() ];
+        let neighbors2 = [ graph2.succs[ index2 ].keys().copied().collect(), graph2.preds[ index2 ].clone() ];
+        for ( neighbors1, neighbors2 ) in neighbors1.iter().zip( neighbors2.iter() ) {
+            let terminal1 = |marks: &[ usize ]| Side::count( neighbors1, index1, &self.side1.core, marks );
+            let terminal2 = |marks: &[ usize ]| Side::count( neighbors2, index2, &self.side2.core, marks );
+            for ( count1, count2 ) in [
+                ( terminal1( &self.side1.ins ), terminal2( &self.side2.ins ) ),
+                ( terminal1( &self.side1.outs ), terminal2( &self.side2.outs ) )
+            ] {
+                if count1 > count2 || ( !self.mono && count1 != count2 ) {
+                    return false;
+                }
+            }
+            if !self.mono && self.side1.untouched( neighbors1, index1 ) != self.side2.untouched( neighbors2, index2 ) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic, generators },
+        graph_repr::BTreeRepr,
+        traits::{ AddNode, AddEdge, GetEdge }
+    };
+
+    use super::Vf2;
+
+    type Labelled<I> = Graph<Directed, Cyclic, BTreeRepr<I, char, u8>>;
+
+    fn labelled<I>( nodes: &[ ( I, char ) ], edges: &[ ( I, I, u8 ) ] ) -> Labelled<I>
+    where
+        I: Clone + Ord
+    {
+        let mut graph = Labelled::default();
+        for ( id, node ) in nodes {
+            graph.add_node( id.clone(), *node );
+        }
+        for ( id1, id2, edge ) in edges {
+            graph.add_edge( id1.clone(), id2.clone(), *edge );
+        }
+        graph
+    }
+
+    #[test]
+    fn test_isomorphism() {
+        let cycle = labelled( &[ ( 1, 'x' ), ( 2, 'y' ), ( 3, 'y' ) ], &[ ( 1, 2, 5 ), ( 2, 3, 6 ), ( 3, 1, 7 ) ] );
+        let renamed = labelled( &[ ( "a", 'y' ), ( "b", 'x' ), ( "c", 'y' ) ], &[ ( "b", "c", 5 ), ( "c", "a", 6 ), ( "a", "b", 7 ) ] );
+        let path = labelled( &[ ( "a", 'y' ), ( "b", 'x' ), ( "c", 'y' ) ], &[ ( "b", "c", 5 ), ( "c", "a", 6 ) ] );
+
+        assert_eq!( Vf2::new().isomorphisms( &cycle, &renamed ).len(), 3 );
+        assert!( !Vf2::new().is_isomorphic( &cycle, &path ) );
+
+        let matching = Vf2::new()
+            .node_match( |node1: &char, node2: &char| node1 == node2 )
+            .edge_match( |edge1: &u8, edge2: &u8| edge1 == edge2 );
+        let mapping = matching.isomorphism( &cycle, &renamed ).unwrap();
+        assert_eq!( mapping.into_iter().collect::<Vec<_>>(), vec![ ( 1, "b" ), ( 2, "c" ), ( 3, "a" ) ] );
+        assert_eq!( matching.isomorphisms( &cycle, &renamed ).len(), 1 );
+
+        let reversed = labelled( &[ ( 1, 'x' ), ( 2, 'y' ), ( 3, 'y' ) ], &[ ( 2, 1, 5 ), ( 3, 2, 6 ), ( 1, 3, 7 ) ] );
+        assert!( !matching.is_isomorphic( &cycle, &reversed ) );
+        assert!( matching.is_isomorphic( &cycle.reversed(), &reversed ) );
+    }
+
+    #[test]
+    fn test_subgraph_isomorphism() {
+        type Simple = Graph<Undirected, Cyclic, BTreeRepr<usize, (), ()>>;
+        let triangle: Simple = generators::complete::<_, _, _, (), ()>( 3 );
+        let path: Simple = generators::path::<_, _, _, (), ()>( 3 );
+        let k4: Simple = generators::complete::<_, _, _, (), ()>( 4 );
+        let square: Simple = generators::cycle::<_, _, _, (), ()>( 4 );
+
+        assert_eq!( Vf2::new().subgraph_isomorphisms( &triangle, &k4 ).len(), 24 );
+        // Monomorphisms need not be induced: the path fits into the triangle, the triangle not into the square.
+        assert_eq!( Vf2::new().subgraph_isomorphisms( &path, &triangle ).len(), 6 );
+        assert!( !Vf2::new().is_subgraph_isomorphic( &triangle, &square ) );
+        assert!( !Vf2::new().is_subgraph_isomorphic( &k4, &triangle ) );
+
+        let motif = Vf2::new().subgraph_isomorphism( &path, &square ).unwrap();
+        assert!( square.edge( motif[ &0 ], motif[ &1 ] ).is_some() && square.edge( motif[ &1 ], motif[ &2 ] ).is_some() );
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::BTreeSet,
+    fmt::{ self, Debug, Display },
+    io::{ self, Write }
+};
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical,
+        quote
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+/// A JSON value, as written by [`NodeLink`].
+#[derive( Debug, Clone, PartialEq )]
+pub enum Json {
+    Null,
+    Bool( bool ),
+    Number( f64 ),
+    String( String ),
+    Array( Vec<Json> ),
+    Object( Fields )
+}
+
+/// The `( key, value )` members of a JSON object, written in order.
+pub type Fields = Vec<( String, Json )>;
+
+macro_rules! impl_json_number {
+    ( $( $type:ty ),* ) => {
+        $(
+            impl From<$type> for Json {
+                fn from( value: $type ) -> Self {
+                    Json::Number( value as f64 )
+                }
+            }
+        )*
+    };
+}
+
+impl_json_number!( i8, i16, i32, i64, u8, u16, u32, u64, usize, f32, f64 );
+
+impl From<bool> for Json {
+    fn from( value: bool ) -> Self {
+        Json::Bool( value )
+    }
+}
+
+impl From<&str> for Json {
+    fn from( value: &str ) -> Self {
+        Json::String( value.to_string() )
+    }
+}
+
+impl From<String> for Json {
+    fn from( value: String ) -> Self {
+        Json::String( value )
+    }
+}
+
+impl<T> From<Option<T>> for Json
+where
+    T: Into<Json>
+{
+    fn from( value: Option<T> ) -> Self {
+        value.map_or( Json::Null, Into::into )
+    }
+}
+
+impl<T> From<Vec<T>> for Json
+where
+    T: Into<Json>
+{
+    fn from( value: Vec<T> ) -> Self {
+        Json::Array( value.into_iter().map( Into::into ).collect() )
+    }
+}
+
+impl Display for Json {
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+        match self {
+            Json::Null => write!( f, "null" ),
+            Json::Bool( value ) => write!( f, "{}", value ),
+            // JSON has no NaN or infinities.
+            Json::Number( value ) if !value.is_finite() => write!( f, "null" ),
+            Json::Number( value ) => write!( f, "{}", value ),
+            Json::String( value ) => write!( f, "{}", quote::json( value ) ),
+            Json::Array( values ) => {
+                write!( f, "[" )?;
+                for ( index, value ) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!( f, "," )?;
+                    }
+                    write!( f, "{}", value )?;
+                }
+                write!( f, "]" )
+            },
+            Json::Object( fields ) => {
+                write!( f, "{{" )?;
+                for ( index, ( key, value ) ) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!( f, "," )?;
+                    }
+                    write!( f, "{}:{}", quote::json( key ), value )?;
+                }
+                write!( f, "}}" )
+            }
+        }
+    }
+}
+
+/// The layout of the document written by [`NodeLink`].
+#[derive( Debug, Clone, Copy, Default, PartialEq, Eq )]
+pub enum Format {
+    /// `{ "directed", "multigraph", "graph", "nodes": [ { "id", .. } ], "links": [ { "source", "target", .. } ] }`,
+    /// as read by d3-force and NetworkX.
+    #[default]
+    NodeLink,
+    /// `{ "elements": { "nodes": [ { "data": { "id", .. } } ], "edges": [ { "data": { "source", "target", .. } } ] } }`,
+    /// as read by Cytoscape.js.
+    Cytoscape
+}
+
+type NodeCallback<'a, I, N> = Box<dyn Fn( &I, &N ) -> Fields + 'a>;
+type EdgeCallback<'a, I, E> = Box<dyn Fn( &I, &I, &E ) -> Fields + 'a>;
+
+/// Writes a [`Graph`] as a node-link JSON document for web viewers.
+///
+/// Ids are written as strings, which every viewer accepts. Undirected edges are written once. Without callbacks
+/// nodes and edges carry a `label` with the `Debug` output of their data; with a callback, the fields it returns
+/// replace the label.
+///
+/// ```ignore
+/// NodeLink::new()
+///     .format( Format::Cytoscape )
+///     .node_data( |_, node| vec![ ( "weight".into(), node.weight.into() ) ] )
+///     .write( &graph, File::create( "graph.json" )? )?;
+/// ```
+///
+pub struct NodeLink<'a, I, N, E> {
+    format: Format,
+    graph_data: Fields,
+    node_data: Option<NodeCallback<'a, I, N>>,
+    edge_data: Option<EdgeCallback<'a, I, E>>
+}
+
+impl<I, N, E> Default for NodeLink<'_, I, N, E> {
+    fn default() -> Self {
+        Self {
+            format: Format::default(),
+            graph_data: Fields::new(),
+            node_data: None,
+            edge_data: None
+        }
+    }
+}
+
+impl<'a, I, N, E> NodeLink<'a, I, N, E>
+where
+    I: Clone + Ord + Display,
+    N: Debug,
+    E: Debug
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format( mut self, format: Format ) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Adds a graph-level field, written to `graph` for [`Format::NodeLink`] and to `data` for [`Format::Cytoscape`].
+    pub fn graph_data<K, V>( mut self, key: K, value: V ) -> Self
+    where
+        K: Into<String>,
+        V: Into<Json>
+    {
+        self.graph_data.push( ( key.into(), value.into() ) );
+        self
+    }
+
+    pub fn node_data<F>( mut self, data: F ) -> Self
+    where
+        F: Fn( &I, &N ) -> Fields + 'a
+    {
+        self.node_data = Some( Box::new( data ) );
+        self
+    }
+
+    pub fn edge_data<F>( mut self, data: F ) -> Self
+    where
+        F: Fn( &I, &I, &E ) -> Fields + 'a
+    {
+        self.edge_data = Some( Box::new( data ) );
+        self
+    }
+
+    /// The document as a [`Json`] value.
+    pub fn to_value<D, C, R>( &self, graph: &Graph<D, C, R> ) -> Json
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>
+    {
+        let nodes = graph.iter_node_ids()
+            .map( |( id, node )| {
+                let mut fields = vec![ ( "id".to_string(), Json::String( id.to_string() ) ) ];
+                fields.extend( match &self.node_data {
+                    Some( data ) => data( &id, node ),
+                    None => vec![ ( "label".to_string(), Json::String( format!( "{:?}", node ) ) ) ]
+                });
+                fields
+            })
+            .collect();
+
+        let mut written = BTreeSet::new();
+        let mut edges = Vec::new();
+        for ( id1, id2, edge ) in graph.iter_edge_ids() {
+            if !D::DIRECTED {
+                if written.contains( &( id2.clone(), id1.clone() ) ) {
+                    continue;
+                }
+                written.insert( ( id1.clone(), id2.clone() ) );
+            }
+            let mut fields = vec![
+                ( "source".to_string(), Json::String( id1.to_string() ) ),
+                ( "target".to_string(), Json::String( id2.to_string() ) )
+            ];
+            fields.extend( match &self.edge_data {
+                Some( data ) => data( &id1, &id2, edge ),
+                None => vec![ ( "label".to_string(), Json::String( format!( "{:?}", edge ) ) ) ]
+            });
+            edges.push( fields );
+        }
+
+        document( self.format, D::DIRECTED, self.graph_data.clone(), nodes, edges )
+    }
+
+    pub fn write<D, C, R, W>( &self, graph: &Graph<D, C, R>, mut writer: W ) -> io::Result<()>
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+        W: Write
+    {
+        writeln!( writer, "{}", self.to_value( graph ) )
+    }
+
+    pub fn to_json<D, C, R>( &self, graph: &Graph<D, C, R> ) -> String
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>
+    {
+        self.to_value( graph ).to_string()
+    }
+}
+
+/// Assembles the document from the fields of the graph, its nodes and its edges.
+pub(crate) fn document( format: Format, directed: bool, graph: Fields, nodes: Vec<Fields>, edges: Vec<Fields> ) -> Json {
+    match format {
+        Format::NodeLink => Json::Object( vec![
+            ( "directed".to_string(), Json::Bool( directed ) ),
+            ( "multigraph".to_string(), Json::Bool( false ) ),
+            ( "graph".to_string(), Json::Object( graph ) ),
+            ( "nodes".to_string(), Json::Array( nodes.into_iter().map( Json::Object ).collect() ) ),
+            ( "links".to_string(), Json::Array( edges.into_iter().map( Json::Object ).collect() ) )
+        ]),
+        Format::Cytoscape => {
+            let wrap = |fields| Json::Object( vec![ ( "data".to_string(), Json::Object( fields ) ) ] );
+            let mut data = vec![ ( "directed".to_string(), Json::Bool( directed ) ) ];
+            data.extend( graph );
+            Json::Object( vec![
+                ( "data".to_string(), Json::Object( data ) ),
+                ( "elements".to_string(), Json::Object( vec![
+                    ( "nodes".to_string(), Json::Array( nodes.into_iter().map( wrap ).collect() ) ),
+                    ( "edges".to_string(), Json::Array( edges.into_iter().map( wrap ).collect() ) )
+                ]))
+            ])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ BTreeRepr, NodeRepr }
+    };
+
+    use super::{ Format, NodeLink };
+
+    fn repr( edges: &[ ( u32, u32 ) ] ) -> BTreeRepr<u32, i32, f64> {
+        let mut nodes = BTreeMap::new();
+        for ( id1, id2 ) in edges {
+            for id in [ id1, id2 ] {
+                nodes.entry( *id ).or_insert_with( || NodeRepr { node: *id as i32 * 10, adjs: BTreeMap::new() } );
+            }
+            nodes.get_mut( id1 ).unwrap().adjs.insert( *id2, 0.5 );
+        }
+        BTreeRepr( nodes )
+    }
+
+    #[test]
+    fn test_node_link() {
+        let graph: Graph<Directed, Cyclic, _> = Graph( repr( &[ ( 1, 2 ) ] ), Default::default() );
+        let json = NodeLink::new()
+            .graph_data( "name", "say \"hi\"\n" )
+            .node_data( |_, node: &i32| vec![ ( "weight".into(), ( *node ).into() ) ] )
+            .edge_data( |_, _, edge: &f64| vec![ ( "weight".into(), ( *edge ).into() ) ] )
+            .to_json( &graph );
+        assert_eq!( json, concat!(
+            r#"{"directed":true,"multigraph":false,"graph":{"name":"say \"hi\"\n"},"#,
+            r#""nodes":[{"id":"1","weight":10},{"id":"2","weight":20}],"#,
+            r#""links":[{"source":"1","target":"2","weight":0.5}]}"#
+        ));
+    }
+
+    #[test]
+    fn test_cytoscape() {
+        let graph: Graph<Undirected, Cyclic, _> = Graph( repr( &[ ( 1, 2 ), ( 2, 1 ) ] ), Default::default() );
+        let json = NodeLink::new().format( Format::Cytoscape ).to_json( &graph );
+        assert_eq!( json, concat!(
+            r#"{"data":{"directed":false},"elements":{"#,
+            r#""nodes":[{"data":{"id":"1","label":"10"}},{"data":{"id":"2","label":"20"}}],"#,
+            r#""edges":[{"data":{"source":"1","target":"2","label":"0.5"}}]}}"#
+        ));
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::{ BTreeMap, BTreeSet },
+    fmt::{ Debug, Display },
+    io::{ self, Write }
+};
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical,
+        quote
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+type LabelCallback<'a, I, N> = Box<dyn Fn( &I, &N ) -> String + 'a>;
+type EdgeLabelCallback<'a, I, E> = Box<dyn Fn( &I, &I, &E ) -> Option<String> + 'a>;
+type ClassCallback<'a, I, N> = Box<dyn Fn( &I, &N ) -> Option<String> + 'a>;
+type StyleCallback<'a, I, E> = Box<dyn Fn( &I, &I, &E ) -> Option<String> + 'a>;
+
+/// Writes a [`Graph`] as a Mermaid flowchart.
+///
+/// Mermaid only accepts plain identifiers, so nodes are named `n0`, `n1`, ... in iteration order and their ids are
+/// shown in the label. Directed edges are written as `-->`, undirected edges as `---`, each edge once. Without
+/// callbacks nodes are labelled with their id and edges are unlabelled.
+///
+/// ```ignore
+/// Mermaid::new()
+///     .direction( "LR" )
+///     .class_definition( "input", "fill:#cfc" )
+///     .node_class( |id, _| ( *id < 4 ).then( || "input".to_string() ) )
+///     .write( &graph, File::create( "graph.mmd" )? )?;
+/// ```
+///
+pub struct Mermaid<'a, I, N, E> {
+    direction: String,
+    class_definitions: Vec<( String, String )>,
+    node_label: Option<LabelCallback<'a, I, N>>,
+    edge_label: Option<EdgeLabelCallback<'a, I, E>>,
+    node_class: Option<ClassCallback<'a, I, N>>,
+    edge_style: Option<StyleCallback<'a, I, E>>
+}
+
+impl<I, N, E> Default for Mermaid<'_, I, N, E> {
+    fn default() -> Self {
+        Self {
+            direction: "TD".to_string(),
+            class_definitions: Vec::new(),
+            node_label: None,
+            edge_label: None,
+            node_class: None,
+            edge_style: None
+        }
+    }
+}
+
+impl<'a, I, N, E> Mermaid<'a, I, N, E>
+where
+    I: Clone + Ord + Display,
+    N: Debug,
+    E: Debug
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The flowchart orientation, one of `TD`, `TB`, `BT`, `LR` or `RL`.
+    pub fn direction<S>( mut self, direction: S ) -> Self
+    where
+        S: Into<String>
+    {
+        self.direction = direction.into();
+        self
+    }
+
+    /// Declares a `classDef`, e.g. `( "input", "fill:#cfc,stroke:#333" )`, for use by [`Mermaid::node_class`].
+    pub fn class_definition<K, V>( mut self, name: K, style: V ) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        self.class_definitions.push( ( name.into(), style.into() ) );
+        self
+    }
+
+    pub fn node_label<F>( mut self, label: F ) -> Self
+    where
+        F: Fn( &I, &N ) -> String + 'a
+    {
+        self.node_label = Some( Box::new( label ) );
+        self
+    }
+
+    pub fn edge_label<F>( mut self, label: F ) -> Self
+    where
+        F: Fn( &I, &I, &E ) -> Option<String> + 'a
+    {
+        self.edge_label = Some( Box::new( label ) );
+        self
+    }
+
+    pub fn node_class<F>( mut self, class: F ) -> Self
+    where
+        F: Fn( &I, &N ) -> Option<String> + 'a
+    {
+        self.node_class = Some( Box::new( class ) );
+        self
+    }
+
+    /// Styles edges with a `linkStyle`, e.g. `stroke:green,stroke-width:2px`.
+    pub fn edge_style<F>( mut self, style: F ) -> Self
+    where
+        F: Fn( &I, &I, &E ) -> Option<String> + 'a
+    {
+        self.edge_style = Some( Box::new( style ) );
+        self
+    }
+
+    pub fn write<D, C, R, W>( &self, graph: &Graph<D, C, R>, mut writer: W ) -> io::Result<()>
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+        W: Write
+    {
+        writeln!( writer, "flowchart {}", self.direction )?;
+        for ( name, style ) in &self.class_definitions {
+            writeln!( writer, "    classDef {} {}", name, style )?;
+        }
+
+        let mut names = BTreeMap::new();
+        let mut classes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for ( index, ( id, node ) ) in graph.iter_node_ids().enumerate() {
+            let name = format!( "n{}", index );
+            let label = match &self.node_label {
+                Some( label ) => label( &id, node ),
+                None => id.to_string()
+            };
+            writeln!( writer, "    {}[{}]", name, quote::mermaid( &label ) )?;
+            if let Some( class ) = self.node_class.as_ref().and_then( |class| class( &id, node ) ) {
+                classes.entry( class ).or_default().push( name.clone() );
+            }
+            names.insert( id, name );
+        }
+
+        let connector = if D::DIRECTED { "-->" } else { "---" };
+        let mut written = BTreeSet::new();
+        let mut styles = Vec::new();
+        for ( id1, id2, edge ) in graph.iter_edge_ids() {
+            if !D::DIRECTED {
+                if written.contains( &( id2.clone(), id1.clone() ) ) {
+                    continue;
+                }
+                written.insert( ( id1.clone(), id2.clone() ) );
+            }
+            let label = self.edge_label.as_ref().and_then( |label| label( &id1, &id2, edge ) );
+            write_edge( &mut writer, &names[ &id1 ], connector, label.as_deref(), &names[ &id2 ] )?;
+            styles.push( self.edge_style.as_ref().and_then( |style| style( &id1, &id2, edge ) ) );
+        }
+
+        write_classes( &mut writer, &classes )?;
+        write_link_styles( &mut writer, &styles )
+    }
+
+    pub fn to_mermaid<D, C, R>( &self, graph: &Graph<D, C, R> ) -> String
+    where
+        D: Directional,
+        C: Cyclical,
+        R: GraphRepr,
+        Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>
+    {
+        let mut mermaid = Vec::new();
+        self.write( graph, &mut mermaid ).expect( "Failed to write to memory" );
+        String::from_utf8( mermaid ).expect( "Mermaid output is not valid UTF-8" )
+    }
+}
+
+pub(crate) fn write_edge<W>( writer: &mut W, from: &str, connector: &str, label: Option<&str>, to: &str ) -> io::Result<()>
+where
+    W: Write
+{
+    match label {
+        Some( label ) => writeln!( writer, "    {} {}|{}| {}", from, connector, quote::mermaid( label ), to ),
+        None => writeln!( writer, "    {} {} {}", from, connector, to )
+    }
+}
+
+pub(crate) fn write_classes<W>( writer: &mut W, classes: &BTreeMap<String, Vec<String>> ) -> io::Result<()>
+where
+    W: Write
+{
+    for ( class, names ) in classes {
+        writeln!( writer, "    class {} {}", names.join( "," ), class )?;
+    }
+    Ok( () )
+}
+
+/// Writes `linkStyle` lines for the styled edges. Mermaid numbers links in the order they were declared.
+pub(crate) fn write_link_styles<W>( writer: &mut W, styles: &[ Option<String> ] ) -> io::Result<()>
+where
+    W: Write
+{
+    for ( index, style ) in styles.iter().enumerate() {
+        if let Some( style ) = style {
+            writeln!( writer, "    linkStyle {} {}", index, style )?;
+        }
+    }
+    Ok( () )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ BTreeRepr, NodeRepr }
+    };
+
+    use super::Mermaid;
+
+    fn repr( edges: &[ ( u32, u32 ) ] ) -> BTreeRepr<u32, i32, i32> {
+        let mut nodes = BTreeMap::new();
+        for ( id1, id2 ) in edges {
+            for id in [ id1, id2 ] {
+                nodes.entry( *id ).or_insert_with( || NodeRepr { node: *id as i32 * 10, adjs: BTreeMap::new() } );
+            }
+            nodes.get_mut( id1 ).unwrap().adjs.insert( *id2, *id1 as i32 );
+        }
+        BTreeRepr( nodes )
+    }
+
+    #[test]
+    fn test_mermaid_directed() {
+        let graph: Graph<Directed, Cyclic, _> = Graph( repr( &[ ( 1, 2 ), ( 2, 3 ) ] ), Default::default() );
+        let mermaid = Mermaid::new()
+            .direction( "LR" )
+            .class_definition( "even", "fill:#cfc" )
+            .node_label( |id, node| format!( "\"{}\"\n{}", id, node ) )
+            .edge_label( |_, _, edge| ( *edge == 1 ).then( || "one".to_string() ) )
+            .node_class( |_, node| ( node % 20 == 0 ).then( || "even".to_string() ) )
+            .edge_style( |id1, _, _| ( *id1 == 2 ).then( || "stroke:red".to_string() ) )
+            .to_mermaid( &graph );
+        assert_eq!( mermaid, concat!(
+            "flowchart LR\n",
+            "    classDef even fill:#cfc\n",
+            "    n0[\"#quot;1#quot;<br/>10\"]\n",
+            "    n1[\"#quot;2#quot;<br/>20\"]\n",
+            "    n2[\"#quot;3#quot;<br/>30\"]\n",
+            "    n0 -->|\"one\"| n1\n",
+            "    n1 --> n2\n",
+            "    class n1 even\n",
+            "    linkStyle 1 stroke:red\n"
+        ));
+    }
+
+    #[test]
+    fn test_mermaid_undirected() {
+        let graph: Graph<Undirected, Cyclic, _> = Graph( repr( &[ ( 1, 2 ), ( 2, 1 ) ] ), Default::default() );
+        assert_eq!( Mermaid::new().to_mermaid( &graph ), concat!(
+            "flowchart TD\n",
+            "    n0[\"1\"]\n",
+            "    n1[\"2\"]\n",
+            "    n0 --- n1\n"
+        ));
+    }
+}
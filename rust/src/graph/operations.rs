@@ -0,0 +1,303 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! Set operations building new graphs from graphs that share an id space.
+//!
+//! Where both graphs hold a node or an edge, the result takes its data from the first graph. Undirected graphs store
+//! every edge in both directions, so the operations keep them symmetric without special cases.
+//!
+
+use std::{
+    collections::BTreeSet,
+    fmt::Debug
+};
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Remapped id {0} is used by both graphs")]
+    Collision( String )
+}
+
+fn node_ids<G, I, N>( graph: &G ) -> BTreeSet<I>
+where
+    G: IterNodeIds<I, N>,
+    I: Ord
+{
+    graph.iter_node_ids().map( |( id, _ )| id ).collect()
+}
+
+fn edge_ids<G, I, E>( graph: &G ) -> BTreeSet<( I, I )>
+where
+    G: IterEdgeIds<I, E>,
+    I: Ord
+{
+    graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect()
+}
+
+/// Every node and edge of either graph.
+pub fn union<D, C, R, I, N, E>( graph1: &Graph<D, C, R>, graph2: &Graph<D, C, R> ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Clone,
+    E: Clone,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E> + IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    // Adding the first graph last lets its data win.
+    let mut graph = Graph::default();
+    for ( id, node ) in graph2.iter_node_ids().chain( graph1.iter_node_ids() ) {
+        graph.add_node( id, node.clone() );
+    }
+    for ( id1, id2, edge ) in graph2.iter_edge_ids().chain( graph1.iter_edge_ids() ) {
+        graph.add_edge( id1, id2, edge.clone() );
+    }
+    graph
+}
+
+/// The nodes and edges both graphs have.
+pub fn intersection<D, C, R, I, N, E>( graph1: &Graph<D, C, R>, graph2: &Graph<D, C, R> ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    N: Clone,
+    E: Clone,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E> + IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    let nodes = node_ids( graph2 );
+    let edges = edge_ids( graph2 );
+    let mut graph = Graph::default();
+    for ( id, node ) in graph1.iter_node_ids().filter( |( id, _ )| nodes.contains( id ) ) {
+        graph.add_node( id, node.clone() );
+    }
+    for ( id1, id2, edge ) in graph1.iter_edge_ids() {
+        if edges.contains( &( id1.clone(), id2.clone() ) ) {
+            graph.add_edge( id1, id2, edge.clone() );
+        }
+    }
+    graph
+}
+
+/// The nodes of the first graph, with the edges it has and the second graph lacks.
+pub fn difference<D, C, R, I, N, E>( graph1: &Graph<D, C, R>, graph2: &Graph<D, C, R> ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    N: Clone,
+    E: Clone,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E> + IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    let edges = edge_ids( graph2 );
+    let mut graph = Graph::default();
+    for ( id, node ) in graph1.iter_node_ids() {
+        graph.add_node( id, node.clone() );
+    }
+    for ( id1, id2, edge ) in graph1.iter_edge_ids() {
+        if !edges.contains( &( id1.clone(), id2.clone() ) ) {
+            graph.add_edge( id1, id2, edge.clone() );
+        }
+    }
+    graph
+}
+
+/// The nodes of either graph, with the edges exactly one of them has.
+pub fn symmetric_difference<D, C, R, I, N, E>( graph1: &Graph<D, C, R>, graph2: &Graph<D, C, R> ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    N: Clone,
+    E: Clone,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E> + IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    let edges1 = edge_ids( graph1 );
+    let edges2 = edge_ids( graph2 );
+    let mut graph = Graph::default();
+    for ( id, node ) in graph2.iter_node_ids().chain( graph1.iter_node_ids() ) {
+        graph.add_node( id, node.clone() );
+    }
+    let only1 = graph1.iter_edge_ids().filter( |( id1, id2, _ )| !edges2.contains( &( id1.clone(), id2.clone() ) ) );
+    let only2 = graph2.iter_edge_ids().filter( |( id1, id2, _ )| !edges1.contains( &( id1.clone(), id2.clone() ) ) );
+    for ( id1, id2, edge ) in only1.chain( only2 ) {
+        graph.add_edge( id1, id2, edge.clone() );
+    }
+    graph
+}
+
+/// The same nodes, joined exactly where the graph has no edge. The new edges hold `E::default()` and no self-loops
+/// are added.
+pub fn complement<D, C, R, I, N, E>( graph: &Graph<D, C, R> ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    N: Clone,
+    E: Default,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E> + IterNodeIds<I, N> + IterEdgeIds<I, E>
+{
+    let edges = edge_ids( graph );
+    let mut flipped = Graph::default();
+    let mut ids = Vec::new();
+    for ( id, node ) in graph.iter_node_ids() {
+        flipped.add_node( id.clone(), node.clone() );
+        ids.push( id );
+    }
+    for id1 in &ids {
+        for id2 in &ids {
+            if id1 != id2 && !edges.contains( &( id1.clone(), id2.clone() ) ) {
+                flipped.add_edge( id1.clone(), id2.clone(), E::default() );
+            }
+        }
+    }
+    flipped
+}
+
+/// Both graphs side by side, with the ids of each mapped into a common id space.
+///
+/// Fails if the mappings send nodes of the two graphs to the same id.
+///
+/// ```ignore
+/// let both: Graph<Directed, Cyclic, BTreeRepr<( u8, u32 ), _, _>> = disjoint_union( &a, &b, |id| ( 0, id ), |id| ( 1, id ) )?;
+/// ```
+///
+pub fn disjoint_union<D, C, R1, R2, R, I, J, K, N, E, F, G>( graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, mut map1: F, mut map2: G ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    K: Clone + Ord + Debug,
+    N: Clone,
+    E: Clone,
+    Graph<D, C, R1>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+    Graph<D, C, R2>: IterNodeIds<J, N> + IterEdgeIds<J, E>,
+    Graph<D, C, R>: Default + AddNode<K, N> + AddEdge<K, E>,
+    F: FnMut( I ) -> K,
+    G: FnMut( J ) -> K
+{
+    let mut graph = Graph::default();
+    let mut ids = BTreeSet::new();
+    for ( id, node ) in graph1.iter_node_ids() {
+        let id = map1( id );
+        ids.insert( id.clone() );
+        graph.add_node( id, node.clone() );
+    }
+    for ( id, node ) in graph2.iter_node_ids() {
+        let id = map2( id );
+        if ids.contains( &id ) {
+            return Err( Error::Collision( format!( "{:?}", id ) ) );
+        }
+        graph.add_node( id, node.clone() );
+    }
+    for ( id1, id2, edge ) in graph1.iter_edge_ids() {
+        graph.add_edge( map1( id1 ), map1( id2 ), edge.clone() );
+    }
+    for ( id1, id2, edge ) in graph2.iter_edge_ids() {
+        graph.add_edge( map2( id1 ), map2( id2 ), edge.clone() );
+    }
+    Ok( graph )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ BTreeRepr, NodeRepr, StaticRepr },
+        traits::{ AddEdge, IterEdgeIds }
+    };
+
+    use super::*;
+
+    type Dir = Graph<Directed, Cyclic, BTreeRepr<u32, char, i32>>;
+
+    fn graph<D>( nodes: &[ ( u32, char ) ], edges: &[ ( u32, u32, i32 ) ] ) -> Graph<D, Cyclic, BTreeRepr<u32, char, i32>>
+    where
+        D: Directional + Default
+    {
+        let mut repr = BTreeMap::new();
+        for ( id, node ) in nodes {
+            repr.insert( *id, NodeRepr { node: *node, adjs: BTreeMap::new() } );
+        }
+        for ( id1, id2, edge ) in edges {
+            repr.get_mut( id1 ).unwrap().adjs.insert( *id2, *edge );
+            if !D::DIRECTED {
+                repr.get_mut( id2 ).unwrap().adjs.insert( *id1, *edge );
+            }
+        }
+        Graph( BTreeRepr( repr ), Default::default() )
+    }
+
+    fn edges<G, I, E>( graph: &G ) -> Vec<( I, I, E )>
+    where
+        G: IterEdgeIds<I, E>,
+        E: Clone
+    {
+        graph.iter_edge_ids().map( |( id1, id2, edge )| ( id1, id2, edge.clone() ) ).collect()
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let graph1: Dir = graph( &[ ( 1, 'a' ), ( 2, 'b' ), ( 3, 'c' ) ], &[ ( 1, 2, 10 ), ( 2, 3, 20 ) ] );
+        let graph2: Dir = graph( &[ ( 2, 'x' ), ( 3, 'y' ), ( 4, 'z' ) ], &[ ( 2, 3, 99 ), ( 3, 4, 30 ) ] );
+
+        let both = union( &graph1, &graph2 );
+        assert_eq!( both.iter_node_ids().map( |( id, node )| ( id, *node ) ).collect::<Vec<_>>(), vec![ ( 1, 'a' ), ( 2, 'b' ), ( 3, 'c' ), ( 4, 'z' ) ] );
+        assert_eq!( edges( &both ), vec![ ( 1, 2, 10 ), ( 2, 3, 20 ), ( 3, 4, 30 ) ] );
+
+        let common = intersection( &graph1, &graph2 );
+        assert_eq!( common.iter_node_ids().map( |( id, _ )| id ).collect::<Vec<_>>(), vec![ 2, 3 ] );
+        assert_eq!( edges( &common ), vec![ ( 2, 3, 20 ) ] );
+
+        assert_eq!( edges( &difference( &graph1, &graph2 ) ), vec![ ( 1, 2, 10 ) ] );
+        assert_eq!( edges( &symmetric_difference( &graph1, &graph2 ) ), vec![ ( 1, 2, 10 ), ( 3, 4, 30 ) ] );
+    }
+
+    #[test]
+    fn test_complement() {
+        let path = graph::<Undirected>( &[ ( 1, 'a' ), ( 2, 'b' ), ( 3, 'c' ) ], &[ ( 1, 2, 5 ), ( 2, 3, 5 ) ] );
+        assert_eq!( edges( &complement( &path ) ), vec![ ( 1, 3, 0 ), ( 3, 1, 0 ) ] );
+
+        let mut fixed: Graph<Directed, Cyclic, StaticRepr<(), u8, 3>> = Graph::default();
+        fixed.add_edge( 0, 1, 7 );
+        fixed.add_edge( 1, 1, 7 );
+        let flipped = fixed.complement();
+        assert_eq!( edges( &flipped ), vec![ ( 0, 2, 0 ), ( 1, 0, 0 ), ( 1, 2, 0 ), ( 2, 0, 0 ), ( 2, 1, 0 ) ] );
+        assert_eq!( edges( &complement( &fixed ) ), edges( &flipped ) );
+    }
+
+    #[test]
+    fn test_disjoint_union() {
+        let graph1: Dir = graph( &[ ( 1, 'a' ), ( 2, 'b' ) ], &[ ( 1, 2, 10 ) ] );
+        let graph2: Dir = graph( &[ ( 1, 'c' ) ], &[ ( 1, 1, 20 ) ] );
+        let both: Graph<Directed, Cyclic, BTreeRepr<( u8, u32 ), char, i32>> = disjoint_union( &graph1, &graph2, |id| ( 0, id ), |id| ( 1, id ) ).unwrap();
+        assert_eq!( edges( &both ), vec![ ( ( 0, 1 ), ( 0, 2 ), 10 ), ( ( 1, 1 ), ( 1, 1 ), 20 ) ] );
+
+        let colliding: Result<Dir, _> = disjoint_union( &graph1, &graph2, |id| id, |id| id );
+        assert!( matches!( colliding, Err( Error::Collision( _ ) ) ) );
+    }
+}
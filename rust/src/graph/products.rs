@@ -0,0 +1,246 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! Products of two graphs, with nodes keyed by `( I1, I2 )`.
+//!
+//! Every product has a node for each pair of nodes, with data combined by a `node` closure. Products differ in which
+//! pairs are joined; the `edge` closure receives the factor edges an edge comes from, `None` for a factor whose nodes
+//! stay the same. Undirected graphs store every edge in both directions, so their products come out undirected too.
+//!
+
+use std::collections::BTreeMap;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+/// Which pairs of nodes a product joins. `~` is adjacency in a factor.
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub enum Product {
+    /// `( u1, u2 ) ~ ( v1, v2 )` if `u1 ~ v1` and `u2 = v2`, or `u1 = v1` and `u2 ~ v2`.
+    Cartesian,
+    /// `( u1, u2 ) ~ ( v1, v2 )` if `u1 ~ v1` and `u2 ~ v2`.
+    Tensor,
+    /// The union of the Cartesian and tensor products.
+    Strong,
+    /// `( u1, u2 ) ~ ( v1, v2 )` if `u1 ~ v1`, or `u1 = v1` and `u2 ~ v2`.
+    Lexicographic
+}
+
+/// The product of two graphs of the given kind.
+pub fn product<D, C, R1, R2, R, I1, I2, N1, N2, E1, E2, N, E, FN, FE>( kind: Product, graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, mut node: FN, mut edge: FE ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    I1: Clone + Ord,
+    I2: Clone + Ord,
+    Graph<D, C, R1>: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+    Graph<D, C, R2>: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+    Graph<D, C, R>: Default + AddNode<( I1, I2 ), N> + AddEdge<( I1, I2 ), E>,
+    FN: FnMut( &N1, &N2 ) -> N,
+    FE: FnMut( Option<&E1>, Option<&E2> ) -> E
+{
+    let nodes1: Vec<_> = graph1.iter_node_ids().collect();
+    let nodes2: Vec<_> = graph2.iter_node_ids().collect();
+    let edges1: Vec<_> = graph1.iter_edge_ids().collect();
+    let edges2: Vec<_> = graph2.iter_edge_ids().collect();
+
+    let mut graph = Graph::default();
+    for ( id1, node1 ) in &nodes1 {
+        for ( id2, node2 ) in &nodes2 {
+            graph.add_node( ( id1.clone(), id2.clone() ), node( *node1, *node2 ) );
+        }
+    }
+
+    // Edges moving in the first factor only.
+    if matches!( kind, Product::Cartesian | Product::Strong ) {
+        for ( from1, to1, edge1 ) in &edges1 {
+            for ( id2, _ ) in &nodes2 {
+                graph.add_edge( ( from1.clone(), id2.clone() ), ( to1.clone(), id2.clone() ), edge( Some( *edge1 ), None ) );
+            }
+        }
+    }
+    // Edges moving in the second factor only.
+    if matches!( kind, Product::Cartesian | Product::Strong | Product::Lexicographic ) {
+        for ( id1, _ ) in &nodes1 {
+            for ( from2, to2, edge2 ) in &edges2 {
+                graph.add_edge( ( id1.clone(), from2.clone() ), ( id1.clone(), to2.clone() ), edge( None, Some( *edge2 ) ) );
+            }
+        }
+    }
+    // Edges moving in both factors.
+    if matches!( kind, Product::Tensor | Product::Strong ) {
+        for ( from1, to1, edge1 ) in &edges1 {
+            for ( from2, to2, edge2 ) in &edges2 {
+                graph.add_edge( ( from1.clone(), from2.clone() ), ( to1.clone(), to2.clone() ), edge( Some( *edge1 ), Some( *edge2 ) ) );
+            }
+        }
+    }
+    // Edges moving in the first factor, between any nodes of the second.
+    if kind == Product::Lexicographic {
+        let adjacent2: BTreeMap<_, _> = edges2.iter().map( |( from2, to2, edge2 )| ( ( from2, to2 ), *edge2 ) ).collect();
+        for ( from1, to1, edge1 ) in &edges1 {
+            for ( from2, _ ) in &nodes2 {
+                for ( to2, _ ) in &nodes2 {
+                    let edge2 = adjacent2.get( &( from2, to2 ) ).copied();
+                    graph.add_edge( ( from1.clone(), from2.clone() ), ( to1.clone(), to2.clone() ), edge( Some( *edge1 ), edge2 ) );
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// The [`Product::Cartesian`] product, e.g. a grid from two paths.
+pub fn cartesian<D, C, R1, R2, R, I1, I2, N1, N2, E1, E2, N, E, FN, FE>( graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, node: FN, edge: FE ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    I1: Clone + Ord,
+    I2: Clone + Ord,
+    Graph<D, C, R1>: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+    Graph<D, C, R2>: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+    Graph<D, C, R>: Default + AddNode<( I1, I2 ), N> + AddEdge<( I1, I2 ), E>,
+    FN: FnMut( &N1, &N2 ) -> N,
+    FE: FnMut( Option<&E1>, Option<&E2> ) -> E
+{
+    product( Product::Cartesian, graph1, graph2, node, edge )
+}
+
+/// The [`Product::Tensor`] product, also called the direct or Kronecker product.
+pub fn tensor<D, C, R1, R2, R, I1, I2, N1, N2, E1, E2, N, E, FN, FE>( graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, node: FN, edge: FE ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    I1: Clone + Ord,
+    I2: Clone + Ord,
+    Graph<D, C, R1>: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+    Graph<D, C, R2>: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+    Graph<D, C, R>: Default + AddNode<( I1, I2 ), N> + AddEdge<( I1, I2 ), E>,
+    FN: FnMut( &N1, &N2 ) -> N,
+    FE: FnMut( Option<&E1>, Option<&E2> ) -> E
+{
+    product( Product::Tensor, graph1, graph2, node, edge )
+}
+
+/// The [`Product::Strong`] product, e.g. a king's graph from two paths.
+pub fn strong<D, C, R1, R2, R, I1, I2, N1, N2, E1, E2, N, E, FN, FE>( graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, node: FN, edge: FE ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    I1: Clone + Ord,
+    I2: Clone + Ord,
+    Graph<D, C, R1>: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+    Graph<D, C, R2>: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+    Graph<D, C, R>: Default + AddNode<( I1, I2 ), N> + AddEdge<( I1, I2 ), E>,
+    FN: FnMut( &N1, &N2 ) -> N,
+    FE: FnMut( Option<&E1>, Option<&E2> ) -> E
+{
+    product( Product::Strong, graph1, graph2, node, edge )
+}
+
+/// The [`Product::Lexicographic`] product: a copy of the second graph for each node of the first, with every node of a
+/// copy joined to every node of the copies adjacent to it.
+pub fn lexicographic<D, C, R1, R2, R, I1, I2, N1, N2, E1, E2, N, E, FN, FE>( graph1: &Graph<D, C, R1>, graph2: &Graph<D, C, R2>, node: FN, edge: FE ) -> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R1: GraphRepr,
+    R2: GraphRepr,
+    R: GraphRepr,
+    I1: Clone + Ord,
+    I2: Clone + Ord,
+    Graph<D, C, R1>: IterNodeIds<I1, N1> + IterEdgeIds<I1, E1>,
+    Graph<D, C, R2>: IterNodeIds<I2, N2> + IterEdgeIds<I2, E2>,
+    Graph<D, C, R>: Default + AddNode<( I1, I2 ), N> + AddEdge<( I1, I2 ), E>,
+    FN: FnMut( &N1, &N2 ) -> N,
+    FE: FnMut( Option<&E1>, Option<&E2> ) -> E
+{
+    product( Product::Lexicographic, graph1, graph2, node, edge )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic, generators },
+        graph_repr::BTreeRepr,
+        traits::IterEdgeIds
+    };
+
+    use super::*;
+
+    type Factor = Graph<Undirected, Cyclic, BTreeRepr<usize, (), ()>>;
+    type Pairs = Graph<Undirected, Cyclic, BTreeRepr<( usize, usize ), (), ()>>;
+
+    fn path( n: usize ) -> Factor {
+        generators::path::<_, _, _, (), ()>( n )
+    }
+
+    fn size( graph: &Pairs ) -> usize {
+        graph.iter_edge_ids().filter( |( id1, id2, _ )| id1 < id2 ).count()
+    }
+
+    #[test]
+    fn test_products() {
+        let unit = |_: &(), _: &()| ();
+        let join = |_: Option<&()>, _: Option<&()>| ();
+        let grid: Pairs = cartesian( &path( 2 ), &path( 3 ), unit, join );
+        assert_eq!( size( &grid ), 7 );
+        let pairs: Pairs = tensor( &path( 2 ), &path( 2 ), unit, join );
+        assert_eq!( size( &pairs ), 2 );
+        let king: Pairs = strong( &path( 2 ), &path( 2 ), unit, join );
+        assert_eq!( size( &king ), 6 );
+        let composed: Pairs = lexicographic( &path( 3 ), &path( 2 ), unit, join );
+        assert_eq!( size( &composed ), 11 );
+    }
+
+    #[test]
+    fn test_product_data() {
+        let mut graph1: Graph<Directed, Cyclic, BTreeRepr<char, i32, i32>> = Graph::default();
+        graph1.add_node( 'a', 1 );
+        graph1.add_node( 'b', 2 );
+        graph1.add_edge( 'a', 'b', 10 );
+        let mut graph2: Graph<Directed, Cyclic, BTreeRepr<u8, i32, i32>> = Graph::default();
+        graph2.add_node( 0, 100 );
+        graph2.add_node( 1, 200 );
+        graph2.add_edge( 0, 1, 20 );
+
+        let combined: Graph<Directed, Cyclic, BTreeRepr<( char, u8 ), i32, i32>> = lexicographic( &graph1, &graph2,
+            |node1, node2| node1 + node2,
+            |edge1, edge2| edge1.copied().unwrap_or( 0 ) + edge2.copied().unwrap_or( 0 )
+        );
+        assert_eq!( combined.iter_node_ids().map( |( id, node )| ( id, *node ) ).collect::<Vec<_>>(), vec![
+            ( ( 'a', 0 ), 101 ), ( ( 'a', 1 ), 201 ), ( ( 'b', 0 ), 102 ), ( ( 'b', 1 ), 202 )
+        ]);
+        assert_eq!( combined.iter_edge_ids().map( |( id1, id2, edge )| ( id1, id2, *edge ) ).collect::<Vec<_>>(), vec![
+            ( ( 'a', 0 ), ( 'a', 1 ), 20 ),
+            ( ( 'a', 0 ), ( 'b', 0 ), 10 ),
+            ( ( 'a', 0 ), ( 'b', 1 ), 30 ),
+            ( ( 'a', 1 ), ( 'b', 0 ), 10 ),
+            ( ( 'a', 1 ), ( 'b', 1 ), 10 ),
+            ( ( 'b', 0 ), ( 'b', 1 ), 20 )
+        ]);
+    }
+}
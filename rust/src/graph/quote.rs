@@ -0,0 +1,51 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::fmt::Write as _;
+
+/// Wraps `value` in double quotes, writing every character through `escape`.
+fn quote_with<F>( value: &str, escape: F ) -> String
+where
+    F: Fn( &mut String, char )
+{
+    let mut quoted = String::with_capacity( value.len() + 2 );
+    quoted.push( '"' );
+    value.chars().for_each( |character| escape( &mut quoted, character ) );
+    quoted.push( '"' );
+    quoted
+}
+
+/// A JSON string literal. Control characters are escaped as `\uXXXX` unless they have a short escape.
+pub(crate) fn json( value: &str ) -> String {
+    quote_with( value, |quoted, character| match character {
+        '"' => quoted.push_str( "\\\"" ),
+        '\\' => quoted.push_str( "\\\\" ),
+        '\n' => quoted.push_str( "\\n" ),
+        '\r' => quoted.push_str( "\\r" ),
+        '\t' => quoted.push_str( "\\t" ),
+        character if character.is_control() => {
+            let _ = write!( quoted, "\\u{:04x}", character as u32 );
+        },
+        character => quoted.push( character )
+    })
+}
+
+/// A DOT quoted string. Newlines become `\n` so multi-line labels stay on one line.
+pub(crate) fn dot( value: &str ) -> String {
+    quote_with( value, |quoted, character| match character {
+        '"' => quoted.push_str( "\\\"" ),
+        '\\' => quoted.push_str( "\\\\" ),
+        '\n' => quoted.push_str( "\\n" ),
+        '\r' => {},
+        character => quoted.push( character )
+    })
+}
+
+/// A Mermaid quoted label. Quotes become the `#quot;` entity and newlines become `<br/>`.
+pub(crate) fn mermaid( value: &str ) -> String {
+    quote_with( value, |quoted, character| match character {
+        '"' => quoted.push_str( "#quot;" ),
+        '\n' => quoted.push_str( "<br/>" ),
+        '\r' => {},
+        character => quoted.push( character )
+    })
+}
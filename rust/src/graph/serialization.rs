@@ -0,0 +1,443 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! Serde support for the graph representations, enabled by the `serde` feature.
+//!
+//! Every representation uses the same schema, a node list and an edge list:
+//!
+//! ```json
+//! { "nodes": [ [ 0, "a" ], [ 1, "b" ] ], "edges": [ [ 0, 1, 2.5 ] ] }
+//! ```
+//!
+//! so a graph saved from one representation loads into any other with the same id type. A [`Graph`] adds a
+//! `directed` field, checked against `D` when loading. Undirected graphs list each edge in both directions, as they
+//! are stored.
+//!
+
+use std::{
+    collections::{ BTreeMap, HashMap },
+    hash::Hash
+};
+
+use serde::{
+    de::Error as _,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer
+};
+
+use crate::{
+    graph::{
+        Graph,
+        GraphType,
+        Directional,
+        Cyclical
+    },
+    graph_repr::{
+        GraphRepr,
+        NodeRepr,
+        StaticRepr,
+        DynRepr,
+        HashRepr,
+        BTreeRepr
+    }
+};
+
+/// Conversion of a representation to and from the node and edge lists of the schema.
+pub trait GraphSchema: Sized {
+    type Id;
+    type Node;
+    type Edge;
+
+    fn node_list( &self ) -> Vec<( Self::Id, &Self::Node )>;
+    fn edge_list( &self ) -> Vec<( Self::Id, Self::Id, &Self::Edge )>;
+    fn from_lists( nodes: Vec<( Self::Id, Self::Node )>, edges: Vec<( Self::Id, Self::Id, Self::Edge )> ) -> Result<Self, String>;
+}
+
+#[derive( Serialize )]
+struct ListsRef<'a, I, N, E> {
+    nodes: Vec<( I, &'a N )>,
+    edges: Vec<( I, I, &'a E )>
+}
+
+#[derive( Deserialize )]
+struct Lists<I, N, E> {
+    nodes: Vec<( I, N )>,
+    edges: Vec<( I, I, E )>
+}
+
+#[derive( Serialize )]
+struct GraphRef<'a, I, N, E> {
+    directed: bool,
+    nodes: Vec<( I, &'a N )>,
+    edges: Vec<( I, I, &'a E )>
+}
+
+#[derive( Deserialize )]
+struct GraphLists<I, N, E> {
+    directed: bool,
+    nodes: Vec<( I, N )>,
+    edges: Vec<( I, I, E )>
+}
+
+fn serialize_repr<R, S>( repr: &R, serializer: S ) -> Result<S::Ok, S::Error>
+where
+    R: GraphSchema,
+    R::Id: Serialize,
+    R::Node: Serialize,
+    R::Edge: Serialize,
+    S: Serializer
+{
+    ListsRef { nodes: repr.node_list(), edges: repr.edge_list() }.serialize( serializer )
+}
+
+fn deserialize_repr<'de, R, D>( deserializer: D ) -> Result<R, D::Error>
+where
+    R: GraphSchema,
+    R::Id: Deserialize<'de>,
+    R::Node: Deserialize<'de>,
+    R::Edge: Deserialize<'de>,
+    D: Deserializer<'de>
+{
+    let Lists { nodes, edges } = Lists::deserialize( deserializer )?;
+    R::from_lists( nodes, edges ).map_err( D::Error::custom )
+}
+
+impl<N, E, const SIZE: usize> GraphSchema for StaticRepr<N, E, SIZE>
+where
+    N: 'static + Clone + Copy + Default + std::fmt::Debug,
+    E: 'static + Clone + Copy + Default + std::fmt::Debug
+{
+    type Id = usize;
+    type Node = N;
+    type Edge = E;
+
+    fn node_list( &self ) -> Vec<( usize, &N )> {
+        self.0.iter().enumerate().map( |( id, node )| ( id, &node.node ) ).collect()
+    }
+
+    fn edge_list( &self ) -> Vec<( usize, usize, &E )> {
+        self.0.iter().enumerate().flat_map( |( id1, node )| node.adjs.iter().enumerate()
+            .filter_map( move |( id2, edge )| edge.as_ref().map( |edge| ( id1, id2, edge ) ) )
+        ).collect()
+    }
+
+    fn from_lists( nodes: Vec<( usize, N )>, edges: Vec<( usize, usize, E )> ) -> Result<Self, String> {
+        let mut repr = std::array::from_fn( |_| NodeRepr { node: N::default(), adjs: [ None; SIZE ] } );
+        let out_of_range = |id| format!( "Node {} does not fit a graph of {} nodes", id, SIZE );
+        for ( id, node ) in nodes {
+            repr.get_mut( id ).ok_or_else( || out_of_range( id ) )?.node = node;
+        }
+        for ( id1, id2, edge ) in edges {
+            if id2 >= SIZE {
+                return Err( out_of_range( id2 ) );
+            }
+            repr.get_mut( id1 ).ok_or_else( || out_of_range( id1 ) )?.adjs[ id2 ] = Some( edge );
+        }
+        Ok( Self( repr ) )
+    }
+}
+
+impl<N, E> GraphSchema for DynRepr<N, Option<E>>
+where
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    type Id = usize;
+    type Node = N;
+    type Edge = E;
+
+    fn node_list( &self ) -> Vec<( usize, &N )> {
+        self.0.iter().enumerate().map( |( id, node )| ( id, &node.node ) ).collect()
+    }
+
+    fn edge_list( &self ) -> Vec<( usize, usize, &E )> {
+        self.0.iter().enumerate().flat_map( |( id1, node )| node.adjs.iter().enumerate()
+            .filter_map( move |( id2, edge )| edge.as_ref().map( |edge| ( id1, id2, edge ) ) )
+        ).collect()
+    }
+
+    /// Ids missing from the node list, below the largest one, get default nodes.
+    fn from_lists( nodes: Vec<( usize, N )>, edges: Vec<( usize, usize, E )> ) -> Result<Self, String> {
+        let size = nodes.iter().map( |( id, _ )| id + 1 ).max().unwrap_or( 0 );
+        let mut repr: Vec<_> = ( 0..size ).map( |_| NodeRepr { node: N::default(), adjs: vec![ None; size ] } ).collect();
+        for ( id, node ) in nodes {
+            repr[ id ].node = node;
+        }
+        for ( id1, id2, edge ) in edges {
+            if id1 >= size || id2 >= size {
+                return Err( format!( "Edge ( {}, {} ) references a node that is not in the node list", id1, id2 ) );
+            }
+            repr[ id1 ].adjs[ id2 ] = Some( edge );
+        }
+        Ok( Self( repr ) )
+    }
+}
+
+impl<I, N, E> GraphSchema for HashRepr<I, N, E>
+where
+    I: Clone + Ord + Hash + std::fmt::Debug,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    type Id = I;
+    type Node = N;
+    type Edge = E;
+
+    /// Sorted by id, so the same graph always serializes the same way.
+    fn node_list( &self ) -> Vec<( I, &N )> {
+        let mut nodes: Vec<_> = self.0.iter().map( |( id, node )| ( id.clone(), &node.node ) ).collect();
+        nodes.sort_by( |( id1, _ ), ( id2, _ )| id1.cmp( id2 ) );
+        nodes
+    }
+
+    fn edge_list( &self ) -> Vec<( I, I, &E )> {
+        let mut edges: Vec<_> = self.0.iter().flat_map( |( id1, node )| node.adjs.iter()
+            .map( move |( id2, edge )| ( id1.clone(), id2.clone(), edge ) )
+        ).collect();
+        edges.sort_by( |( from1, to1, _ ), ( from2, to2, _ )| ( from1, to1 ).cmp( &( from2, to2 ) ) );
+        edges
+    }
+
+    fn from_lists( nodes: Vec<( I, N )>, edges: Vec<( I, I, E )> ) -> Result<Self, String> {
+        let mut repr: HashMap<_, _> = nodes.into_iter()
+            .map( |( id, node )| ( id, NodeRepr { node, adjs: HashMap::new() } ) )
+            .collect();
+        for ( id1, id2, edge ) in edges {
+            if !repr.contains_key( &id2 ) {
+                return Err( format!( "Edge target {:?} is not in the node list", id2 ) );
+            }
+            match repr.get_mut( &id1 ) {
+                Some( node ) => node.adjs.insert( id2, edge ),
+                None => return Err( format!( "Edge source {:?} is not in the node list", id1 ) )
+            };
+        }
+        Ok( Self( repr ) )
+    }
+}
+
+impl<I, N, E> GraphSchema for BTreeRepr<I, N, E>
+where
+    I: Clone + Ord + std::fmt::Debug,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    type Id = I;
+    type Node = N;
+    type Edge = E;
+
+    fn node_list( &self ) -> Vec<( I, &N )> {
+        self.0.iter().map( |( id, node )| ( id.clone(), &node.node ) ).collect()
+    }
+
+    fn edge_list( &self ) -> Vec<( I, I, &E )> {
+        self.0.iter().flat_map( |( id1, node )| node.adjs.iter()
+            .map( move |( id2, edge )| ( id1.clone(), id2.clone(), edge ) )
+        ).collect()
+    }
+
+    fn from_lists( nodes: Vec<( I, N )>, edges: Vec<( I, I, E )> ) -> Result<Self, String> {
+        let mut repr: BTreeMap<_, _> = nodes.into_iter()
+            .map( |( id, node )| ( id, NodeRepr { node, adjs: BTreeMap::new() } ) )
+            .collect();
+        for ( id1, id2, edge ) in edges {
+            if !repr.contains_key( &id2 ) {
+                return Err( format!( "Edge target {:?} is not in the node list", id2 ) );
+            }
+            match repr.get_mut( &id1 ) {
+                Some( node ) => node.adjs.insert( id2, edge ),
+                None => return Err( format!( "Edge source {:?} is not in the node list", id1 ) )
+            };
+        }
+        Ok( Self( repr ) )
+    }
+}
+
+impl<N, E, const SIZE: usize> Serialize for StaticRepr<N, E, SIZE>
+where
+    N: 'static + Clone + Copy + Default + std::fmt::Debug + Serialize,
+    E: 'static + Clone + Copy + Default + std::fmt::Debug + Serialize
+{
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serialize_repr( self, serializer )
+    }
+}
+
+impl<'de, N, E, const SIZE: usize> Deserialize<'de> for StaticRepr<N, E, SIZE>
+where
+    N: 'static + Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>,
+    E: 'static + Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>
+{
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserialize_repr( deserializer )
+    }
+}
+
+impl<N, E> Serialize for DynRepr<N, Option<E>>
+where
+    N: Clone + Copy + Default + std::fmt::Debug + Serialize,
+    E: Clone + Copy + Default + std::fmt::Debug + Serialize
+{
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serialize_repr( self, serializer )
+    }
+}
+
+impl<'de, N, E> Deserialize<'de> for DynRepr<N, Option<E>>
+where
+    N: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>,
+    E: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>
+{
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserialize_repr( deserializer )
+    }
+}
+
+impl<I, N, E> Serialize for HashRepr<I, N, E>
+where
+    I: Clone + Ord + Hash + std::fmt::Debug + Serialize,
+    N: Clone + Copy + Default + std::fmt::Debug + Serialize,
+    E: Clone + Copy + Default + std::fmt::Debug + Serialize
+{
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serialize_repr( self, serializer )
+    }
+}
+
+impl<'de, I, N, E> Deserialize<'de> for HashRepr<I, N, E>
+where
+    I: Clone + Ord + Hash + std::fmt::Debug + Deserialize<'de>,
+    N: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>,
+    E: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>
+{
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserialize_repr( deserializer )
+    }
+}
+
+impl<I, N, E> Serialize for BTreeRepr<I, N, E>
+where
+    I: Clone + Ord + std::fmt::Debug + Serialize,
+    N: Clone + Copy + Default + std::fmt::Debug + Serialize,
+    E: Clone + Copy + Default + std::fmt::Debug + Serialize
+{
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        serialize_repr( self, serializer )
+    }
+}
+
+impl<'de, I, N, E> Deserialize<'de> for BTreeRepr<I, N, E>
+where
+    I: Clone + Ord + std::fmt::Debug + Deserialize<'de>,
+    N: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>,
+    E: Clone + Copy + Default + std::fmt::Debug + Deserialize<'de>
+{
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        deserialize_repr( deserializer )
+    }
+}
+
+impl<D, C, R> Serialize for Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr + GraphSchema,
+    R::Id: Serialize,
+    R::Node: Serialize,
+    R::Edge: Serialize
+{
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        GraphRef { directed: D::DIRECTED, nodes: self.0.node_list(), edges: self.0.edge_list() }.serialize( serializer )
+    }
+}
+
+impl<'de, D, C, R> Deserialize<'de> for Graph<D, C, R>
+where
+    D: Directional + Default,
+    C: Cyclical + Default,
+    R: GraphRepr + GraphSchema,
+    R::Id: Deserialize<'de>,
+    R::Node: Deserialize<'de>,
+    R::Edge: Deserialize<'de>
+{
+    fn deserialize<De>( deserializer: De ) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>
+    {
+        let GraphLists { directed, nodes, edges } = GraphLists::deserialize( deserializer )?;
+        if directed != D::DIRECTED {
+            let kind = |directed| if directed { "directed" } else { "undirected" };
+            return Err( De::Error::custom( format!( "Expected an {} graph but found an {} one", kind( D::DIRECTED ), kind( directed ) ) ) );
+        }
+        let repr = R::from_lists( nodes, edges ).map_err( De::Error::custom )?;
+        Ok( Graph( repr, GraphType::default() ) )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ StaticRepr, DynRepr, HashRepr, BTreeRepr },
+        traits::{ AddNode, AddEdge, IterNodeIds, IterEdgeIds }
+    };
+
+    #[test]
+    fn test_serde_across_representations() {
+        let mut graph = Graph::<Directed, Cyclic, HashRepr<u32, i32, f64>>::default();
+        for id in [ 3, 1, 2 ] {
+            graph.add_node( id, id as i32 * 10 );
+        }
+        graph.add_edge( 1, 2, 0.5 );
+        graph.add_edge( 3, 1, 1.5 );
+
+        let json = serde_json::to_string( &graph ).unwrap();
+        assert_eq!( json, r#"{"directed":true,"nodes":[[1,10],[2,20],[3,30]],"edges":[[1,2,0.5],[3,1,1.5]]}"# );
+        let loaded: Graph<Directed, Cyclic, BTreeRepr<u32, i32, f64>> = serde_json::from_str( &json ).unwrap();
+        assert_eq!( loaded.iter_node_ids().collect::<Vec<_>>(), vec![ ( 1, &10 ), ( 2, &20 ), ( 3, &30 ) ] );
+        assert_eq!( loaded.iter_edge_ids().collect::<Vec<_>>(), vec![ ( 1, 2, &0.5 ), ( 3, 1, &1.5 ) ] );
+
+        assert!( serde_json::from_str::<Graph<Undirected, Cyclic, BTreeRepr<u32, i32, f64>>>( &json ).is_err() );
+    }
+
+    #[test]
+    fn test_serde_index_representations() {
+        let json = r#"{"directed":false,"nodes":[[0,1],[2,3]],"edges":[[0,2,7],[2,0,7]]}"#;
+        let dynamic: Graph<Undirected, Cyclic, DynRepr<u8, Option<u8>>> = serde_json::from_str( json ).unwrap();
+        assert_eq!( dynamic.iter_node_ids().collect::<Vec<_>>(), vec![ ( 0, &1 ), ( 1, &0 ), ( 2, &3 ) ] );
+
+        let fixed: Graph<Undirected, Cyclic, StaticRepr<u8, u8, 3>> = serde_json::from_str( &serde_json::to_string( &dynamic ).unwrap() ).unwrap();
+        assert_eq!( fixed.iter_edge_ids().collect::<Vec<_>>(), dynamic.iter_edge_ids().collect::<Vec<_>>() );
+
+        assert!( serde_json::from_str::<Graph<Undirected, Cyclic, StaticRepr<u8, u8, 2>>>( json ).is_err() );
+        let dangling = r#"{"nodes":[[0,1]],"edges":[[0,5,1]]}"#;
+        assert!( serde_json::from_str::<BTreeRepr<usize, u8, u8>>( dangling ).is_err() );
+    }
+}
@@ -0,0 +1,578 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! A versioned binary snapshot format for large graphs, readable without deserializing.
+//!
+//! A snapshot is a 64-byte [`Header`] followed by five sections, each starting at a multiple of 64 bytes:
+//!
+//! | section   | contents                                                        |
+//! |-----------|-----------------------------------------------------------------|
+//! | `offsets` | `node_count + 1` `u64`s, the edges of node `i` are `offsets[ i ]..offsets[ i + 1 ]` |
+//! | `targets` | `edge_count` `u64` node indices                                 |
+//! | `ids`     | `node_count` ids, sorted                                        |
+//! | `nodes`   | `node_count` node payloads                                      |
+//! | `edges`   | `edge_count` edge payloads, in the order of `targets`           |
+//!
+//! Nodes are indexed by the position of their id in the sorted `ids` section, so the same graph gives the same
+//! snapshot whatever its representation. Ids and payloads are stored as their in-memory bytes, which is why they must
+//! be [`Pod`], and in native byte order; a snapshot written on a machine of the other endianness is rejected.
+//!
+
+use std::{
+    io::{ self, Write },
+    mem::{ align_of, size_of },
+    slice
+};
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        GetNode,
+        GetEdge,
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+pub const MAGIC: [ u8; 8 ] = *b"GRAPHSNP";
+pub const VERSION: u32 = 1;
+
+const SECTION_ALIGN: usize = 64;
+const FLAG_DIRECTED: u32 = 1;
+const FLAG_BIG_ENDIAN: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    Io( #[from] io::Error ),
+    #[error("Not a graph snapshot")]
+    InvalidMagic,
+    #[error("Unsupported snapshot version {0}, expected {VERSION}")]
+    UnsupportedVersion( u32 ),
+    #[error("Snapshot was written with the other byte order")]
+    Endianness,
+    #[error("Expected {section} of size {expected_size} and alignment {expected_align}, found size {found_size} and alignment {found_align}")]
+    PayloadMismatch { section: &'static str, expected_size: usize, expected_align: usize, found_size: usize, found_align: usize },
+    #[error("Expected a {expected} graph but found a {found} one")]
+    Direction { expected: &'static str, found: &'static str },
+    #[error("Snapshot is truncated, expected {expected} bytes but found {found}")]
+    Truncated { expected: usize, found: usize },
+    #[error("Snapshot buffer is not aligned to {0} bytes")]
+    Misaligned( usize ),
+    #[error("Corrupt snapshot: {0}")]
+    Corrupt( String )
+}
+
+/// Types that can be stored as their raw bytes.
+///
+/// # Safety
+///
+/// The type must have no padding bytes, must be valid for every bit pattern, and must not contain pointers or
+/// references. `#[repr(C)]` structs of such fields, without padding between them, qualify.
+///
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for () {}
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for u128 {}
+unsafe impl Pod for usize {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for i128 {}
+unsafe impl Pod for isize {}
+unsafe impl Pod for f32 {}
+unsafe impl Pod for f64 {}
+unsafe impl<T, const N: usize> Pod for [ T; N ] where T: Pod {}
+
+#[repr(C)]
+#[derive( Debug, Clone, Copy, PartialEq, Eq )]
+pub struct Header {
+    pub magic: [ u8; 8 ],
+    pub version: u32,
+    pub flags: u32,
+    pub node_count: u64,
+    pub edge_count: u64,
+    pub id_size: u32,
+    pub id_align: u32,
+    pub node_size: u32,
+    pub node_align: u32,
+    pub edge_size: u32,
+    pub edge_align: u32,
+    pub reserved: u64
+}
+
+unsafe impl Pod for Header {}
+
+impl Header {
+    pub fn is_directed( &self ) -> bool {
+        self.flags & FLAG_DIRECTED != 0
+    }
+
+    /// The section bounds, or `None` if they do not fit in the address space.
+    fn sections( &self ) -> Option<Sections> {
+        let node_count = usize::try_from( self.node_count ).ok()?;
+        let edge_count = usize::try_from( self.edge_count ).ok()?;
+        let after = |start: usize, count: usize, size: usize| count.checked_mul( size ).and_then( |len| start.checked_add( len ) );
+        let offsets = align( size_of::<Header>() )?;
+        let targets = align( after( offsets, node_count.checked_add( 1 )?, size_of::<u64>() )? )?;
+        let ids = align( after( targets, edge_count, size_of::<u64>() )? )?;
+        let nodes = align( after( ids, node_count, self.id_size as usize )? )?;
+        let edges = align( after( nodes, node_count, self.node_size as usize )? )?;
+        let end = after( edges, edge_count, self.edge_size as usize )?;
+        Some( Sections { offsets, targets, ids, nodes, edges, end } )
+    }
+}
+
+struct Sections {
+    offsets: usize,
+    targets: usize,
+    ids: usize,
+    nodes: usize,
+    edges: usize,
+    end: usize
+}
+
+fn align( position: usize ) -> Option<usize> {
+    position.checked_next_multiple_of( SECTION_ALIGN )
+}
+
+fn bytes_of<T>( values: &[ T ] ) -> &[ u8 ]
+where
+    T: Pod
+{
+    // SAFETY: `Pod` values have no padding, so every byte is initialized.
+    unsafe { slice::from_raw_parts( values.as_ptr() as *const u8, std::mem::size_of_val( values ) ) }
+}
+
+/// Reinterprets `bytes[ start.. ]` as `count` values. The caller has checked bounds and `bytes` is aligned.
+fn cast<T>( bytes: &[ u8 ], start: usize, count: usize ) -> &[ T ]
+where
+    T: Pod
+{
+    let bytes = &bytes[ start..start + count * size_of::<T>() ];
+    // SAFETY: in bounds, aligned since sections start at multiples of `SECTION_ALIGN` from an aligned buffer, and any
+    // bit pattern is a valid `T`.
+    unsafe { slice::from_raw_parts( bytes.as_ptr() as *const T, count ) }
+}
+
+/// Writes `graph` as a snapshot.
+pub fn write<D, C, R, I, N, E, W>( graph: &Graph<D, C, R>, mut writer: W ) -> io::Result<()>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod,
+    Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+    W: Write
+{
+    let mut nodes: Vec<( I, N )> = graph.iter_node_ids().map( |( id, node )| ( id, *node ) ).collect();
+    nodes.sort_by_key( |( id, _ )| *id );
+    let ids: Vec<I> = nodes.iter().map( |( id, _ )| *id ).collect();
+    let index = |id: &I| ids.binary_search( id ).map( |index| index as u64 ).map_err( |_| {
+        io::Error::new( io::ErrorKind::InvalidInput, "Edge references a node that is not in the graph" )
+    });
+    let mut edges = Vec::new();
+    for ( id1, id2, edge ) in graph.iter_edge_ids() {
+        edges.push( ( index( &id1 )?, index( &id2 )?, *edge ) );
+    }
+    edges.sort_by_key( |( from, to, _ )| ( *from, *to ) );
+
+    let mut offsets = vec![ 0u64; ids.len() + 1 ];
+    for ( from, _, _ ) in &edges {
+        offsets[ *from as usize + 1 ] += 1;
+    }
+    for index in 1..offsets.len() {
+        offsets[ index ] += offsets[ index - 1 ];
+    }
+    let targets: Vec<u64> = edges.iter().map( |( _, to, _ )| *to ).collect();
+    let node_payloads: Vec<N> = nodes.iter().map( |( _, node )| *node ).collect();
+    let edge_payloads: Vec<E> = edges.iter().map( |( _, _, edge )| *edge ).collect();
+
+    let header = Header {
+        magic: MAGIC,
+        version: VERSION,
+        flags: ( if D::DIRECTED { FLAG_DIRECTED } else { 0 } ) | ( if cfg!( target_endian = "big" ) { FLAG_BIG_ENDIAN } else { 0 } ),
+        node_count: ids.len() as u64,
+        edge_count: edges.len() as u64,
+        id_size: size_of::<I>() as u32,
+        id_align: align_of::<I>() as u32,
+        node_size: size_of::<N>() as u32,
+        node_align: align_of::<N>() as u32,
+        edge_size: size_of::<E>() as u32,
+        edge_align: align_of::<E>() as u32,
+        reserved: 0
+    };
+    let sections = header.sections().ok_or_else( || io::Error::new( io::ErrorKind::InvalidInput, "Graph is too large for a snapshot" ) )?;
+    let mut position = 0;
+    for ( start, bytes ) in [
+        ( 0, bytes_of( &[ header ] ) ),
+        ( sections.offsets, bytes_of( &offsets ) ),
+        ( sections.targets, bytes_of( &targets ) ),
+        ( sections.ids, bytes_of( &ids ) ),
+        ( sections.nodes, bytes_of( &node_payloads ) ),
+        ( sections.edges, bytes_of( &edge_payloads ) )
+    ] {
+        writer.write_all( &vec![ 0; start - position ] )?;
+        writer.write_all( bytes )?;
+        position = start + bytes.len();
+    }
+    writer.flush()
+}
+
+/// A read-only graph over the bytes of a snapshot, without copying them.
+///
+/// Nodes are addressed by id through [`GetNode`] and [`GetEdge`], which binary search the sorted ids, or by index.
+///
+#[derive( Debug, Clone, Copy )]
+pub struct SnapshotView<'a, I, N, E> {
+    header: Header,
+    offsets: &'a [ u64 ],
+    targets: &'a [ u64 ],
+    ids: &'a [ I ],
+    nodes: &'a [ N ],
+    edges: &'a [ E ]
+}
+
+impl<'a, I, N, E> SnapshotView<'a, I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    /// Validates `bytes` and views them as a graph. `bytes` must start at a 64-byte boundary, which memory maps do.
+    pub fn new( bytes: &'a [ u8 ] ) -> Result<Self, Error> {
+        if !( bytes.as_ptr() as usize ).is_multiple_of( SECTION_ALIGN ) {
+            return Err( Error::Misaligned( SECTION_ALIGN ) );
+        }
+        if bytes.len() < size_of::<Header>() {
+            return Err( Error::Truncated { expected: size_of::<Header>(), found: bytes.len() } );
+        }
+        let header = cast::<Header>( bytes, 0, 1 )[ 0 ];
+        if header.magic != MAGIC {
+            return Err( Error::InvalidMagic );
+        }
+        if header.version != VERSION {
+            return Err( Error::UnsupportedVersion( header.version ) );
+        }
+        if ( header.flags & FLAG_BIG_ENDIAN != 0 ) != cfg!( target_endian = "big" ) {
+            return Err( Error::Endianness );
+        }
+        for ( section, expected_size, expected_align, found_size, found_align ) in [
+            ( "ids", size_of::<I>(), align_of::<I>(), header.id_size, header.id_align ),
+            ( "nodes", size_of::<N>(), align_of::<N>(), header.node_size, header.node_align ),
+            ( "edges", size_of::<E>(), align_of::<E>(), header.edge_size, header.edge_align )
+        ] {
+            if expected_size != found_size as usize || expected_align != found_align as usize || expected_align > SECTION_ALIGN {
+                return Err( Error::PayloadMismatch { section, expected_size, expected_align, found_size: found_size as usize, found_align: found_align as usize } );
+            }
+        }
+        let sections = header.sections().ok_or_else( || Error::Corrupt( "section sizes overflow".to_string() ) )?;
+        if bytes.len() < sections.end {
+            return Err( Error::Truncated { expected: sections.end, found: bytes.len() } );
+        }
+
+        let ( node_count, edge_count ) = ( header.node_count as usize, header.edge_count as usize );
+        let view = Self {
+            header,
+            offsets: cast( bytes, sections.offsets, node_count + 1 ),
+            targets: cast( bytes, sections.targets, edge_count ),
+            ids: cast( bytes, sections.ids, node_count ),
+            nodes: cast( bytes, sections.nodes, node_count ),
+            edges: cast( bytes, sections.edges, edge_count )
+        };
+        if view.offsets[ 0 ] != 0 || view.offsets[ node_count ] != edge_count as u64 || view.offsets.windows( 2 ).any( |pair| pair[ 0 ] > pair[ 1 ] ) {
+            return Err( Error::Corrupt( "offsets are not monotonic".to_string() ) );
+        }
+        if view.targets.iter().any( |target| *target >= node_count as u64 ) {
+            return Err( Error::Corrupt( "edge target out of range".to_string() ) );
+        }
+        if view.ids.windows( 2 ).any( |pair| pair[ 0 ] >= pair[ 1 ] ) {
+            return Err( Error::Corrupt( "ids are not sorted".to_string() ) );
+        }
+        Ok( view )
+    }
+
+    /// Like [`SnapshotView::new`], also checking that the snapshot has the directedness of `D`.
+    pub fn with_direction<D>( bytes: &'a [ u8 ] ) -> Result<Self, Error>
+    where
+        D: Directional
+    {
+        let view = Self::new( bytes )?;
+        let kind = |directed| if directed { "directed" } else { "undirected" };
+        if view.is_directed() != D::DIRECTED {
+            return Err( Error::Direction { expected: kind( D::DIRECTED ), found: kind( view.is_directed() ) } );
+        }
+        Ok( view )
+    }
+
+    pub fn header( &self ) -> &Header {
+        &self.header
+    }
+
+    pub fn is_directed( &self ) -> bool {
+        self.header.is_directed()
+    }
+
+    pub fn order( &self ) -> usize {
+        self.ids.len()
+    }
+
+    /// The number of stored edges, each direction of an undirected edge counting once.
+    pub fn size( &self ) -> usize {
+        self.targets.len()
+    }
+
+    pub fn index_of( &self, id: &I ) -> Option<usize> {
+        self.ids.binary_search( id ).ok()
+    }
+
+    pub fn id( &self, index: usize ) -> &'a I {
+        &self.ids[ index ]
+    }
+
+    pub fn node_at( &self, index: usize ) -> &'a N {
+        &self.nodes[ index ]
+    }
+
+    /// The `( target index, edge )` pairs of the node at `index`, sorted by target.
+    pub fn edges_at( &self, index: usize ) -> impl Iterator<Item = ( usize, &'a E )> {
+        let ( targets, edges ) = ( self.targets, self.edges );
+        let range = self.offsets[ index ] as usize..self.offsets[ index + 1 ] as usize;
+        targets[ range.clone() ].iter().map( |target| *target as usize ).zip( &edges[ range ] )
+    }
+}
+
+impl<I, N, E> GetNode<I, N> for SnapshotView<'_, I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    fn node( &self, id: I ) -> Option<&N> {
+        self.index_of( &id ).map( |index| &self.nodes[ index ] )
+    }
+}
+
+impl<I, N, E> GetEdge<I, E> for SnapshotView<'_, I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    fn edge( &self, id1: I, id2: I ) -> Option<&E> {
+        let ( from, to ) = ( self.index_of( &id1 )?, self.index_of( &id2 )? as u64 );
+        let range = self.offsets[ from ] as usize..self.offsets[ from + 1 ] as usize;
+        let position = self.targets[ range.clone() ].binary_search( &to ).ok()?;
+        Some( &self.edges[ range.start + position ] )
+    }
+}
+
+impl<I, N, E> IterNodeIds<I, N> for SnapshotView<'_, I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, &'a N )>
+    where
+        N: 'a
+    {
+        self.ids.iter().copied().zip( self.nodes )
+    }
+}
+
+impl<I, N, E> IterEdgeIds<I, E> for SnapshotView<'_, I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, I, &'a E )>
+    where
+        E: 'a
+    {
+        ( 0..self.order() ).flat_map( move |index| {
+            self.edges_at( index ).map( move |( target, edge )| ( self.ids[ index ], self.ids[ target ], edge ) )
+        })
+    }
+}
+
+#[cfg(feature = "mmap")]
+type Items<I, N, E> = fn() -> ( I, N, E );
+
+/// A snapshot file mapped into memory, enabled by the `mmap` feature.
+///
+/// The pages are read on demand, so opening a snapshot costs one validation pass over its offsets, targets and ids.
+/// The file must not be modified while it is mapped.
+///
+#[cfg(feature = "mmap")]
+#[derive( Debug )]
+pub struct MappedSnapshot<I, N, E> {
+    map: memmap2::Mmap,
+    _marker: std::marker::PhantomData<Items<I, N, E>>
+}
+
+#[cfg(feature = "mmap")]
+impl<I, N, E> MappedSnapshot<I, N, E>
+where
+    I: Pod + Ord,
+    N: Pod,
+    E: Pod
+{
+    pub fn open<P>( path: P ) -> Result<Self, Error>
+    where
+        P: AsRef<std::path::Path>
+    {
+        let file = std::fs::File::open( path )?;
+        // SAFETY: the mapping is read-only, and callers are told not to modify the file while it is mapped.
+        let map = unsafe { memmap2::Mmap::map( &file )? };
+        SnapshotView::<I, N, E>::new( &map )?;
+        Ok( Self { map, _marker: std::marker::PhantomData } )
+    }
+
+    pub fn view( &self ) -> SnapshotView<'_, I, N, E> {
+        SnapshotView::new( &self.map ).expect( "Snapshot was validated when it was opened" )
+    }
+}
+
+/// Bytes of a snapshot in a buffer aligned for [`SnapshotView`], for snapshots read without a memory map.
+#[derive( Debug, Clone, Default )]
+pub struct AlignedBytes {
+    blocks: Vec<Block>,
+    len: usize
+}
+
+#[repr(align(64))]
+#[derive( Debug, Clone, Copy )]
+struct Block( [ u8; SECTION_ALIGN ] );
+
+impl AlignedBytes {
+    pub fn new( bytes: &[ u8 ] ) -> Self {
+        let mut blocks = vec![ Block( [ 0; SECTION_ALIGN ] ); bytes.len().div_ceil( SECTION_ALIGN ) ];
+        for ( block, chunk ) in blocks.iter_mut().zip( bytes.chunks( SECTION_ALIGN ) ) {
+            block.0[ ..chunk.len() ].copy_from_slice( chunk );
+        }
+        Self { blocks, len: bytes.len() }
+    }
+
+    pub fn as_bytes( &self ) -> &[ u8 ] {
+        // SAFETY: the blocks are contiguous and hold at least `len` bytes.
+        unsafe { slice::from_raw_parts( self.blocks.as_ptr() as *const u8, self.len ) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ BTreeRepr, HashRepr },
+        traits::{ AddNode, AddEdge, GetNode, GetEdge, IterNodeIds, IterEdgeIds }
+    };
+
+    use super::{ AlignedBytes, Error, SnapshotView, write };
+
+    fn sample() -> Graph<Directed, Cyclic, HashRepr<u32, [ f32; 2 ], u16>> {
+        let mut graph = Graph::<Directed, Cyclic, HashRepr<u32, [ f32; 2 ], u16>>::default();
+        for id in [ 40, 10, 30, 20 ] {
+            graph.add_node( id, [ id as f32, 0.5 ] );
+        }
+        for ( id1, id2, edge ) in [ ( 10, 20, 1 ), ( 10, 40, 2 ), ( 30, 10, 3 ), ( 40, 40, 4 ) ] {
+            graph.add_edge( id1, id2, edge );
+        }
+        graph
+    }
+
+    #[test]
+    fn test_snapshot_view() {
+        let graph = sample();
+        let mut bytes = Vec::new();
+        write( &graph, &mut bytes ).unwrap();
+        let bytes = AlignedBytes::new( &bytes );
+        let view = SnapshotView::<u32, [ f32; 2 ], u16>::with_direction::<Directed>( bytes.as_bytes() ).unwrap();
+
+        assert_eq!( ( view.order(), view.size() ), ( 4, 4 ) );
+        assert_eq!( view.iter_node_ids().map( |( id, _ )| id ).collect::<Vec<_>>(), vec![ 10, 20, 30, 40 ] );
+        assert_eq!( view.node( 30 ), Some( &[ 30.0, 0.5 ] ) );
+        assert_eq!( view.edge( 10, 40 ), Some( &2 ) );
+        assert_eq!( view.edge( 40, 10 ), None );
+        assert_eq!( view.edges_at( 0 ).collect::<Vec<_>>(), vec![ ( 1, &1 ), ( 3, &2 ) ] );
+        assert_eq!(
+            view.iter_edge_ids().collect::<Vec<_>>(),
+            vec![ ( 10, 20, &1 ), ( 10, 40, &2 ), ( 30, 10, &3 ), ( 40, 40, &4 ) ]
+        );
+
+        let mut sorted = Graph::<Directed, Cyclic, BTreeRepr<u32, [ f32; 2 ], u16>>::default();
+        for ( id, node ) in graph.iter_node_ids() {
+            sorted.add_node( id, *node );
+        }
+        for ( id1, id2, edge ) in graph.iter_edge_ids() {
+            sorted.add_edge( id1, id2, *edge );
+        }
+        let mut sorted_bytes = Vec::new();
+        write( &sorted, &mut sorted_bytes ).unwrap();
+        assert_eq!( sorted_bytes, bytes.as_bytes() );
+    }
+
+    #[test]
+    fn test_snapshot_validation() {
+        let mut bytes = Vec::new();
+        write( &sample(), &mut bytes ).unwrap();
+
+        let view = |bytes: &[ u8 ]| SnapshotView::<u32, [ f32; 2 ], u16>::new( AlignedBytes::new( bytes ).as_bytes() ).map( |_| () );
+        assert!( matches!( SnapshotView::<u32, [ f32; 2 ], u16>::with_direction::<Undirected>( AlignedBytes::new( &bytes ).as_bytes() ), Err( Error::Direction { .. } ) ) );
+        assert!( matches!( SnapshotView::<u32, [ f32; 2 ], u32>::new( AlignedBytes::new( &bytes ).as_bytes() ), Err( Error::PayloadMismatch { section: "edges", .. } ) ) );
+        assert!( matches!( view( &bytes[ ..bytes.len() - 1 ] ), Err( Error::Truncated { .. } ) ) );
+
+        let mut corrupt = bytes.clone();
+        corrupt[ 0 ] = b'X';
+        assert!( matches!( view( &corrupt ), Err( Error::InvalidMagic ) ) );
+        let mut corrupt = bytes.clone();
+        corrupt[ 8 ] = 2;
+        assert!( matches!( view( &corrupt ), Err( Error::UnsupportedVersion( 2 ) ) ) );
+        let mut corrupt = bytes.clone();
+        // The first target, at the start of the second section.
+        corrupt[ 128 ] = 9;
+        assert!( matches!( view( &corrupt ), Err( Error::Corrupt( _ ) ) ) );
+    }
+
+    #[test]
+    fn test_snapshot_oversized_header() {
+        let mut bytes = Vec::new();
+        write( &sample(), &mut bytes ).unwrap();
+        bytes.resize( 4096, 0 );
+        let view = |bytes: &[ u8 ]| SnapshotView::<u32, [ f32; 2 ], u16>::new( AlignedBytes::new( bytes ).as_bytes() ).map( |_| () );
+
+        // With 4-byte ids the sections of 2^62 nodes wrap around to a size that fits the buffer.
+        let mut oversized = bytes.clone();
+        oversized[ 16..24 ].copy_from_slice( &( 1u64 << 62 ).to_ne_bytes() );
+        assert!( matches!( view( &oversized ), Err( Error::Corrupt( _ ) ) ) );
+
+        let mut oversized = bytes.clone();
+        oversized[ 24..32 ].copy_from_slice( &u64::MAX.to_ne_bytes() );
+        assert!( matches!( view( &oversized ), Err( Error::Corrupt( _ ) ) ) );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_snapshot_mmap() {
+        let path = std::env::temp_dir().join( format!( "graph-snapshot-{}.bin", std::process::id() ) );
+        write( &sample(), std::fs::File::create( &path ).unwrap() ).unwrap();
+        let mapped = super::MappedSnapshot::<u32, [ f32; 2 ], u16>::open( &path ).unwrap();
+        assert_eq!( mapped.view().edge( 30, 10 ), Some( &3 ) );
+        drop( mapped );
+        std::fs::remove_file( path ).unwrap();
+    }
+}
@@ -1,12 +1,7 @@
 // Copyright 2024 Bewusstsein Labs
 
 //: Standard
-use std::{
-    cmp::{ Eq, Ord, PartialEq },
-    collections::{ BTreeMap, HashMap },
-    marker::PhantomData,
-    ops::{ Deref, DerefMut, Not }
-};
+use std::ops::Not;
 
 use crate::{
     graph::{
@@ -21,17 +16,15 @@ use crate::{
         GetEdge,
         GetEdgeMut,
         AddNode,
-        RemoveNode,
         AddEdge,
-        RemoveEdge,
-        ContainsNode,
-        ContainsEdge,
-        ClearNodes,
         ClearEdges,
         IterNodes,
         IterNodesMut,
         IterEdges,
         IterEdgesMut,
+        IterNodeIds,
+        IterEdgeIds,
+        IterAdjacent,
         IterPair,
         IterPairMut,
         IsComplete,
@@ -98,6 +91,58 @@ where
     }
 }
 
+impl<D, C, N, E, const SIZE: usize> AddNode<usize, N> for Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    /// Sets the data of the node `id`; every node of a static graph always exists.
+    fn add_node( &mut self, id: usize, node: N ) {
+        if let Some( slot ) = self.0.0.get_mut( id ) {
+            slot.node = node;
+        }
+    }
+}
+
+impl<D, C, N, E, const SIZE: usize> AddEdge<usize, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    fn add_edge( &mut self, id1: usize, id2: usize, edge: E ) {
+        if let Some( slot ) = self.0.0.get_mut( id1 ).and_then( |node| node.adjs.get_mut( id2 ) ) {
+            *slot = Some( edge );
+        }
+    }
+}
+
+impl<D, C, N, E, const SIZE: usize> Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional + Clone,
+    C: Cyclical + Clone,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    /// The same nodes, with every edge slot off the diagonal flipped: present edges are removed and missing ones are
+    /// added holding `E::default()`.
+    pub fn complement( &self ) -> Self {
+        let mut flipped = self.clone();
+        for ( id1, node ) in flipped.0.0.iter_mut().enumerate() {
+            for ( id2, slot ) in node.adjs.iter_mut().enumerate() {
+                *slot = match slot {
+                    None if id1 != id2 => Some( E::default() ),
+                    _ => None
+                };
+            }
+        }
+        flipped
+    }
+}
+
 impl<D, C, N, E, const SIZE: usize> ClearEdges for Graph<D, C, StaticRepr<N, E, SIZE>>
 where
     D: Directional,
@@ -117,7 +162,10 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_nodes( &self ) -> impl Iterator<Item = Option<&N>> {
+    fn iter_nodes<'a>( &'a self ) -> impl Iterator<Item = Option<&'a N>>
+    where
+        N: 'a
+    {
         self.0.0.iter().map( |pair| Some( &pair.node ) )
     }
 }
@@ -129,7 +177,10 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_nodes_mut( &mut self ) -> impl Iterator<Item = Option<&mut N>> {
+    fn iter_nodes_mut<'a>( &'a mut self ) -> impl Iterator<Item = Option<&'a mut N>>
+    where
+        N: 'a
+    {
         self.0.0.iter_mut().map( |pair| Some( &mut pair.node ) )
     }
 }
@@ -141,7 +192,10 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_edges( &self, id: usize ) -> impl Iterator<Item = Option<&E>> {
+    fn iter_edges<'a>( &'a self, id: usize ) -> impl Iterator<Item = Option<&'a E>>
+    where
+        E: 'a
+    {
         self.0.0[ id ].adjs.iter().map( |edge| edge.as_ref() )
     }
 }
@@ -153,11 +207,46 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_edges_mut( &mut self, id: usize ) -> impl Iterator<Item = Option<&mut E>> {
+    fn iter_edges_mut<'a>( &'a mut self, id: usize ) -> impl Iterator<Item = Option<&'a mut E>>
+    where
+        E: 'a
+    {
         self.0.0[ id ].adjs.iter_mut().map( |edge| edge.as_mut() )
     }
 }
 
+impl<D, C, N, E, const SIZE: usize> IterNodeIds<usize, N> for Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( usize, &'a N )>
+    where
+        N: 'a
+    {
+        self.0.0.iter().enumerate().map( |( id, node )| ( id, &node.node ) )
+    }
+}
+
+impl<D, C, N, E, const SIZE: usize> IterEdgeIds<usize, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( usize, usize, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.iter().enumerate().flat_map( |( id1, node )| node.adjs.iter().enumerate()
+            .filter_map( move |( id2, edge )| edge.as_ref().map( |edge| ( id1, id2, edge ) ) )
+        )
+    }
+}
+
 impl<'a, D, C, N, E, const SIZE: usize> IterPair<'a, N, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
 where
     D: Directional,
@@ -165,7 +254,7 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_pair( &self ) -> impl Iterator<Item = ( Option<&N>, impl Iterator<Item = Option<&E>> )> {
+    fn iter_pair( &'a self ) -> impl Iterator<Item = ( Option<&'a N>, impl Iterator<Item = Option<&'a E>> )> {
         self.0.0.iter().map( |node| ( Some( &node.node ), node.adjs.iter().map( |edge| edge.as_ref() ) ) )
     }
 }
@@ -177,11 +266,28 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn iter_pair_mut( &mut self ) -> impl Iterator<Item = ( Option<&mut N>, impl Iterator<Item = Option<&mut E>> )> {
+    fn iter_pair_mut( &'a mut self ) -> impl Iterator<Item = ( Option<&'a mut N>, impl Iterator<Item = Option<&'a mut E>> )> {
         self.0.0.iter_mut().map( |pair| ( Some( &mut pair.node ), pair.adjs.iter_mut().map( |edge| edge.as_mut() ) ) )
     }
 }
 
+impl<D, C, N, E, const SIZE: usize> IterAdjacent<usize, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
+where
+    D: Directional,
+    C: Cyclical,
+    N: Clone + Copy + Default + std::fmt::Debug,
+    E: Clone + Copy + Default + std::fmt::Debug
+{
+    fn iter_adjacent<'a>( &'a self, id: usize ) -> impl Iterator<Item = ( usize, &'a E )>
+    where
+        E: 'a
+    {
+        self.0.0.get( id ).into_iter().flat_map( |node| node.adjs.iter().enumerate()
+            .filter_map( |( id, edge )| edge.as_ref().map( |edge| ( id, edge ) ) )
+        )
+    }
+}
+
 impl<D, C, N, E, const SIZE: usize> IsComplete<usize, N, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
 where
     D: Directional,
@@ -218,7 +324,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_trivial( &self ) -> bool {
-        self.0.data.len() == 1 && self.data().values().next().is_some_and( |neighbors| neighbors.adjacencies().is_empty())
+        SIZE == 1 && self.is_empty()
     }
 }
 
@@ -230,7 +336,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_null( &self ) -> bool {
-        self.0.data.is_empty()
+        SIZE == 0
     }
 }
 
@@ -242,7 +348,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_child_node( &self, node_1: usize ) -> bool {
-        self.0.data.contains_node( node_1 )
+        node_1 < SIZE
     }
 }
 
@@ -253,12 +359,10 @@ where
     N: Clone + Copy + Default + std::fmt::Debug,
     E: Clone + Copy + Default + std::fmt::Debug
 {
-    fn is_subgraph(&self, subgraph: &Self) -> bool {
-        subgraph.0.data.iter().all( |(node, neighbors)| {
-            self.0.data.get( node ).is_some_and( |graph_node| {
-                neighbors.adjacencies().keys().all( |key| graph_node.adjacencies().contains_key( key ) )
-            })
-        })
+    fn is_subgraph( &self, subgraph: &Self ) -> bool {
+        subgraph.0.0.iter().zip( self.0.0.iter() ).all( |( sub, node )| sub.adjs.iter().zip( node.adjs.iter() )
+            .all( |( sub, edge )| sub.is_none() || edge.is_some() )
+        )
     }
 }
 
@@ -270,7 +374,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_proper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data != subgraph.0.data && self.is_subgraph( subgraph )
+        self.is_subgraph( subgraph ) && self.size() != subgraph.size()
     }
 }
 
@@ -282,10 +386,11 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_improper_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data == subgraph.0.data
+        self.is_subgraph( subgraph ) && self.size() == subgraph.size()
     }
 }
 
+/// Every node exists in a static graph, so each subgraph spans it.
 impl<D, C, N, E, const SIZE: usize> IsSpanningSubgraph<usize, N, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
 where
     D: Directional,
@@ -294,7 +399,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn is_spanning_subgraph( &self, subgraph: &Self ) -> bool {
-        self.0.data.len() == subgraph.data().len() && self.is_subgraph( subgraph )
+        self.is_subgraph( subgraph )
     }
 }
 
@@ -306,9 +411,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn are_adjacent_nodes( &self, node_1: usize, node_2: usize ) -> bool {
-        self.is_child_node( node_1.clone() )
-            && self.is_child_node( node_2.clone() )
-            && self.0.data.get( &node_1 ).unwrap().adjacencies().contains_key( &node_2 )
+        self.edge( node_1, node_2 ).is_some()
     }
 }
 
@@ -320,7 +423,7 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn are_adjacent_edges( &self, node_1: usize, node_2: usize, node_3: usize ) -> bool {
-        self.are_adjacent_nodes( node_1, node_2.clone() )
+        self.are_adjacent_nodes( node_1, node_2 )
             && self.are_adjacent_nodes( node_2, node_3 )
     }
 }
@@ -333,10 +436,11 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn order( &self ) -> usize {
-        self.0.data.len()
+        SIZE
     }
 }
 
+/// Undirected edges are stored in both directions and count once.
 impl<D, C, N, E, const SIZE: usize> Size<usize, N, E> for Graph<D, C, StaticRepr<N, E, SIZE>>
 where
     D: Directional,
@@ -345,6 +449,10 @@ where
     E: Clone + Copy + Default + std::fmt::Debug
 {
     fn size( &self ) -> usize {
-        self.0.data.values().map( |neighbors| neighbors.adjacencies().len() ).sum::<usize>() / 2
+        let edges = self.0.0.iter().map( |node| node.adjs.iter().filter( |edge| edge.is_some() ).count() ).sum::<usize>();
+        match D::DIRECTED {
+            true => edges,
+            false => ( edges + self.0.0.iter().enumerate().filter( |( id, node )| node.adjs[ *id ].is_some() ).count() ) / 2
+        }
     }
 }
@@ -0,0 +1,543 @@
+// Copyright 2024 Bewusstsein Labs
+
+//! Plain-text graph formats used by public datasets: whitespace separated edge lists (SNAP), METIS adjacency lists
+//! and Matrix Market coordinate files (SuiteSparse).
+//!
+//! Readers build any graph that can add nodes and edges, in practice one backed by [`DynRepr`](crate::graph_repr::DynRepr)
+//! or [`HashRepr`](crate::graph_repr::HashRepr). Weights are converted to and from node and edge data through
+//! [`Weight`]. Undirected graphs store both directions of an edge, and writers emit each edge once.
+//!
+
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    io::{ self, BufRead, Write },
+    str::FromStr
+};
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        AddNode,
+        AddEdge,
+        IterNodeIds,
+        IterEdgeIds
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("IO Error: {0}")]
+    Io( #[from] io::Error ),
+    #[error("Line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Unsupported: {0}")]
+    Unsupported( String )
+}
+
+/// Node or edge data read from and written as an optional numeric weight.
+pub trait Weight: Sized {
+    fn from_weight( weight: Option<f64> ) -> Self;
+    fn weight( &self ) -> Option<f64>;
+}
+
+/// Unweighted data, weights are ignored when reading.
+impl Weight for () {
+    fn from_weight( _: Option<f64> ) -> Self {}
+
+    fn weight( &self ) -> Option<f64> {
+        None
+    }
+}
+
+macro_rules! impl_weight {
+    ( $( $ty:ty ),* ) => { $(
+        /// Missing weights read as `1`, the convention of the formats.
+        impl Weight for $ty {
+            fn from_weight( weight: Option<f64> ) -> Self {
+                weight.unwrap_or( 1.0 ) as $ty
+            }
+
+            fn weight( &self ) -> Option<f64> {
+                Some( *self as f64 )
+            }
+        }
+    )* };
+}
+
+impl_weight!( f32, f64, i32, i64, u32, u64, usize );
+
+struct Lines<B> {
+    reader: B,
+    line: usize,
+    buffer: String
+}
+
+impl<B> Lines<B>
+where
+    B: BufRead
+{
+    fn new( reader: B ) -> Self {
+        Self { reader, line: 0, buffer: String::new() }
+    }
+
+    /// The next line that is not blank or a comment starting with one of `comments`, with its line number.
+    fn next( &mut self, comments: &[ char ] ) -> Result<Option<( usize, &str )>, Error> {
+        loop {
+            self.buffer.clear();
+            if self.reader.read_line( &mut self.buffer )? == 0 {
+                return Ok( None );
+            }
+            self.line += 1;
+            let trimmed = self.buffer.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with( comments ) {
+                return Ok( Some( ( self.line, self.buffer.trim() ) ) );
+            }
+        }
+    }
+
+    fn error<T>( &self, message: impl Into<String> ) -> Result<T, Error> {
+        Err( Error::Parse { line: self.line, message: message.into() } )
+    }
+}
+
+fn parse<T>( line: usize, field: Option<&str>, name: &str ) -> Result<T, Error>
+where
+    T: FromStr
+{
+    let field = field.ok_or_else( || Error::Parse { line, message: format!( "Missing {}", name ) } )?;
+    field.parse().map_err( |_| Error::Parse { line, message: format!( "Invalid {} '{}'", name, field ) } )
+}
+
+/// Edges once per pair for undirected graphs, in both stored directions for directed ones.
+fn unique_edges<'a, D, I, E, It>( edges: It ) -> Vec<( I, I, &'a E )>
+where
+    D: Directional,
+    I: Clone + Ord,
+    E: 'a,
+    It: Iterator<Item = ( I, I, &'a E )>
+{
+    let mut written = BTreeSet::new();
+    edges.filter( |( id1, id2, _ )| {
+        if D::DIRECTED || written.contains( &( id2.clone(), id1.clone() ) ) {
+            return D::DIRECTED;
+        }
+        written.insert( ( id1.clone(), id2.clone() ) )
+    }).collect()
+}
+
+/// Nodes sorted by id, for the formats that number nodes by their 1-based position.
+fn indices<I, N>( nodes: impl Iterator<Item = ( I, N )> ) -> Vec<( I, N )>
+where
+    I: Ord
+{
+    let mut nodes: Vec<_> = nodes.collect();
+    nodes.sort_by( |( id1, _ ), ( id2, _ )| id1.cmp( id2 ) );
+    nodes
+}
+
+/// The 1-based position of `id`, failing for an edge that leads to a node missing from the graph.
+fn position<I, N>( nodes: &[ ( I, N ) ], id: &I ) -> Result<usize, Error>
+where
+    I: Ord
+{
+    nodes.binary_search_by( |( other, _ )| other.cmp( id ) )
+        .map( |index| index + 1 )
+        .map_err( |_| Error::Unsupported( "an edge to a node that is not in the graph".to_string() ) )
+}
+
+/// Reads lines of `source target [weight]`, skipping `#` and `%` comments. Nodes are added as they first appear.
+pub fn read_edge_list<D, C, R, I, N, E, B>( reader: B ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord + FromStr,
+    N: Default,
+    E: Weight,
+    Graph<D, C, R>: Default + AddNode<I, N> + AddEdge<I, E>,
+    B: BufRead
+{
+    let mut graph = Graph::default();
+    let mut nodes = BTreeSet::new();
+    let mut lines = Lines::new( reader );
+    while let Some( ( line, text ) ) = lines.next( &[ '#', '%' ] )? {
+        let mut fields = text.split_whitespace();
+        let source: I = parse( line, fields.next(), "source" )?;
+        let target: I = parse( line, fields.next(), "target" )?;
+        let weight = fields.next().map( |weight| parse::<f64>( line, Some( weight ), "weight" ) ).transpose()?;
+        for id in [ &source, &target ] {
+            if nodes.insert( id.clone() ) {
+                graph.add_node( id.clone(), N::default() );
+            }
+        }
+        if !D::DIRECTED {
+            graph.add_edge( target.clone(), source.clone(), E::from_weight( weight ) );
+        }
+        graph.add_edge( source, target, E::from_weight( weight ) );
+    }
+    Ok( graph )
+}
+
+/// Writes one `source target [weight]` line per edge. Isolated nodes are not written.
+pub fn write_edge_list<D, C, R, I, E, W>( graph: &Graph<D, C, R>, mut writer: W ) -> Result<(), Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord + Display,
+    E: Weight,
+    Graph<D, C, R>: IterEdgeIds<I, E>,
+    W: Write
+{
+    for ( id1, id2, edge ) in unique_edges::<D, _, _, _>( graph.iter_edge_ids() ) {
+        match edge.weight() {
+            Some( weight ) => writeln!( writer, "{} {} {}", id1, id2, weight )?,
+            None => writeln!( writer, "{} {}", id1, id2 )?
+        }
+    }
+    Ok( writer.flush()? )
+}
+
+/// Reads a METIS graph file. Nodes are numbered from 0, vertex weights become node data (the first constraint
+/// only) and edge weights edge data.
+pub fn read_metis<D, C, R, N, E, B>( reader: B ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Weight,
+    E: Weight,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>,
+    B: BufRead
+{
+    let mut lines = Lines::new( reader );
+    let Some( ( line, header ) ) = lines.next( &[ '%' ] )? else {
+        return lines.error( "Missing header" );
+    };
+    let mut fields = header.split_whitespace();
+    let order: usize = parse( line, fields.next(), "node count" )?;
+    let size: usize = parse( line, fields.next(), "edge count" )?;
+    let format = fields.next().unwrap_or( "0" );
+    let constraints: usize = fields.next().map_or( Ok( 1 ), |field| parse( line, Some( field ), "constraint count" ) )?;
+    let flag = |position: usize| format.len() > position && format.as_bytes()[ format.len() - 1 - position ] == b'1';
+    let ( edge_weights, node_weights, node_sizes ) = ( flag( 0 ), flag( 1 ), flag( 2 ) );
+
+    let mut graph = Graph::default();
+    let mut adjacencies = Vec::with_capacity( order );
+    // Blank lines are isolated nodes, so lines are read directly instead of skipping them.
+    for id in 0..order {
+        let mut text = String::new();
+        loop {
+            text.clear();
+            if lines.reader.read_line( &mut text )? == 0 {
+                return lines.error( format!( "Expected {} node lines but found {}", order, id ) );
+            }
+            lines.line += 1;
+            if !text.trim_start().starts_with( '%' ) {
+                break;
+            }
+        }
+        let mut fields = text.split_whitespace();
+        if node_sizes {
+            parse::<u64>( lines.line, fields.next(), "node size" )?;
+        }
+        let mut weight = None;
+        if node_weights {
+            for constraint in 0..constraints {
+                let value: f64 = parse( lines.line, fields.next(), "node weight" )?;
+                if constraint == 0 {
+                    weight = Some( value );
+                }
+            }
+        }
+        graph.add_node( id, N::from_weight( weight ) );
+        let mut neighbors = Vec::new();
+        while let Some( neighbor ) = fields.next() {
+            let neighbor: usize = parse( lines.line, Some( neighbor ), "neighbor" )?;
+            if neighbor == 0 || neighbor > order {
+                return lines.error( format!( "Neighbor {} is out of range", neighbor ) );
+            }
+            let weight = if edge_weights { Some( parse::<f64>( lines.line, fields.next(), "edge weight" )? ) } else { None };
+            neighbors.push( ( neighbor - 1, weight ) );
+        }
+        adjacencies.push( neighbors );
+    }
+    let count: usize = adjacencies.iter().map( Vec::len ).sum();
+    if count != 2 * size {
+        return Err( Error::Parse { line: 1, message: format!( "Header declares {} edges but the lists hold {} adjacencies", size, count ) } );
+    }
+    for ( id, neighbors ) in adjacencies.into_iter().enumerate() {
+        for ( neighbor, weight ) in neighbors {
+            graph.add_edge( id, neighbor, E::from_weight( weight ) );
+        }
+    }
+    Ok( graph )
+}
+
+/// Writes an undirected graph as a METIS file, numbering nodes by the order of their ids. Fails for self-loops, which
+/// the format cannot hold.
+///
+pub fn write_metis<D, C, R, I, N, E, W>( graph: &Graph<D, C, R>, mut writer: W ) -> Result<(), Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    N: Weight,
+    E: Weight,
+    Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+    W: Write
+{
+    if D::DIRECTED {
+        return Err( Error::Unsupported( "METIS files hold undirected graphs".to_string() ) );
+    }
+    let nodes = indices( graph.iter_node_ids() );
+    let mut adjacencies = vec![ Vec::new(); nodes.len() ];
+    for ( id1, id2, edge ) in graph.iter_edge_ids() {
+        if id1 == id2 {
+            return Err( Error::Unsupported( "self-loops in METIS files".to_string() ) );
+        }
+        adjacencies[ position( &nodes, &id1 )? - 1 ].push( ( position( &nodes, &id2 )?, edge.weight() ) );
+    }
+    let node_weights = nodes.iter().any( |( _, node )| node.weight().is_some() );
+    let edge_weights = adjacencies.iter().flatten().any( |( _, weight )| weight.is_some() );
+    let size = adjacencies.iter().map( Vec::len ).sum::<usize>() / 2;
+    match ( node_weights, edge_weights ) {
+        ( false, false ) => writeln!( writer, "{} {}", nodes.len(), size )?,
+        ( node_weights, edge_weights ) => writeln!( writer, "{} {} {}{}", nodes.len(), size, node_weights as u8, edge_weights as u8 )?
+    }
+    for ( ( _, node ), neighbors ) in nodes.iter().zip( &mut adjacencies ) {
+        neighbors.sort_by_key( |( neighbor, _ )| *neighbor );
+        let mut fields = Vec::new();
+        if node_weights {
+            fields.push( node.weight().unwrap_or( 1.0 ).to_string() );
+        }
+        for ( neighbor, weight ) in neighbors.iter() {
+            fields.push( neighbor.to_string() );
+            if edge_weights {
+                fields.push( weight.unwrap_or( 1.0 ).to_string() );
+            }
+        }
+        writeln!( writer, "{}", fields.join( " " ) )?;
+    }
+    Ok( writer.flush()? )
+}
+
+/// Reads a square Matrix Market coordinate file, each entry `( i, j )` being an edge from node `i - 1` to `j - 1`.
+///
+/// `symmetric` and `skew-symmetric` matrices store one triangle, so their entries also add the mirrored edge, as do
+/// all entries when reading into an undirected graph. Pattern matrices have no weights; complex ones are rejected.
+///
+pub fn read_matrix_market<D, C, R, N, E, B>( reader: B ) -> Result<Graph<D, C, R>, Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    N: Default,
+    E: Weight,
+    Graph<D, C, R>: Default + AddNode<usize, N> + AddEdge<usize, E>,
+    B: BufRead
+{
+    let mut lines = Lines::new( reader );
+    lines.reader.read_line( &mut lines.buffer )?;
+    lines.line = 1;
+    let banner: Vec<String> = lines.buffer.split_whitespace().map( str::to_ascii_lowercase ).collect();
+    if banner.len() != 5 || banner[ 0 ] != "%%matrixmarket" || banner[ 1 ] != "matrix" {
+        return lines.error( "Expected a '%%MatrixMarket matrix' banner" );
+    }
+    if banner[ 2 ] != "coordinate" {
+        return Err( Error::Unsupported( format!( "{} matrices", banner[ 2 ] ) ) );
+    }
+    let pattern = match banner[ 3 ].as_str() {
+        "real" | "integer" | "double" => false,
+        "pattern" => true,
+        field => return Err( Error::Unsupported( format!( "{} entries", field ) ) )
+    };
+    let mirrored = match banner[ 4 ].as_str() {
+        "general" => false,
+        "symmetric" | "skew-symmetric" | "hermitian" => true,
+        symmetry => return Err( Error::Unsupported( format!( "{} matrices", symmetry ) ) )
+    };
+    let skew = banner[ 4 ] == "skew-symmetric";
+
+    let Some( ( line, size ) ) = lines.next( &[ '%' ] )? else {
+        return lines.error( "Missing size line" );
+    };
+    let mut fields = size.split_whitespace();
+    let rows: usize = parse( line, fields.next(), "row count" )?;
+    let columns: usize = parse( line, fields.next(), "column count" )?;
+    let entries: usize = parse( line, fields.next(), "entry count" )?;
+    if rows != columns {
+        return Err( Error::Unsupported( format!( "a {} by {} matrix, graphs need a square one", rows, columns ) ) );
+    }
+
+    let mut graph = Graph::default();
+    for id in 0..rows {
+        graph.add_node( id, N::default() );
+    }
+    for entry in 0..entries {
+        let Some( ( line, text ) ) = lines.next( &[ '%' ] )? else {
+            return lines.error( format!( "Expected {} entries but found {}", entries, entry ) );
+        };
+        let mut fields = text.split_whitespace();
+        let row: usize = parse( line, fields.next(), "row" )?;
+        let column: usize = parse( line, fields.next(), "column" )?;
+        if row == 0 || row > rows || column == 0 || column > columns {
+            return Err( Error::Parse { line, message: format!( "Entry ( {}, {} ) is out of range", row, column ) } );
+        }
+        let weight = if pattern { None } else { Some( parse::<f64>( line, fields.next(), "value" )? ) };
+        let ( source, target ) = ( row - 1, column - 1 );
+        if ( mirrored || !D::DIRECTED ) && source != target {
+            graph.add_edge( target, source, E::from_weight( if skew { weight.map( |weight| -weight ) } else { weight } ) );
+        }
+        graph.add_edge( source, target, E::from_weight( weight ) );
+    }
+    Ok( graph )
+}
+
+/// Writes a Matrix Market coordinate file, `general` for directed graphs and the lower triangle of a `symmetric`
+/// matrix for undirected ones. Nodes are numbered by the order of their ids; unweighted graphs are written as
+/// `pattern` matrices.
+///
+pub fn write_matrix_market<D, C, R, I, N, E, W>( graph: &Graph<D, C, R>, mut writer: W ) -> Result<(), Error>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + Ord,
+    E: Weight,
+    Graph<D, C, R>: IterNodeIds<I, N> + IterEdgeIds<I, E>,
+    W: Write
+{
+    let nodes = indices( graph.iter_node_ids() );
+    let mut entries: Vec<( usize, usize, Option<f64> )> = unique_edges::<D, _, _, _>( graph.iter_edge_ids() ).into_iter()
+        .map( |( id1, id2, edge )| {
+            let ( row, column ) = ( position( &nodes, &id1 )?, position( &nodes, &id2 )? );
+            // The lower triangle, as the format requires of symmetric matrices.
+            let ( row, column ) = if D::DIRECTED { ( row, column ) } else { ( row.max( column ), row.min( column ) ) };
+            Ok( ( row, column, edge.weight() ) )
+        })
+        .collect::<Result<_, Error>>()?;
+    entries.sort_by_key( |( row, column, _ )| ( *column, *row ) );
+    let pattern = entries.iter().all( |( _, _, weight )| weight.is_none() );
+    writeln!( writer, "%%MatrixMarket matrix coordinate {} {}",
+        if pattern { "pattern" } else { "real" },
+        if D::DIRECTED { "general" } else { "symmetric" }
+    )?;
+    writeln!( writer, "{} {} {}", nodes.len(), nodes.len(), entries.len() )?;
+    for ( row, column, weight ) in entries {
+        match ( pattern, weight ) {
+            ( true, _ ) => writeln!( writer, "{} {}", row, column )?,
+            ( false, weight ) => writeln!( writer, "{} {} {}", row, column, weight.unwrap_or( 1.0 ) )?
+        }
+    }
+    Ok( writer.flush()? )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::{ DynRepr, HashRepr },
+        traits::{ AddNode, AddEdge, IterNodeIds, IterEdgeIds }
+    };
+
+    use super::{ Error, read_edge_list, write_edge_list, read_metis, write_metis, read_matrix_market, write_matrix_market };
+
+    fn sorted<T: Ord>( mut values: Vec<T> ) -> Vec<T> {
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_edge_list() {
+        let text = "# SNAP style\n1 2\n2 3 0.5\n\n% other comment\n7 1 2\n";
+        let graph: Graph<Undirected, Cyclic, HashRepr<u64, (), f64>> = read_edge_list( text.as_bytes() ).unwrap();
+        assert_eq!( sorted( graph.iter_node_ids().map( |( id, _ )| id ).collect() ), vec![ 1, 2, 3, 7 ] );
+        assert_eq!( graph.iter_edge_ids().count(), 6 );
+
+        let directed: Graph<Directed, Cyclic, DynRepr<(), Option<f64>>> = read_edge_list( text.as_bytes() ).unwrap();
+        let mut written = Vec::new();
+        write_edge_list( &directed, &mut written ).unwrap();
+        assert_eq!( String::from_utf8( written ).unwrap(), "1 2 1\n2 3 0.5\n7 1 2\n" );
+        assert_eq!( directed.iter_node_ids().count(), 8 );
+
+        assert!( matches!(
+            read_edge_list::<Directed, Cyclic, DynRepr<(), Option<f64>>, usize, (), f64, _>( "1 2\n3 x\n".as_bytes() ),
+            Err( Error::Parse { line: 2, .. } )
+        ));
+    }
+
+    #[test]
+    fn test_metis() {
+        // Node and edge weights, with node 4 only adjacent to node 3.
+        let text = "% weighted\n4 4 11\n2 2 3 3 1\n1 1 3 3 2\n4 1 1 2 2 4 5\n7 3 5\n";
+        let graph: Graph<Undirected, Cyclic, DynRepr<u32, Option<u32>>> = read_metis( text.as_bytes() ).unwrap();
+        assert_eq!( graph.iter_node_ids().map( |( _, node )| *node ).collect::<Vec<_>>(), vec![ 2, 1, 4, 7 ] );
+        assert_eq!( graph.iter_edge_ids().collect::<Vec<_>>(), vec![
+            ( 0, 1, &3 ), ( 0, 2, &1 ), ( 1, 0, &3 ), ( 1, 2, &2 ), ( 2, 0, &1 ), ( 2, 1, &2 ), ( 2, 3, &5 ), ( 3, 2, &5 )
+        ]);
+
+        let mut written = Vec::new();
+        write_metis( &graph, &mut written ).unwrap();
+        assert_eq!( String::from_utf8( written.clone() ).unwrap(), "4 4 11\n2 2 3 3 1\n1 1 3 3 2\n4 1 1 2 2 4 5\n7 3 5\n" );
+        let reread: Graph<Undirected, Cyclic, DynRepr<u32, Option<u32>>> = read_metis( written.as_slice() ).unwrap();
+        assert_eq!( reread.iter_edge_ids().collect::<Vec<_>>(), graph.iter_edge_ids().collect::<Vec<_>>() );
+
+        let unweighted = "3 2\n2\n1 3\n2\n";
+        let graph: Graph<Undirected, Cyclic, HashRepr<usize, (), ()>> = read_metis( unweighted.as_bytes() ).unwrap();
+        assert_eq!( sorted( graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect() ), vec![ ( 0, 1 ), ( 1, 0 ), ( 1, 2 ), ( 2, 1 ) ] );
+        assert!( matches!( read_metis::<Undirected, Cyclic, DynRepr<(), Option<()>>, (), (), _>( "3 3\n2\n1 3\n2\n".as_bytes() ), Err( Error::Parse { .. } ) ) );
+    }
+
+    #[test]
+    fn test_matrix_market() {
+        let text = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n3 3 3\n1 1 4.0\n2 1 -1.5\n3 2 2\n";
+        let graph: Graph<Directed, Cyclic, DynRepr<(), Option<f64>>> = read_matrix_market( text.as_bytes() ).unwrap();
+        assert_eq!( sorted( graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect() ), vec![ ( 0, 0 ), ( 0, 1 ), ( 1, 0 ), ( 1, 2 ), ( 2, 1 ) ] );
+
+        let undirected: Graph<Undirected, Cyclic, DynRepr<(), Option<f64>>> = read_matrix_market( text.as_bytes() ).unwrap();
+        let mut written = Vec::new();
+        write_matrix_market( &undirected, &mut written ).unwrap();
+        assert_eq!( String::from_utf8( written ).unwrap(), "%%MatrixMarket matrix coordinate real symmetric\n3 3 3\n1 1 4\n2 1 -1.5\n3 2 2\n" );
+
+        let pattern = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 2\n";
+        let graph: Graph<Directed, Cyclic, DynRepr<(), Option<()>>> = read_matrix_market( pattern.as_bytes() ).unwrap();
+        assert_eq!( graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect::<Vec<_>>(), vec![ ( 0, 1 ) ] );
+        let mut written = Vec::new();
+        write_matrix_market( &graph, &mut written ).unwrap();
+        assert_eq!( String::from_utf8( written ).unwrap(), pattern );
+
+        let rectangular = "%%MatrixMarket matrix coordinate real general\n2 3 0\n";
+        assert!( matches!( read_matrix_market::<Directed, Cyclic, DynRepr<(), Option<f64>>, (), f64, _>( rectangular.as_bytes() ), Err( Error::Unsupported( _ ) ) ) );
+    }
+
+    #[test]
+    fn test_write_unsupported() {
+        let mut dangling = Graph::<Undirected, Cyclic, HashRepr<u32, (), ()>>::default();
+        dangling.add_node( 1, () );
+        dangling.add_edge( 1, 2, () );
+        assert!( matches!( write_metis( &dangling, Vec::new() ), Err( Error::Unsupported( _ ) ) ) );
+        assert!( matches!( write_matrix_market( &dangling, Vec::new() ), Err( Error::Unsupported( _ ) ) ) );
+
+        let mut looped = Graph::<Undirected, Cyclic, HashRepr<u32, (), ()>>::default();
+        for id in [ 1, 2 ] {
+            looped.add_node( id, () );
+        }
+        for ( id1, id2 ) in [ ( 1, 2 ), ( 2, 1 ), ( 2, 2 ) ] {
+            looped.add_edge( id1, id2, () );
+        }
+        assert!( matches!( write_metis( &looped, Vec::new() ), Err( Error::Unsupported( _ ) ) ) );
+        let mut written = Vec::new();
+        write_matrix_market( &looped, &mut written ).unwrap();
+        assert_eq!( String::from_utf8( written ).unwrap(), "%%MatrixMarket matrix coordinate pattern symmetric\n2 2 2\n2 1\n2 2\n" );
+    }
+}
@@ -0,0 +1,538 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    collections::BTreeSet,
+    marker::PhantomData
+};
+
+use crate::{
+    graph::{
+        Graph,
+        Directed,
+        Directional,
+        Cyclical
+    },
+    graph_repr::GraphRepr,
+    traits::{
+        GetNode,
+        GetEdge,
+        ContainsNode,
+        ContainsEdge,
+        IterNodes,
+        IterEdges,
+        IterNodeIds,
+        IterEdgeIds,
+        IterAdjacent,
+        Order,
+        Size
+    }
+};
+
+/// Decides which nodes a [`View`] keeps.
+pub trait NodeFilter<I, N> {
+    fn keep_node( &self, id: &I, node: &N ) -> bool;
+}
+
+/// Decides which edges a [`View`] keeps, given them in the view's direction.
+pub trait EdgeFilter<I, E> {
+    fn keep_edge( &self, id1: &I, id2: &I, edge: &E ) -> bool;
+}
+
+/// The filter keeping everything.
+#[derive( Debug, Clone, Copy, Default )]
+pub struct All;
+
+impl<I, N> NodeFilter<I, N> for All {
+    fn keep_node( &self, _: &I, _: &N ) -> bool {
+        true
+    }
+}
+
+impl<I, E> EdgeFilter<I, E> for All {
+    fn keep_edge( &self, _: &I, _: &I, _: &E ) -> bool {
+        true
+    }
+}
+
+impl<I, N, F> NodeFilter<I, N> for F
+where
+    F: Fn( &I, &N ) -> bool
+{
+    fn keep_node( &self, id: &I, node: &N ) -> bool {
+        self( id, node )
+    }
+}
+
+impl<I, E, F> EdgeFilter<I, E> for F
+where
+    F: Fn( &I, &I, &E ) -> bool
+{
+    fn keep_edge( &self, id1: &I, id2: &I, edge: &E ) -> bool {
+        self( id1, id2, edge )
+    }
+}
+
+/// Two filters that must both accept, built by chaining filters on a [`View`].
+#[derive( Debug, Clone, Copy )]
+pub struct Both<F1, F2>( F1, F2 );
+
+impl<I, N, F1, F2> NodeFilter<I, N> for Both<F1, F2>
+where
+    F1: NodeFilter<I, N>,
+    F2: NodeFilter<I, N>
+{
+    fn keep_node( &self, id: &I, node: &N ) -> bool {
+        self.0.keep_node( id, node ) && self.1.keep_node( id, node )
+    }
+}
+
+impl<I, E, F1, F2> EdgeFilter<I, E> for Both<F1, F2>
+where
+    F1: EdgeFilter<I, E>,
+    F2: EdgeFilter<I, E>
+{
+    fn keep_edge( &self, id1: &I, id2: &I, edge: &E ) -> bool {
+        self.0.keep_edge( id1, id2, edge ) && self.1.keep_edge( id1, id2, edge )
+    }
+}
+
+/// The nodes of a set, as a [`NodeFilter`] for [`Graph::induced_subgraph`].
+#[derive( Debug, Clone )]
+pub struct NodeSet<I>( BTreeSet<I> );
+
+impl<I, N> NodeFilter<I, N> for NodeSet<I>
+where
+    I: Ord
+{
+    fn keep_node( &self, id: &I, _: &N ) -> bool {
+        self.0.contains( id )
+    }
+}
+
+/// A read-only view of a [`Graph`] that hides nodes and edges and may reverse edges, without copying anything.
+///
+/// A view keeps the nodes its node filter accepts and the edges between kept nodes its edge filter accepts. It
+/// implements the same read traits as a graph, so algorithms written against them run on views unchanged. Filters
+/// compose, e.g. to follow only enabled edges of a subgraph:
+///
+/// ```ignore
+/// let enabled = graph.induced_subgraph( [ 1, 2, 3 ] ).filter_edges( |_: &u32, _: &u32, enabled: &bool| *enabled );
+/// ```
+///
+#[derive( Debug, Clone, Copy )]
+pub struct View<'a, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr
+{
+    graph: &'a Graph<D, C, R>,
+    nodes: NF,
+    edges: EF,
+    reversed: bool,
+    node: PhantomData<fn() -> ( I, N )>
+}
+
+impl<D, C, R> Graph<D, C, R>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr
+{
+    /// A view of the whole graph.
+    pub fn view<I, N>( &self ) -> View<'_, D, C, R, I, N, All, All> {
+        View { graph: self, nodes: All, edges: All, reversed: false, node: PhantomData }
+    }
+
+    /// The subgraph induced by `nodes`: those nodes and every edge between them.
+    pub fn induced_subgraph<I, N, S>( &self, nodes: S ) -> View<'_, D, C, R, I, N, NodeSet<I>, All>
+    where
+        I: Ord,
+        S: IntoIterator<Item = I>
+    {
+        View { graph: self, nodes: NodeSet( nodes.into_iter().collect() ), edges: All, reversed: false, node: PhantomData }
+    }
+
+    pub fn filter_nodes<I, N, F>( &self, filter: F ) -> View<'_, D, C, R, I, N, F, All> {
+        View { graph: self, nodes: filter, edges: All, reversed: false, node: PhantomData }
+    }
+
+    pub fn filter_edges<I, N, F>( &self, filter: F ) -> View<'_, D, C, R, I, N, All, F> {
+        View { graph: self, nodes: All, edges: filter, reversed: false, node: PhantomData }
+    }
+}
+
+impl<C, R> Graph<Directed, C, R>
+where
+    C: Cyclical,
+    R: GraphRepr
+{
+    /// A view with every edge pointing the other way.
+    pub fn reversed<I, N>( &self ) -> View<'_, Directed, C, R, I, N, All, All> {
+        self.view().reversed()
+    }
+}
+
+impl<'a, D, C, R, I, N, NF, EF> View<'a, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr
+{
+    /// Keeps only the nodes `filter` also accepts.
+    pub fn filter_nodes<F>( self, filter: F ) -> View<'a, D, C, R, I, N, Both<NF, F>, EF> {
+        View { graph: self.graph, nodes: Both( self.nodes, filter ), edges: self.edges, reversed: self.reversed, node: PhantomData }
+    }
+
+    /// Keeps only the edges `filter` also accepts.
+    pub fn filter_edges<F>( self, filter: F ) -> View<'a, D, C, R, I, N, NF, Both<EF, F>> {
+        View { graph: self.graph, nodes: self.nodes, edges: Both( self.edges, filter ), reversed: self.reversed, node: PhantomData }
+    }
+
+    /// The graph being viewed.
+    pub fn graph( &self ) -> &'a Graph<D, C, R> {
+        self.graph
+    }
+
+    pub fn is_directed( &self ) -> bool {
+        D::DIRECTED
+    }
+}
+
+impl<'a, C, R, I, N, NF, EF> View<'a, Directed, C, R, I, N, NF, EF>
+where
+    C: Cyclical,
+    R: GraphRepr
+{
+    /// Reverses every edge.
+    ///
+    /// Every edge filter of the view sees the edges in the reversed direction, including filters added before.
+    ///
+    pub fn reversed( self ) -> Self {
+        View { reversed: !self.reversed, ..self }
+    }
+}
+
+impl<D, C, R, I, N, NF, EF> GetNode<I, N> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N>,
+    NF: NodeFilter<I, N>
+{
+    fn node( &self, id: I ) -> Option<&N> {
+        self.graph.node( id.clone() ).filter( |node| self.nodes.keep_node( &id, node ) )
+    }
+}
+
+impl<D, C, R, I, N, E, NF, EF> GetEdge<I, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N> + GetEdge<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    fn edge( &self, id1: I, id2: I ) -> Option<&E> {
+        if self.node( id1.clone() ).is_none() || self.node( id2.clone() ).is_none() {
+            return None;
+        }
+        let edge = match self.reversed {
+            true => self.graph.edge( id2.clone(), id1.clone() ),
+            false => self.graph.edge( id1.clone(), id2.clone() )
+        };
+        edge.filter( |edge| self.edges.keep_edge( &id1, &id2, edge ) )
+    }
+}
+
+impl<D, C, R, I, N, NF, EF> ContainsNode<I, N> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N>,
+    NF: NodeFilter<I, N>
+{
+    fn contains_node( &self, id: I ) -> bool {
+        self.node( id ).is_some()
+    }
+}
+
+impl<D, C, R, I, N, E, NF, EF> ContainsEdge<I, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N> + GetEdge<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    fn contains_edge( &self, id1: I, id2: I ) -> bool {
+        self.edge( id1, id2 ).is_some()
+    }
+}
+
+impl<D, C, R, I, N, NF, EF> IterNodeIds<I, N> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    Graph<D, C, R>: IterNodeIds<I, N>,
+    NF: NodeFilter<I, N>
+{
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, &'a N )>
+    where
+        N: 'a
+    {
+        self.graph.iter_node_ids().filter( |( id, node )| self.nodes.keep_node( id, node ) )
+    }
+}
+
+impl<D, C, R, I, N, E, NF, EF> IterEdgeIds<I, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N> + IterEdgeIds<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, I, &'a E )>
+    where
+        E: 'a
+    {
+        self.graph.iter_edge_ids()
+            .map( |( id1, id2, edge )| if self.reversed { ( id2, id1, edge ) } else { ( id1, id2, edge ) } )
+            .filter( |( id1, id2, edge )| self.node( id1.clone() ).is_some()
+                && self.node( id2.clone() ).is_some()
+                && self.edges.keep_edge( id1, id2, edge )
+            )
+    }
+}
+
+impl<D, C, R, I, N, NF, EF> IterNodes<N> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    Graph<D, C, R>: IterNodeIds<I, N>,
+    NF: NodeFilter<I, N>
+{
+    fn iter_nodes<'a>( &'a self ) -> impl Iterator<Item = Option<&'a N>>
+    where
+        N: 'a
+    {
+        self.iter_node_ids().map( |( _, node )| Some( node ) )
+    }
+}
+
+/// The edges leaving `id` in the view, found through the graph's adjacency.
+///
+/// Representations only index the edges leaving a node, so in a reversed view the edges entering `id` are looked up
+/// from every kept node.
+///
+impl<D, C, R, I, N, E, NF, EF> IterAdjacent<I, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N> + GetEdge<I, E> + IterNodeIds<I, N> + IterAdjacent<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    fn iter_adjacent<'a>( &'a self, id: I ) -> impl Iterator<Item = ( I, &'a E )>
+    where
+        E: 'a
+    {
+        let kept = self.node( id.clone() ).is_some();
+        let outgoing = ( kept && !self.reversed ).then( || self.graph.iter_adjacent( id.clone() ) );
+        let incoming = ( kept && self.reversed ).then( || {
+            let id = id.clone();
+            self.iter_node_ids().filter_map( move |( next_id, _ )|
+                self.graph.edge( next_id.clone(), id.clone() ).map( |edge| ( next_id, edge ) )
+            )
+        });
+        outgoing.into_iter().flatten()
+            .chain( incoming.into_iter().flatten() )
+            .filter( move |( next_id, edge )| self.node( next_id.clone() ).is_some() && self.edges.keep_edge( &id, next_id, edge ) )
+    }
+}
+
+impl<D, C, R, I, N, E, NF, EF> IterEdges<I, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone,
+    Graph<D, C, R>: GetNode<I, N> + GetEdge<I, E> + IterNodeIds<I, N> + IterAdjacent<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    /// The edges leaving `id` in the view, see [`IterAdjacent`].
+    fn iter_edges<'a>( &'a self, id: I ) -> impl Iterator<Item = Option<&'a E>>
+    where
+        E: 'a
+    {
+        self.iter_adjacent( id ).map( |( _, edge )| Some( edge ) )
+    }
+}
+
+impl<D, C, R, I, N, E, NF, EF> Order<I, N, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    Graph<D, C, R>: IterNodeIds<I, N> + Order<I, N, E>,
+    NF: NodeFilter<I, N>
+{
+    fn order( &self ) -> usize {
+        self.iter_node_ids().count()
+    }
+}
+
+/// Undirected edges are stored in both directions and count once, like in the viewed graph.
+impl<D, C, R, I, N, E, NF, EF> Size<I, N, E> for View<'_, D, C, R, I, N, NF, EF>
+where
+    D: Directional,
+    C: Cyclical,
+    R: GraphRepr,
+    I: Clone + PartialEq,
+    Graph<D, C, R>: GetNode<I, N> + IterEdgeIds<I, E>,
+    NF: NodeFilter<I, N>,
+    EF: EdgeFilter<I, E>
+{
+    fn size( &self ) -> usize {
+        let ( edges, loops ) = self.iter_edge_ids().fold( ( 0, 0 ), |( edges, loops ), ( id1, id2, _ )|
+            ( edges + 1, loops + usize::from( id1 == id2 ) )
+        );
+        match D::DIRECTED {
+            true => edges,
+            false => ( edges + loops ) / 2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        graph::{ Graph, Directed, Undirected, Cyclic },
+        graph_repr::BTreeRepr,
+        traits::{ AddNode, AddEdge, GetNode, GetEdge, IterNodes, IterEdges, IterNodeIds, IterEdgeIds, IterAdjacent, Order, Size }
+    };
+
+    type Flow = Graph<Directed, Cyclic, BTreeRepr<u32, i32, bool>>;
+
+    fn flow() -> Flow {
+        let mut graph = Flow::default();
+        for id in 1..=4 {
+            graph.add_node( id, id as i32 * 10 );
+        }
+        graph.add_edge( 1, 2, true );
+        graph.add_edge( 2, 3, false );
+        graph.add_edge( 3, 4, true );
+        graph.add_edge( 4, 1, true );
+        graph
+    }
+
+    fn edges<G>( graph: &G ) -> Vec<( u32, u32 )>
+    where
+        G: IterEdgeIds<u32, bool>
+    {
+        graph.iter_edge_ids().map( |( id1, id2, _ )| ( id1, id2 ) ).collect()
+    }
+
+    #[test]
+    fn test_views() {
+        let graph = flow();
+
+        let induced = graph.induced_subgraph( [ 1, 2, 3 ] );
+        assert_eq!( induced.node( 4 ), None );
+        assert_eq!( induced.node( 2 ), Some( &20 ) );
+        assert_eq!( edges( &induced ), vec![ ( 1, 2 ), ( 2, 3 ) ] );
+        assert_eq!( induced.edge( 3, 4 ), None );
+
+        let enabled = induced.filter_edges( |_: &u32, _: &u32, enabled: &bool| *enabled );
+        assert_eq!( edges( &enabled ), vec![ ( 1, 2 ) ] );
+        assert_eq!( enabled.iter_edges( 2 ).count(), 0 );
+
+        let heavy = graph.filter_nodes( |_: &u32, node: &i32| *node >= 20 );
+        assert_eq!( heavy.iter_node_ids().map( |( id, _ )| id ).collect::<Vec<_>>(), vec![ 2, 3, 4 ] );
+        assert_eq!( edges( &heavy ), vec![ ( 2, 3 ), ( 3, 4 ) ] );
+
+        let reversed = graph.reversed();
+        assert_eq!( edges( &reversed ), vec![ ( 2, 1 ), ( 3, 2 ), ( 4, 3 ), ( 1, 4 ) ] );
+        assert_eq!( reversed.edge( 2, 1 ), Some( &true ) );
+        assert_eq!( reversed.edge( 1, 2 ), None );
+        assert_eq!( edges( &reversed.filter_edges( |id1: &u32, _: &u32, _: &bool| *id1 == 1 ) ), vec![ ( 1, 4 ) ] );
+    }
+
+    #[test]
+    fn test_view_adjacency() {
+        let graph = flow();
+
+        let view = graph.view();
+        assert_eq!( view.iter_adjacent( 1 ).collect::<Vec<_>>(), vec![ ( 2, &true ) ] );
+        assert_eq!( view.iter_edges( 2 ).collect::<Vec<_>>(), vec![ Some( &false ) ] );
+
+        let reversed = graph.reversed();
+        assert_eq!( reversed.iter_adjacent( 1 ).collect::<Vec<_>>(), vec![ ( 4, &true ) ] );
+        assert_eq!( reversed.iter_adjacent( 3 ).collect::<Vec<_>>(), vec![ ( 2, &false ) ] );
+        assert_eq!( reversed.iter_edges( 2 ).count(), 1 );
+
+        let induced = graph.induced_subgraph( [ 1, 2, 4 ] );
+        assert_eq!( induced.iter_adjacent( 4 ).collect::<Vec<_>>(), vec![ ( 1, &true ) ] );
+        assert_eq!( induced.iter_adjacent( 3 ).count(), 0 );
+        let reversed = induced.reversed();
+        assert_eq!( reversed.iter_adjacent( 1 ).collect::<Vec<_>>(), vec![ ( 4, &true ) ] );
+        assert_eq!( reversed.iter_adjacent( 2 ).collect::<Vec<_>>(), vec![ ( 1, &true ) ] );
+        assert_eq!( reversed.iter_adjacent( 4 ).count(), 0 );
+    }
+
+    #[test]
+    fn test_view_filter_before_reversed() {
+        let graph = flow();
+
+        // Filters see the view's direction, even when added before the view was reversed.
+        let from_one = graph.view().filter_edges( |id1: &u32, _: &u32, _: &bool| *id1 == 1 );
+        assert_eq!( edges( &from_one ), vec![ ( 1, 2 ) ] );
+        let reversed = from_one.reversed();
+        assert_eq!( edges( &reversed ), vec![ ( 1, 4 ) ] );
+        assert_eq!( reversed.edge( 1, 4 ), Some( &true ) );
+        assert_eq!( reversed.edge( 2, 1 ), None );
+        assert_eq!( reversed.iter_adjacent( 1 ).collect::<Vec<_>>(), vec![ ( 4, &true ) ] );
+        assert_eq!( reversed.iter_adjacent( 2 ).count(), 0 );
+        assert_eq!( edges( &reversed.reversed() ), vec![ ( 1, 2 ) ] );
+    }
+
+    #[test]
+    fn test_view_order_and_size() {
+        let graph = flow();
+        let view = graph.view();
+        assert_eq!( ( view.order(), view.size() ), ( 4, 4 ) );
+        assert_eq!( view.iter_nodes().flatten().copied().collect::<Vec<_>>(), vec![ 10, 20, 30, 40 ] );
+
+        let heavy = graph.filter_nodes( |_: &u32, node: &i32| *node >= 20 ).filter_edges( |_: &u32, _: &u32, enabled: &bool| *enabled );
+        assert_eq!( ( heavy.order(), heavy.size() ), ( 3, 1 ) );
+        assert_eq!( heavy.iter_nodes().flatten().copied().collect::<Vec<_>>(), vec![ 20, 30, 40 ] );
+
+        let mut graph = Graph::<Undirected, Cyclic, BTreeRepr<u32, i32, bool>>::default();
+        for id in 1..=3 {
+            graph.add_node( id, 0 );
+        }
+        for ( id1, id2 ) in [ ( 1, 2 ), ( 2, 3 ), ( 3, 3 ) ] {
+            graph.add_edge( id1, id2, true );
+            graph.add_edge( id2, id1, true );
+        }
+        assert_eq!( graph.view().size(), 3 );
+        assert_eq!( graph.induced_subgraph( [ 2, 3 ] ).size(), 2 );
+        assert_eq!( graph.induced_subgraph( [ 2, 3 ] ).iter_adjacent( 3 ).map( |( id, _ )| id ).collect::<Vec<_>>(), vec![ 2, 3 ] );
+    }
+}
@@ -1,12 +1,5 @@
 
-use std::{
-    ops::{ Deref, DerefMut },
-    collections::{ BTreeMap, HashMap }
-};
-
-use linear_algebra::{
-    matrix::Matrix, traits::Fillable, vector::Vector
-};
+use std::collections::{ BTreeMap, HashMap };
 
 #[derive( Clone, Copy, Debug, Default )]
 pub struct NodeRepr<N, A> {
@@ -20,6 +13,7 @@ pub trait GraphRepr {}
 ///
 /// This representation is useful for graphs with a fixed number of nodes.
 ///
+#[derive( Debug, Clone, Copy )]
 pub struct StaticRepr<N, E, const SIZE: usize>( pub(crate) [ NodeRepr<N, [ Option<E>; SIZE ]>; SIZE ] )
 where
     N: 'static + Clone + Copy + Default + std::fmt::Debug,
@@ -45,7 +39,9 @@ where
 /// A dynamic graph representation with variable size.
 ///
 /// This representation is useful for graphs with a variable number of nodes.
+/// Each node keeps one adjacency slot per node, so edges are stored as `Option<E>` and an absent edge is `None`.
 ///
+#[derive( Debug )]
 pub struct DynRepr<N, E> ( pub(crate) Vec<NodeRepr<N, Vec<E>>> );
 
 impl<N, E> GraphRepr for DynRepr<N, E>
@@ -64,13 +60,10 @@ impl<N, E> Default for DynRepr<N, E> {
 ///
 /// This representation is useful for graphs with a variable number of nodes.
 ///
+#[derive( Debug )]
 pub struct HashRepr<I, N, E> ( pub(crate) HashMap<I, NodeRepr<N, HashMap<I, E>>> );
 
-impl<I, N, E> GraphRepr for HashRepr<I, N, E>
-where
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
-{}
+impl<I, N, E> GraphRepr for HashRepr<I, N, E> {}
 
 impl<I, N, E> Default for HashRepr<I, N, E>
 where
@@ -85,13 +78,10 @@ where
 ///
 /// This representation is useful for graphs with a variable number of nodes.
 ///
+#[derive( Debug )]
 pub struct BTreeRepr<I, N, E> ( pub(crate) BTreeMap<I, NodeRepr<N, BTreeMap<I, E>>> );
 
-impl<I, N, E> GraphRepr for BTreeRepr<I, N, E>
-where
-    N: Clone + Copy + Default + std::fmt::Debug,
-    E: Clone + Copy + Default + std::fmt::Debug
-{}
+impl<I, N, E> GraphRepr for BTreeRepr<I, N, E> {}
 
 impl<I, N, E> Default for BTreeRepr<I, N, E>
 where
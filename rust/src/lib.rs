@@ -5,7 +5,7 @@
 #![feature(generic_const_exprs)]
 #![feature(min_specialization)]
 
-//#![feature(mapped_lock_guards)]
+#![cfg_attr(feature = "unstable", feature(mapped_lock_guards))]
 
 //#![deny(clippy::unwrap_used)]
 //#![deny(clippy::expect_used)]
@@ -17,5 +17,6 @@ pub mod graph_repr;
 pub mod graph;
 //pub mod undirected_graph;
 //pub mod directed_graph;
-//pub mod function_graph;
-//pub mod async_function_graph;
+pub mod function_graph;
+pub mod async_function_graph;
+pub mod mixed_function_graph;
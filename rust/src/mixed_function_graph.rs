@@ -0,0 +1,264 @@
+// Copyright 2024 Bewusstsein Labs
+
+use std::{
+    hash::Hash,
+    pin::Pin,
+    future::Future,
+    sync::Arc,
+    time::Duration
+};
+
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        Graph,
+        Directed,
+        Cyclic
+    },
+    graph_repr::BTreeRepr,
+    traits::{
+        GetNode,
+        AddNode,
+        AddEdge,
+        IterAdjacent
+    },
+    function_graph::{
+        variable::{ Variable, Variables },
+        function::FunctionResult,
+        operation::Operation
+    },
+    async_function_graph::{
+        operation::{ AsyncOperation, Error as AsyncOperationError },
+        cancellation::CancellationToken,
+        runner::{ BoxFuture, Runnable, Spawner, Started }
+    }
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Operation not found")]
+    OperationNotFound
+}
+
+/// A node of a [`MixedFnGraph`], either a blocking or an async operation.
+#[derive( Debug )]
+pub enum MixedOperation<I> {
+    Sync( Operation<I> ),
+    Async( AsyncOperation<I> )
+}
+
+impl<I> MixedOperation<I>
+where
+    I: Ord + Hash
+{
+    pub fn variables( &self ) -> &Variables<I> {
+        match self {
+            Self::Sync( operation ) => operation.variables(),
+            Self::Async( operation ) => operation.variables()
+        }
+    }
+
+    pub fn is_async( &self ) -> bool {
+        matches!( self, Self::Async( _ ) )
+    }
+
+    /// How long a runner lets the operation execute. Only async operations set their own timeout.
+    pub fn timeout( &self ) -> Option<Duration> {
+        match self {
+            Self::Sync( _ ) => None,
+            Self::Async( operation ) => operation.timeout()
+        }
+    }
+}
+
+impl<I> From<Operation<I>> for MixedOperation<I> {
+    fn from( operation: Operation<I> ) -> Self {
+        Self::Sync( operation )
+    }
+}
+
+impl<I> From<AsyncOperation<I>> for MixedOperation<I> {
+    fn from( operation: AsyncOperation<I> ) -> Self {
+        Self::Async( operation )
+    }
+}
+
+impl<I> PartialEq for MixedOperation<I>
+where
+    I: 'static + Ord + Hash
+{
+    fn eq( &self, other: &Self ) -> bool {
+        match ( self, other ) {
+            ( Self::Sync( operation ), Self::Sync( other ) ) => operation == other,
+            ( Self::Async( operation ), Self::Async( other ) ) => operation == other,
+            _ => false
+        }
+    }
+}
+
+/// A function graph whose nodes may be blocking or async operations.
+///
+/// Executed by a [`Runner`](crate::async_function_graph::runner::Runner), which runs async operations on its
+/// spawner and blocking operations through [`Spawner::spawn_blocking`], so neither holds up the other. A blocking
+/// operation that times out keeps its blocking thread until it returns, but its result is discarded and the default
+/// blocking pool starts a replacement thread meanwhile.
+///
+pub type MixedFnGraph<I, J> = Graph<Directed, Cyclic, BTreeRepr<I, MixedOperation<J>, bool>>;
+
+impl<I, J> MixedFnGraph<I, J>
+where
+    I: Clone + Ord,
+    J: 'static + Clone + Ord + Hash
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_operation<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
+    where
+        F: 'static + Fn( &Variables<J> ) -> FunctionResult + Send + Sync
+    {
+        self.add_node( id, MixedOperation::Sync( Operation::new(
+            variables,
+            function
+        )));
+        Ok( () )
+    }
+
+    pub fn add_async_operation<const N: usize, F>( &mut self, id: I, variables: [ ( J, Variable ); N ], function: F ) -> Result<(), Error>
+    where
+        F: for<'b> Fn(&'b Variables<J>) -> Pin<Box<dyn Future<Output = FunctionResult> + Send + 'b>>
+        + Send
+        + Sync
+        + 'static,
+    {
+        self.add_node( id, MixedOperation::Async( AsyncOperation::new(
+            variables,
+            function
+        )));
+        Ok( () )
+    }
+
+    /// Adds an edge between two operations, failing if either of them is missing.
+    pub fn add_edge( &mut self, id1: I, id2: I, enabled: bool ) -> Result<(), Error> {
+        if self.node( id1.clone() ).is_none() || self.node( id2.clone() ).is_none() {
+            return Err( Error::OperationNotFound );
+        }
+        AddEdge::add_edge( self, id1, id2, enabled );
+        Ok( () )
+    }
+
+    pub(crate) fn enabled_successors( &self, id: &I ) -> Vec<I> {
+        self.iter_adjacent( id.clone() )
+            .filter( |( _, edge )| **edge )
+            .map( |( next_id, _ )| next_id )
+            .collect()
+    }
+}
+
+impl<I, J> Runnable<I> for MixedFnGraph<I, J>
+where
+    I: 'static + Clone + Ord + Send + Sync,
+    J: 'static + Clone + Ord + Hash + Send + Sync
+{
+    type Key = J;
+
+    fn variables( &self, id: &I ) -> Option<&Variables<J>> {
+        self.node( id.clone() ).map( MixedOperation::variables )
+    }
+
+    fn successors( &self, id: &I ) -> Vec<I> {
+        self.enabled_successors( id )
+    }
+
+    fn timeout( &self, id: &I ) -> Option<Duration> {
+        self.node( id.clone() ).and_then( MixedOperation::timeout )
+    }
+
+    fn launch<S>( graph: Arc<Self>, spawner: &S, id: I, token: CancellationToken, started: Started ) -> BoxFuture<'static, Result<(), AsyncOperationError>>
+    where
+        S: Spawner
+    {
+        match graph.node( id.clone() ) {
+            Some( MixedOperation::Sync( _ ) ) => spawner.spawn_blocking( move || {
+                started();
+                match graph.node( id ) {
+                    Some( MixedOperation::Sync( operation ) ) => operation.execute().map( |_| () ).map_err( AsyncOperationError::from ),
+                    _ => Ok( () )
+                }
+            }),
+            Some( MixedOperation::Async( _ ) ) => Box::pin( async move {
+                started();
+                match graph.node( id ) {
+                    Some( MixedOperation::Async( operation ) ) => operation.execute_with_token( &token ).await,
+                    _ => Ok( () )
+                }
+            }),
+            None => Box::pin( async { Ok( () ) } )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        thread::{ self, ThreadId }
+    };
+
+    use crate::{
+        function_graph::{ variable::TypedVariable, trace::Trace },
+        async_function_graph::runner::{ Inline, Runner, tests::block_on },
+        mixed_function_graph::MixedFnGraph
+    };
+
+    #[test]
+    fn test_mixed_graph() {
+        let a = TypedVariable::shared( 2 );
+        let b = TypedVariable::shared( 0 );
+        let c = TypedVariable::shared( 0 );
+        let blocking = TypedVariable::<Option<ThreadId>>::shared( None );
+        let mut graph = MixedFnGraph::<char, char>::new();
+
+        graph.add_operation( 'a',
+            [ ( 'a', a.clone().into() ), ( 'b', b.clone().into() ), ( 't', blocking.clone().into() ) ],
+            |variables| {
+                *variables.get_typed_mut::<i32>( &'b' )? = *variables.get_typed::<i32>( &'a' )? * 10;
+                *variables.get_typed_mut::<Option<ThreadId>>( &'t' )? = Some( thread::current().id() );
+                Ok( () )
+            }
+        ).unwrap();
+        graph.add_async_operation( 'b',
+            [ ( 'b', b.clone().into() ), ( 'c', c.clone().into() ) ],
+            |variables| Box::pin( async move {
+                let b = *variables.get_typed::<i32>( &'b' )?;
+                *variables.get_typed_mut::<i32>( &'c' )? = b + 1;
+                Ok( () )
+            })
+        ).unwrap();
+        graph.add_edge( 'a', 'b', true ).unwrap();
+
+        let report = block_on( Runner::new( Inline ).run( Arc::new( graph ), 'a' ) );
+        assert!( report.is_success() );
+        assert_eq!( *c.read(), 21 );
+        assert!( blocking.read().is_some_and( |id| id != thread::current().id() ) );
+    }
+
+    #[test]
+    fn test_mixed_graph_trace_records_blocking_thread() {
+        let blocking = TypedVariable::<Option<ThreadId>>::shared( None );
+        let mut graph = MixedFnGraph::<char, char>::new();
+        graph.add_operation( 'a', [ ( 't', blocking.clone().into() ) ], |variables| {
+            *variables.get_typed_mut::<Option<ThreadId>>( &'t' )? = Some( thread::current().id() );
+            Ok( () )
+        }).unwrap();
+
+        let trace = Arc::new( Trace::new() );
+        let report = block_on( Runner::new( Inline ).run_traced( Arc::new( graph ), 'a', trace.clone() ) );
+        assert!( report.is_success() );
+        let spans = trace.spans();
+        assert_eq!( spans.len(), 1 );
+        assert_eq!( Some( spans[ 0 ].thread ), *blocking.read() );
+    }
+}
@@ -21,7 +21,7 @@ pub trait AddNode<I, N> {
 }
 
 pub trait RemoveNode<I, N> {
-    fn remove_node( &mut self, id: I ) -> N;
+    fn remove_node( &mut self, id: I ) -> Option<N>;
 }
 
 pub trait AddEdge<I, E> {
@@ -29,7 +29,7 @@ pub trait AddEdge<I, E> {
 }
 
 pub trait RemoveEdge<I, E> {
-    fn remove_edge( &mut self, id1: I, id2: I ) -> E;
+    fn remove_edge( &mut self, id1: I, id2: I ) -> Option<E>;
 }
 
 pub trait ContainsNode<I, N> {
@@ -50,22 +50,57 @@ pub trait ClearEdges {
 
 pub trait IterNodes<N>
 {
-    fn iter_nodes( &self ) -> impl Iterator<Item = Option<&N>>;
+    fn iter_nodes<'a>( &'a self ) -> impl Iterator<Item = Option<&'a N>>
+    where
+        N: 'a;
 }
 
 pub trait IterNodesMut<N>
 {
-    fn iter_nodes_mut( &mut self ) -> impl Iterator<Item = Option<&mut N>>;
+    fn iter_nodes_mut<'a>( &'a mut self ) -> impl Iterator<Item = Option<&'a mut N>>
+    where
+        N: 'a;
 }
 
 pub trait IterEdges<I, E>
 {
-    fn iter_edges( &self, id: I ) -> impl Iterator<Item = Option<&E>>;
+    fn iter_edges<'a>( &'a self, id: I ) -> impl Iterator<Item = Option<&'a E>>
+    where
+        E: 'a;
 }
 
 pub trait IterEdgesMut<I, E>
 {
-    fn iter_edges_mut( &mut self, id: I ) -> impl Iterator<Item = Option<&mut E>>;
+    fn iter_edges_mut<'a>( &'a mut self, id: I ) -> impl Iterator<Item = Option<&'a mut E>>
+    where
+        E: 'a;
+}
+
+/// Iterates the nodes together with their ids.
+pub trait IterNodeIds<I, N>
+{
+    fn iter_node_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, &'a N )>
+    where
+        N: 'a;
+}
+
+/// Iterates every stored edge as `( from, to, edge )`.
+///
+/// Undirected representations store each edge in both directions, so it is yielded once per direction.
+///
+pub trait IterEdgeIds<I, E>
+{
+    fn iter_edge_ids<'a>( &'a self ) -> impl Iterator<Item = ( I, I, &'a E )>
+    where
+        E: 'a;
+}
+
+/// Iterates the edges leaving `id` together with the ids of the nodes they lead to.
+pub trait IterAdjacent<I, E>
+{
+    fn iter_adjacent<'a>( &'a self, id: I ) -> impl Iterator<Item = ( I, &'a E )>
+    where
+        E: 'a;
 }
 
 pub trait IterPair<'a, N, E>
@@ -88,50 +123,50 @@ pub trait IsComplete<I, N, E> {
     fn is_complete( &self ) -> bool;
 }
 
-pub trait IsEmpty<N, E> {
+pub trait IsEmpty<I, N, E> {
     fn is_empty( &self ) -> bool;
 }
 
-pub trait IsTrivial<N, E> {
+pub trait IsTrivial<I, N, E> {
     fn is_trivial( &self ) -> bool;
 }
 
-pub trait IsNull<N, E> {
+pub trait IsNull<I, N, E> {
     fn is_null( &self ) -> bool;
 }
 
-pub trait IsChildNode<N, E> {
+pub trait IsChildNode<I, N, E> {
     fn is_child_node( &self, node_1: I ) -> bool;
 }
 
-pub trait IsSubgraph<N, E> {
+pub trait IsSubgraph<I, N, E> {
     fn is_subgraph( &self, subgraph: &Self ) -> bool;
 }
 
-pub trait IsProperSubgraph<N, E> {
+pub trait IsProperSubgraph<I, N, E> {
     fn is_proper_subgraph( &self, subgraph: &Self ) -> bool;
 }
 
-pub trait IsImproperSubgraph<N, E> {
+pub trait IsImproperSubgraph<I, N, E> {
     fn is_improper_subgraph( &self, subgraph: &Self ) -> bool;
 }
 
-pub trait IsSpanningSubgraph<N, E> {
+pub trait IsSpanningSubgraph<I, N, E> {
     fn is_spanning_subgraph( &self, subgraph: &Self ) -> bool;
 }
 
-pub trait AreAdjacentNodes<N, E> {
+pub trait AreAdjacentNodes<I, N, E> {
     fn are_adjacent_nodes( &self, node_1: I, node_2: I ) -> bool;
 }
 
-pub trait AreAdjacentEdges<N, E> {
+pub trait AreAdjacentEdges<I, N, E> {
     fn are_adjacent_edges( &self, node_1: I, node_2: I, node_3: I ) -> bool;
 }
 
-pub trait Order<N, E> {
+pub trait Order<I, N, E> {
     fn order( &self ) -> usize;
 }
 
-pub trait Size<N, E> {
+pub trait Size<I, N, E> {
     fn size( &self ) -> usize;
 }